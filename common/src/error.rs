@@ -3,7 +3,12 @@ use std::{
     path::PathBuf,
 };
 
-use crate::token::Token;
+use crate::{
+    source_location::SourceLocation,
+    token::Token,
+    types::{Constraint, Type},
+    units::Unit,
+};
 
 pub enum CompilerError {
     FileIOError(PathBuf, std::io::Error),
@@ -13,13 +18,126 @@ pub enum CompilerError {
     NonExistentFileError,
     InvalidNumberError(String),
     ExpectedNumberError(usize, String),
+    UnterminatedStringError(usize, String),
+    InvalidEscapeError(char),
     ExpectedExpressionError,
     InvalidOperaterCharacter(char),
     FunctionNameNotFound,
     UnexpectedTokenError(Token),
+    InvalidAssignmentTargetError(String),
+    AssignmentToImmutableError(String),
+    UndefinedVariableError(String),
+    UndefinedFunctionError(String),
+    DefaultParameterOrderError(String, String),
+    PositionalAfterNamedArgumentError(String),
+    TooManyArgumentsError(String, usize, usize),
+    UnknownNamedArgumentError(String, String),
+    DuplicateArgumentError(String, String),
+    MissingArgumentError(String, String),
+    ArgumentCountError(usize, usize),
+    NamedArgumentToValueError(String),
+    LambdaDefaultParameterError(String),
+    DuplicateTypeParameterError(String, String),
+    DuplicateParameterError(String, String),
+    DuplicateFunctionError(String),
+    DuplicateBindingError(String),
+    NotAFunctionError(Type),
+    BlockWithoutValueError,
+    OutsideLoopError(String),
+    LoopValueError,
+    YieldOutsideFunctionError,
+    NotIterableError(Type),
+    PropagationOutsideHandlerError,
+    NotAResultError(Type),
+    UnknownTypeError(String),
+    DuplicateTypeError(String),
+    DuplicateFieldError(String, String),
+    RecursiveFieldError(String, String),
+    UnknownFieldError(String, String),
+    MissingFieldError(String, String),
+    NotAStructError(Type, String),
+    NotATupleError(Type, usize),
+    DuplicateVariantError(String),
+    UnknownVariantError(String),
+    PatternArityError(String, usize, usize),
+    TypeArgumentCountError(String, usize, usize),
+    NonExhaustiveMatchError(String),
+    UnreachableArmError,
+    TupleIndexOutOfBoundsError(usize, usize),
+    UnknownTupleTypeError(usize),
+    UnknownRecordTypeError(String),
+    TypeMismatchError(Type, Type),
+    TypeConflictError(Type, Type, SourceLocation),
+    UnsatisfiedConstraintError(Constraint, Type, SourceLocation),
+    ConflictingConstraintsError(Constraint, Constraint),
+    InfiniteTypeError(Type, Type),
+    NotAnArrayError(Type),
+    IndexOutOfBoundsError(i64, usize),
+    InvalidOperandTypesError(&'static str, Type, Type),
+    InvalidUnaryOperandTypeError(&'static str, Type),
+    DuplicateConstantError(String),
+    NonConstantExpressionError(String, String),
+    CircularConstantError(Vec<String>),
+    InvalidConstantError(String),
+    DivisionByZeroError,
+    ConstantOverflowError(i64, &'static str, i64),
+    ModuleNotFoundError(String, Vec<PathBuf>),
+    CircularImportError(Vec<String>),
+    ModuleNameMismatchError(String, String),
+    MisplacedModuleDeclarationError,
+    PrivateFunctionError(String, String),
+    AmbiguousImportError(String, String, String),
+    ImportConflictError(String, String),
+    ContractViolationError(String, String, SourceLocation),
+    PostconditionViolationError(String, String, SourceLocation),
+    DuplicateMacroError(String),
+    UndefinedMacroError(String),
+    DuplicateMacroVariableError(String, String),
+    UnknownMacroVariableError(String, String),
+    MacroArgumentsError(String, SourceLocation),
+    MacroRecursionError(String, usize),
+    InvalidUnitError(String),
+    NonDifferentiableError(String),
+    DerivativeParameterError(String),
+    MissingExternError(String, String),
+    UnitMismatchError(&'static str, Unit, Unit),
+    InconsistentUnitError(Unit, Unit),
+    UnitOverflowError(String),
+    UnknownAttributeError(String),
+    DuplicateAttributeError(String),
+    ConflictingAttributesError(String, String),
+    AttributeTargetError(String, &'static str),
+    AttributeArgumentError(String),
+    SideEffectError(String, &'static str, String),
+    LocatedError(SourceLocation, Box<CompilerError>),
+    ParsingFailedError(String, usize),
 }
 
 impl CompilerError {
+    /// Attach `location` to this error, unless it already carries a more precise one
+    pub fn with_location(self, location: &SourceLocation) -> CompilerError {
+        match self {
+            CompilerError::LocatedError(..) => self,
+            error => CompilerError::LocatedError(location.clone(), Box::new(error)),
+        }
+    }
+
+    /// Where the error was raised, if it carries a location
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            CompilerError::LocatedError(location, _) => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The underlying error with any source location stripped off
+    pub fn root_cause(&self) -> &CompilerError {
+        match self {
+            CompilerError::LocatedError(_, error) => error.root_cause(),
+            error => error,
+        }
+    }
+
     fn error_message(&self) -> String {
         match self {
             CompilerError::FileIOError(f, e) => {
@@ -53,6 +171,13 @@ impl CompilerError {
                 "Expected to find number expression, but none were found\nLine: {}\nFile: {}",
                 n, f,
             ),
+            CompilerError::UnterminatedStringError(n, f) => format!(
+                "String literal is missing its closing quote\nLine: {}\nFile: {}",
+                n, f,
+            ),
+            CompilerError::InvalidEscapeError(c) => {
+                format!("\\{} is not a valid escape sequence", c)
+            }
             CompilerError::ExpectedExpressionError => {
                 String::from("An expression was expected, but not found...")
             }
@@ -65,10 +190,375 @@ impl CompilerError {
             CompilerError::UnexpectedTokenError(token) => {
                 format!("Encountered unexpected token: {:?}", token)
             }
+            CompilerError::InvalidAssignmentTargetError(target) => {
+                format!(
+                    "Only mutable variables can be assigned to, found: {}",
+                    target
+                )
+            }
+            CompilerError::AssignmentToImmutableError(name) => {
+                format!(
+                    "Cannot assign to {}, it was not declared with var and is immutable",
+                    name
+                )
+            }
+            CompilerError::UndefinedVariableError(name) => {
+                format!("Use of undeclared variable: {}", name)
+            }
+            CompilerError::UndefinedFunctionError(name) => {
+                format!("Call to undefined function: {}", name)
+            }
+            CompilerError::DefaultParameterOrderError(function, parameter) => format!(
+                "Parameter {} of {} has no default value but follows one that does",
+                parameter, function
+            ),
+            CompilerError::PositionalAfterNamedArgumentError(function) => format!(
+                "Positional arguments cannot follow named arguments in call to {}",
+                function
+            ),
+            CompilerError::TooManyArgumentsError(function, expected, found) => format!(
+                "{} takes at most {} argument(s) but {} were given",
+                function, expected, found
+            ),
+            CompilerError::UnknownNamedArgumentError(function, argument) => {
+                format!("{} has no parameter named {}", function, argument)
+            }
+            CompilerError::DuplicateArgumentError(function, parameter) => format!(
+                "Parameter {} of {} was given more than one argument",
+                parameter, function
+            ),
+            CompilerError::MissingArgumentError(function, parameter) => format!(
+                "Call to {} is missing an argument for parameter {}",
+                function, parameter
+            ),
+            CompilerError::ArgumentCountError(expected, found) => format!(
+                "Function value takes {} argument(s) but {} were given",
+                expected, found
+            ),
+            CompilerError::NamedArgumentToValueError(argument) => format!(
+                "Named argument {} cannot be used when calling a function value, its parameter names are unknown",
+                argument
+            ),
+            CompilerError::LambdaDefaultParameterError(parameter) => format!(
+                "Parameter {} of an anonymous function cannot have a default value",
+                parameter
+            ),
+            CompilerError::DuplicateTypeParameterError(function, parameter) => format!(
+                "Type parameter {} of {} is declared more than once",
+                parameter, function
+            ),
+            CompilerError::DuplicateParameterError(function, parameter) => format!(
+                "Parameter {} of {} is declared more than once",
+                parameter, function
+            ),
+            CompilerError::DuplicateFunctionError(name) => {
+                format!("A function named {} has already been declared", name)
+            }
+            CompilerError::DuplicateBindingError(name) => {
+                format!("{} is bound more than once by the same pattern", name)
+            }
+            CompilerError::NotAFunctionError(found) => {
+                let [found] = readable([found]);
+                format!("Only functions can be called, found a value of type {}", found)
+            }
+            CompilerError::BlockWithoutValueError => {
+                String::from("A block must end with an expression that gives its value")
+            }
+            CompilerError::OutsideLoopError(keyword) => {
+                format!("{} can only be used inside a loop", keyword)
+            }
+            CompilerError::LoopValueError => String::from(
+                "Only a loop whose condition is always true can break with a value, any other loop can end without one",
+            ),
+            CompilerError::YieldOutsideFunctionError => String::from(
+                "yield can only be used in the body of a function defined with def",
+            ),
+            CompilerError::NotIterableError(found) => {
+                let [found] = readable([found]);
+                format!(
+                    "A for loop can only go over an array or a generator, found a value of type {}",
+                    found
+                )
+            }
+            CompilerError::PropagationOutsideHandlerError => String::from(
+                "? can only be used in the body of a function or of a try, which its errors are handed to",
+            ),
+            CompilerError::NotAResultError(found) => {
+                let [found] = readable([found]);
+                format!(
+                    "? can only be used on a result, found a value of type {}",
+                    found
+                )
+            }
+            CompilerError::UnknownTypeError(name) => format!("Unknown type: {}", name),
+            CompilerError::DuplicateTypeError(name) => {
+                format!("A type named {} has already been declared", name)
+            }
+            CompilerError::DuplicateFieldError(name, field) => {
+                format!("Field {} of {} is given more than once", field, name)
+            }
+            CompilerError::RecursiveFieldError(name, field) => format!(
+                "Field {} of {} holds a {} in turn, which only an array of them can do",
+                field, name, name
+            ),
+            CompilerError::UnknownFieldError(name, field) => {
+                format!("{} has no field named {}", name, field)
+            }
+            CompilerError::MissingFieldError(name, field) => {
+                format!("Construction of {} is missing a value for field {}", name, field)
+            }
+            CompilerError::NotAStructError(found, field) => {
+                let [found] = readable([found]);
+                format!(
+                    "Only structs have fields, found a value of type {} when reading field {}",
+                    found, field
+                )
+            }
+            CompilerError::DuplicateVariantError(name) => {
+                format!("A variant named {} has already been declared", name)
+            }
+            CompilerError::UnknownVariantError(name) => {
+                format!("{} is not a variant of any enum", name)
+            }
+            CompilerError::PatternArityError(name, expected, found) => format!(
+                "Variant {} carries {} value(s) but its pattern has {}",
+                name, expected, found
+            ),
+            CompilerError::TypeArgumentCountError(name, expected, found) => format!(
+                "{} takes {} type(s) in its brackets but was given {}",
+                name, expected, found
+            ),
+            CompilerError::NonExhaustiveMatchError(witness) => format!(
+                "Match does not cover every value, {} is not matched by any arm",
+                witness
+            ),
+            CompilerError::UnreachableArmError => String::from(
+                "This arm can never be reached, the arms before it match every value it does",
+            ),
+            CompilerError::NotATupleError(found, index) => {
+                let [found] = readable([found]);
+                format!(
+                    "Only tuples have numbered elements, found a value of type {} when reading element {}",
+                    found, index
+                )
+            }
+            CompilerError::TupleIndexOutOfBoundsError(index, length) => format!(
+                "Element {} is out of bounds for a tuple of {} element(s)",
+                index, length
+            ),
+            CompilerError::UnknownTupleTypeError(index) => format!(
+                "Cannot tell which tuple element {} is read from, annotate the value it is read from",
+                index
+            ),
+            CompilerError::UnknownRecordTypeError(field) => format!(
+                "Cannot tell which struct field {} belongs to, annotate the value it is read from",
+                field
+            ),
+            CompilerError::TypeMismatchError(expected, found) => {
+                let [expected, found] = readable([expected, found]);
+                format!(
+                    "Expected a value of type {} but found one of type {}",
+                    expected, found
+                )
+            }
+            CompilerError::TypeConflictError(expected, found, origin) => {
+                let [expected, found] = readable([expected, found]);
+                format!(
+                    "Expected a value of type {} but found one of type {}, conflicting with the type given at {}",
+                    expected, found, origin
+                )
+            }
+            CompilerError::UnsatisfiedConstraintError(constraint, found, origin) => {
+                let [found] = readable([found]);
+                format!(
+                    "Expected a {} value but found one of type {}, a {} value is required at {}",
+                    constraint, found, constraint, origin
+                )
+            }
+            CompilerError::ConflictingConstraintsError(first, second) => format!(
+                "No type can be both {} and {}",
+                first, second
+            ),
+            CompilerError::InfiniteTypeError(variable, t) => {
+                let [variable, t] = readable([variable, t]);
+                format!("Cannot construct the infinite type {} = {}", variable, t)
+            }
+            CompilerError::NotAnArrayError(found) => {
+                let [found] = readable([found]);
+                format!("Only arrays can be indexed, found a value of type {}", found)
+            }
+            CompilerError::IndexOutOfBoundsError(index, length) => format!(
+                "Index {} is out of bounds for an array of length {}",
+                index, length
+            ),
+            CompilerError::InvalidOperandTypesError(operator, left, right) => {
+                let [left, right] = readable([left, right]);
+                format!(
+                    "Operator {} cannot be applied to values of type {} and {}",
+                    operator, left, right
+                )
+            }
+            CompilerError::InvalidUnaryOperandTypeError(operator, operand) => {
+                let [operand] = readable([operand]);
+                format!(
+                    "Operator {} cannot be applied to a value of type {}",
+                    operator, operand
+                )
+            }
+            CompilerError::DuplicateConstantError(name) => {
+                format!("A constant named {} has already been declared", name)
+            }
+            CompilerError::NonConstantExpressionError(name, expression) => format!(
+                "The value of constant {} must be known at compile time, but {} is only known when the program runs",
+                name, expression
+            ),
+            CompilerError::CircularConstantError(cycle) => format!(
+                "Constants cannot depend on themselves: {}",
+                cycle.join(" -> ")
+            ),
+            CompilerError::InvalidConstantError(name) => {
+                format!("Constant {} could not be evaluated", name)
+            }
+            CompilerError::DivisionByZeroError => String::from("Division by zero"),
+            CompilerError::ConstantOverflowError(left, operator, right) => {
+                format!("{} {} {} does not fit in an i64", left, operator, right)
+            }
+            CompilerError::ModuleNotFoundError(name, searched) => {
+                let searched: Vec<String> = searched
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                format!(
+                    "Cannot find module {}, looked for: {}",
+                    name,
+                    searched.join(", ")
+                )
+            }
+            CompilerError::CircularImportError(cycle) => {
+                format!("Modules import each other in a cycle: {}", cycle.join(" -> "))
+            }
+            CompilerError::ModuleNameMismatchError(declared, imported) => format!(
+                "Module declares itself as {} but is imported as {}",
+                declared, imported
+            ),
+            CompilerError::MisplacedModuleDeclarationError => String::from(
+                "A module declaration must come before anything else in the file",
+            ),
+            CompilerError::PrivateFunctionError(name, module) => format!(
+                "{} is not public in module {}, declare it with pub def to use it here",
+                name, module
+            ),
+            CompilerError::AmbiguousImportError(name, first, second) => format!(
+                "{} is imported from both {} and {}, so it is unclear which one is meant",
+                name, first, second
+            ),
+            CompilerError::ImportConflictError(name, module) => format!(
+                "{} is declared here but also imported from {}",
+                name, module
+            ),
+            CompilerError::ContractViolationError(name, contract, declared) => format!(
+                "This call to {} breaks its contract {}, declared at {}",
+                name, contract, declared
+            ),
+            CompilerError::PostconditionViolationError(name, contract, declared) => format!(
+                "{} always returns a value breaking its contract {}, declared at {}",
+                name, contract, declared
+            ),
+            CompilerError::DuplicateMacroError(name) => {
+                format!("A macro named {} has already been defined", name)
+            }
+            CompilerError::UndefinedMacroError(name) => {
+                format!("No macro named {} has been defined before this point", name)
+            }
+            CompilerError::DuplicateMacroVariableError(name, variable) => format!(
+                "The pattern of macro {} binds ${} more than once",
+                name, variable
+            ),
+            CompilerError::UnknownMacroVariableError(name, variable) => format!(
+                "The template of macro {} uses ${}, which its pattern does not bind",
+                name, variable
+            ),
+            CompilerError::MacroArgumentsError(name, definition) => format!(
+                "The arguments given to {}! do not match the pattern of its definition at {}",
+                name, definition
+            ),
+            CompilerError::MacroRecursionError(name, limit) => format!(
+                "Expanding {}! did not finish after {} nested expansions",
+                name, limit
+            ),
+            CompilerError::InvalidUnitError(text) => {
+                format!("<{}> is not a valid unit of measure", text)
+            }
+            CompilerError::NonDifferentiableError(expression) => {
+                format!("Cannot work out the derivative of {}", expression)
+            }
+            CompilerError::DerivativeParameterError(function) => format!(
+                "Name the parameter of {} to differentiate with respect to, as {}:parameter",
+                function, function
+            ),
+            CompilerError::MissingExternError(function, needed) => format!(
+                "The derivative of {} calls {}, which has to be declared as an extern too",
+                function, needed
+            ),
+            CompilerError::UnitMismatchError(operator, left, right) => format!(
+                "Operator {} cannot be applied to values measured in {} and {}",
+                operator, left, right
+            ),
+            CompilerError::InconsistentUnitError(expected, found) => format!(
+                "Expected a value measured in {} but found one measured in {}",
+                expected, found
+            ),
+            CompilerError::UnitOverflowError(name) => {
+                format!("The power {} is raised to is too large to work with", name)
+            }
+            CompilerError::UnknownAttributeError(name) => format!("Unknown attribute @{}", name),
+            CompilerError::DuplicateAttributeError(name) => {
+                format!("@{} is given more than once", name)
+            }
+            CompilerError::ConflictingAttributesError(first, second) => {
+                format!("@{} cannot be combined with @{}", first, second)
+            }
+            CompilerError::AttributeTargetError(name, target) => {
+                format!("@{} cannot be applied to {}", name, target)
+            }
+            CompilerError::AttributeArgumentError(name) => {
+                format!("@{} does not take an argument", name)
+            }
+            CompilerError::SideEffectError(function, attribute, callee) => format!(
+                "{} is marked @{} but calls {}, which may have side effects",
+                function, attribute, callee
+            ),
+            CompilerError::LocatedError(location, error) => {
+                format!("{}: {}{}", location, error, location.expansion_notes())
+            }
+            CompilerError::ParsingFailedError(file, count) => {
+                format!("Compiling {} failed with {} error(s)", file, count)
+            }
         }
     }
 }
 
+/// `types` with the variables inference left unbound named `'a`, `'b`, ... in the order they
+/// first appear, rather than by their numbering in the whole module
+fn readable<const N: usize>(types: [&Type; N]) -> [Type; N] {
+    let mut variables: Vec<usize> = Vec::new();
+    types.map(|t| {
+        t.map_variables(&mut |t| match t {
+            Type::Variable(id) => {
+                let index: usize = match variables.iter().position(|variable| variable == id) {
+                    Some(index) => index,
+                    None => {
+                        variables.push(*id);
+                        variables.len() - 1
+                    }
+                };
+                Some(Type::Parameter(index))
+            }
+            _ => None,
+        })
+    })
+}
+
 impl Display for CompilerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message: String = self.error_message();
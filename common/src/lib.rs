@@ -1,6 +1,10 @@
 pub mod error;
 pub mod operator_precedence;
+pub mod source_location;
 pub mod token;
+pub mod types;
+pub mod units;
+pub mod warning;
 
 #[cfg(test)]
 mod tests {
@@ -1,34 +1,100 @@
 use crate::token::SimpleBinaryOperater;
 use crate::token::Token;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Binding power of an operator token. Operators with a higher precedence bind tighter, and the
+/// associativity decides which side wins between two operators of the same precedence
 pub struct OperatorPrecedence {
     precedence: isize,
+    associativity: Associativity,
 }
 
 impl OperatorPrecedence {
-    pub fn new(precedence: &Token) -> OperatorPrecedence {
-        OperatorPrecedence {
-            precedence: match precedence {
-                Token::SimpleBinaryOperator(SimpleBinaryOperater::LessThan) => 10,
-                Token::SimpleBinaryOperator(SimpleBinaryOperater::Addition) => 20,
-                Token::SimpleBinaryOperator(SimpleBinaryOperater::Subtraction) => 20,
-                Token::SimpleBinaryOperator(SimpleBinaryOperater::Multiplication) => 40,
-                _ => -1,
+    const CALL: isize = 60;
+    const PREFIX: isize = 50;
+    /// The minimum binding power that leaves out assignments, for an expression that can be
+    /// followed by a `=` of its own
+    pub const WITHOUT_ASSIGNMENT: isize = 4;
+
+    /// Binding power of `token` when it appears between two operands
+    pub fn infix(token: &Token) -> Option<OperatorPrecedence> {
+        let (precedence, associativity) = match token {
+            Token::SimpleBinaryOperator(operator) => match operator {
+                SimpleBinaryOperater::Assignment => (2, Associativity::Right),
+                SimpleBinaryOperater::Or => (4, Associativity::Left),
+                SimpleBinaryOperater::And => (6, Associativity::Left),
+                SimpleBinaryOperater::Equality => (8, Associativity::Left),
+                SimpleBinaryOperater::LessThan => (10, Associativity::Left),
+                SimpleBinaryOperater::GreaterThan => (10, Associativity::Left),
+                SimpleBinaryOperater::LessEqual => (10, Associativity::Left),
+                SimpleBinaryOperater::GreaterEqual => (10, Associativity::Left),
+                SimpleBinaryOperater::Addition => (20, Associativity::Left),
+                SimpleBinaryOperater::Subtraction => (20, Associativity::Left),
+                SimpleBinaryOperater::Multiplication => (40, Associativity::Left),
+                SimpleBinaryOperater::Division => (40, Associativity::Left),
             },
+            _ => return None,
+        };
+
+        Some(OperatorPrecedence {
+            precedence,
+            associativity,
+        })
+    }
+
+    /// Binding power of `token` when it appears before its operand
+    pub fn prefix(token: &Token) -> Option<OperatorPrecedence> {
+        match token {
+            Token::SimpleBinaryOperator(SimpleBinaryOperater::Subtraction) | Token::Bang => {
+                Some(OperatorPrecedence {
+                    precedence: Self::PREFIX,
+                    associativity: Associativity::Right,
+                })
+            }
+            _ => None,
         }
     }
 
-    pub fn from_number(number: isize) -> OperatorPrecedence {
-        OperatorPrecedence { precedence: number }
+    /// Binding power of `token` when it appears after its operand
+    pub fn postfix(token: &Token) -> Option<OperatorPrecedence> {
+        match token {
+            Token::LeftParenthesis | Token::LeftBracket | Token::Dot | Token::Question => {
+                Some(OperatorPrecedence {
+                    precedence: Self::CALL,
+                    associativity: Associativity::Left,
+                })
+            }
+            _ => None,
+        }
     }
 
     pub fn get_precedence(&self) -> isize {
         self.precedence
     }
 
-    pub fn increment_other(other: &OperatorPrecedence) -> OperatorPrecedence {
-        OperatorPrecedence {
-            precedence: other.get_precedence() + 1,
+    pub fn associativity(&self) -> Associativity {
+        self.associativity
+    }
+
+    /// How strongly the operator holds on to the operand on its left
+    pub fn left_binding_power(&self) -> isize {
+        match self.associativity {
+            Associativity::Left => self.precedence,
+            Associativity::Right => self.precedence + 1,
+        }
+    }
+
+    /// How strongly the operator holds on to the operand on its right. This is the minimum
+    /// binding power the right hand side is parsed with
+    pub fn right_binding_power(&self) -> isize {
+        match self.associativity {
+            Associativity::Left => self.precedence + 1,
+            Associativity::Right => self.precedence,
         }
     }
 }
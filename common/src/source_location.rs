@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+/// Where in the source a diagnostic was raised
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    file: String,
+    line: usize,
+    column: usize,
+    /// The macro whose expansion produced the code here, and where it was used
+    expansion: Option<Box<(String, SourceLocation)>>,
+}
+
+impl SourceLocation {
+    pub fn new(file: &str, line: usize, column: usize) -> Self {
+        Self {
+            file: file.to_string(),
+            line,
+            column,
+            expansion: None,
+        }
+    }
+
+    /// This location inside the code `macro_name` expanded to where it was used at `call_site`
+    pub fn in_expansion(&self, macro_name: &str, call_site: &SourceLocation) -> Self {
+        Self {
+            expansion: Some(Box::new((macro_name.to_string(), call_site.clone()))),
+            ..self.clone()
+        }
+    }
+
+    /// The macro whose expansion produced the code here, and where it was used
+    pub fn expansion(&self) -> Option<(&str, &SourceLocation)> {
+        self.expansion
+            .as_deref()
+            .map(|(macro_name, call_site)| (macro_name.as_str(), call_site))
+    }
+
+    /// How many macro expansions, each inside the previous one, produced the code here
+    pub fn expansion_depth(&self) -> usize {
+        self.expansion()
+            .map_or(0, |(_, call_site)| call_site.expansion_depth() + 1)
+    }
+
+    /// A line for each macro expansion that produced the code here, naming the macro and
+    /// where it was used, from the innermost one out
+    pub fn expansion_notes(&self) -> String {
+        let mut notes: String = String::new();
+        let mut expansion = self.expansion();
+        let mut previous: String = String::new();
+        while let Some((macro_name, call_site)) = expansion {
+            // a macro using itself would otherwise repeat the same line many times
+            let note: String = format!("\n  in the expansion of {}! at {}", macro_name, call_site);
+            if note != previous {
+                notes.push_str(&note);
+            }
+            previous = note;
+            expansion = call_site.expansion();
+        }
+        notes
+    }
+
+    /// Where builtins provided by the runtime are declared
+    pub fn builtin() -> Self {
+        Self::new("<builtin>", 0, 0)
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}", self.file);
+        }
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
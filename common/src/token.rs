@@ -7,15 +7,58 @@ pub enum Token {
     Eof,
     Def,
     Extern,
+    Const,
+    Fn,
+    Struct,
+    Enum,
+    Match,
+    If,
+    Module,
+    Import,
+    Pub,
+    Var,
+    In,
+    While,
+    For,
+    Do,
+    Break,
+    Continue,
+    Yield,
+    Try,
+    Catch,
+    Macro,
+    Requires,
+    Ensures,
     Identifier(String),
+    /// `$name`, a variable in the pattern or template of a macro
+    MacroVariable(String),
     F64Literal(f64),
+    I64Literal(i64),
+    /// `<m/s^2>` right after a number, the unit of measure it is given in
+    Unit(String),
+    BooleanLiteral(bool),
+    StringLiteral(String),
     SimpleBinaryOperator(SimpleBinaryOperater),
     Unknown(char),
     LeftParenthesis,
     RightParenthesis,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
     SemiColon,
+    Colon,
+    Arrow,
+    FatArrow,
     BeginningOfFile,
     Comma,
+    Dot,
+    DotDot,
+    Bang,
+    /// `@`, starting an attribute such as `@memo`
+    At,
+    /// `?` after a result, handing its error on when it holds one
+    Question,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,6 +69,18 @@ pub enum SimpleBinaryOperater {
     Division,
     LessThan,
     GreaterThan,
+    LessEqual,
+    GreaterEqual,
+    Equality,
+    And,
+    Or,
+    Assignment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    Negation,
+    Not,
 }
 
 impl Clone for Token {
@@ -34,14 +89,53 @@ impl Clone for Token {
             Token::Eof => Token::Eof,
             Token::Def => Token::Def,
             Token::Extern => Token::Extern,
+            Token::Const => Token::Const,
+            Token::Fn => Token::Fn,
+            Token::Struct => Token::Struct,
+            Token::Enum => Token::Enum,
+            Token::Match => Token::Match,
+            Token::If => Token::If,
+            Token::Module => Token::Module,
+            Token::Import => Token::Import,
+            Token::Pub => Token::Pub,
+            Token::Var => Token::Var,
+            Token::In => Token::In,
+            Token::While => Token::While,
+            Token::For => Token::For,
+            Token::Do => Token::Do,
+            Token::Break => Token::Break,
+            Token::Continue => Token::Continue,
+            Token::Yield => Token::Yield,
+            Token::Try => Token::Try,
+            Token::Catch => Token::Catch,
+            Token::Macro => Token::Macro,
+            Token::Requires => Token::Requires,
+            Token::Ensures => Token::Ensures,
             Token::Identifier(s) => Token::Identifier(s.clone()),
+            Token::MacroVariable(s) => Token::MacroVariable(s.clone()),
             Token::F64Literal(f) => Token::F64Literal(*f),
+            Token::I64Literal(i) => Token::I64Literal(*i),
+            Token::Unit(u) => Token::Unit(u.clone()),
+            Token::BooleanLiteral(b) => Token::BooleanLiteral(*b),
+            Token::StringLiteral(s) => Token::StringLiteral(s.clone()),
             Token::SimpleBinaryOperator(op) => Token::SimpleBinaryOperator(op.clone()),
             Token::Unknown(c) => Token::Unknown(*c),
             Token::LeftParenthesis => Token::LeftParenthesis,
             Token::RightParenthesis => Token::RightParenthesis,
+            Token::LeftBrace => Token::LeftBrace,
+            Token::RightBrace => Token::RightBrace,
+            Token::LeftBracket => Token::LeftBracket,
+            Token::RightBracket => Token::RightBracket,
             Token::SemiColon => Token::SemiColon,
+            Token::Colon => Token::Colon,
+            Token::Arrow => Token::Arrow,
+            Token::FatArrow => Token::FatArrow,
             Token::Comma => Token::Comma,
+            Token::Dot => Token::Dot,
+            Token::DotDot => Token::DotDot,
+            Token::Bang => Token::Bang,
+            Token::At => Token::At,
+            Token::Question => Token::Question,
             Token::BeginningOfFile => Token::BeginningOfFile,
         }
     }
@@ -53,17 +147,56 @@ impl Debug for Token {
             Token::Eof => String::from("Token: End of file token"),
             Token::Def => String::from("Token: keyword -> def"),
             Token::Extern => String::from("Token: keyword -> extern"),
+            Token::Const => String::from("Token: keyword -> const"),
+            Token::Fn => String::from("Token: keyword -> fn"),
+            Token::Struct => String::from("Token: keyword -> struct"),
+            Token::Enum => String::from("Token: keyword -> enum"),
+            Token::Match => String::from("Token: keyword -> match"),
+            Token::If => String::from("Token: keyword -> if"),
+            Token::Module => String::from("Token: keyword -> module"),
+            Token::Import => String::from("Token: keyword -> import"),
+            Token::Pub => String::from("Token: keyword -> pub"),
+            Token::Var => String::from("Token: keyword -> var"),
+            Token::In => String::from("Token: keyword -> in"),
+            Token::While => String::from("Token: keyword -> while"),
+            Token::For => String::from("Token: keyword -> for"),
+            Token::Do => String::from("Token: keyword -> do"),
+            Token::Break => String::from("Token: keyword -> break"),
+            Token::Continue => String::from("Token: keyword -> continue"),
+            Token::Yield => String::from("Token: keyword -> yield"),
+            Token::Try => String::from("Token: keyword -> try"),
+            Token::Catch => String::from("Token: keyword -> catch"),
+            Token::Macro => String::from("Token: keyword -> macro"),
+            Token::Requires => String::from("Token: keyword -> requires"),
+            Token::Ensures => String::from("Token: keyword -> ensures"),
             Token::Identifier(i) => format!("Token: identifier -> {}", i),
+            Token::MacroVariable(v) => format!("Token: macro variable -> ${}", v),
             Token::F64Literal(f) => format!("Token: f64 literal -> {}", f),
+            Token::I64Literal(i) => format!("Token: i64 literal -> {}", i),
+            Token::Unit(u) => format!("Token: unit -> <{}>", u),
+            Token::BooleanLiteral(b) => format!("Token: bool literal -> {}", b),
+            Token::StringLiteral(s) => format!("Token: string literal -> {:?}", s),
             Token::SimpleBinaryOperator(op) => {
-                format!("Token: Simple binary operator -> {}", op.to_char())
+                format!("Token: Simple binary operator -> {}", op.symbol())
             }
             Token::Unknown(u) => format!("Unknown token: {}", u),
             Token::LeftParenthesis => String::from("Token: Left Parenthesis -> ("),
             Token::RightParenthesis => String::from("Token: Right Parenthesis -> )"),
+            Token::LeftBrace => String::from("Token: Left Brace -> {"),
+            Token::RightBrace => String::from("Token: Right Brace -> }"),
+            Token::LeftBracket => String::from("Token: Left Bracket -> ["),
+            Token::RightBracket => String::from("Token: Right Bracket -> ]"),
             Token::SemiColon => String::from("Token: Semicolon -> ;"),
+            Token::Colon => String::from("Token: Colon -> :"),
+            Token::Arrow => String::from("Token: Arrow -> ->"),
+            Token::FatArrow => String::from("Token: Fat Arrow -> =>"),
             Token::BeginningOfFile => String::from("Beginning of file"),
             Token::Comma => String::from("Token: Comma -> ,"),
+            Token::Dot => String::from("Token: Dot -> ."),
+            Token::DotDot => String::from("Token: Dot Dot -> .."),
+            Token::Bang => String::from("Token: Bang -> !"),
+            Token::At => String::from("Token: At -> @"),
+            Token::Question => String::from("Token: Question -> ?"),
         };
         write!(f, "{}", message)
     }
@@ -78,6 +211,7 @@ impl SimpleBinaryOperater {
             '/' => Ok(SimpleBinaryOperater::Division),
             '<' => Ok(SimpleBinaryOperater::LessThan),
             '>' => Ok(SimpleBinaryOperater::GreaterThan),
+            '=' => Ok(SimpleBinaryOperater::Assignment),
             c => Err(CompilerError::InvalidOperaterCharacter(c)),
         }
     }
@@ -89,14 +223,63 @@ impl SimpleBinaryOperater {
         }
     }
 
-    pub fn to_char(&self) -> char {
+    /// Comparisons produce a bool, every other arithmetic operator gives a value of the type
+    /// of its operands
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::LessThan
+                | Self::GreaterThan
+                | Self::LessEqual
+                | Self::GreaterEqual
+                | Self::Equality
+        )
+    }
+
+    /// `&&` and `||` only evaluate their right operand when the left one does not already
+    /// decide the result
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Self::And | Self::Or)
+    }
+
+    /// Strings can be concatenated with `+` and compared, but not used in any other arithmetic
+    pub fn applies_to_strings(&self) -> bool {
+        matches!(self, Self::Addition) || self.is_comparison()
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Addition => "+",
+            Self::Subtraction => "-",
+            Self::Multiplication => "*",
+            Self::Division => "/",
+            Self::LessThan => "<",
+            Self::GreaterThan => ">",
+            Self::LessEqual => "<=",
+            Self::GreaterEqual => ">=",
+            Self::Equality => "==",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Assignment => "=",
+        }
+    }
+}
+
+impl UnaryOperator {
+    pub fn from_token(operator: &Token) -> Result<Self, CompilerError> {
+        match operator {
+            Token::SimpleBinaryOperator(SimpleBinaryOperater::Subtraction) => {
+                Ok(UnaryOperator::Negation)
+            }
+            Token::Bang => Ok(UnaryOperator::Not),
+            token => Err(CompilerError::UnexpectedTokenError(token.clone())),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
         match self {
-            Self::Addition => '+',
-            Self::Subtraction => '-',
-            Self::Multiplication => '*',
-            Self::Division => '/',
-            Self::LessThan => '<',
-            Self::GreaterThan => '>',
+            Self::Negation => "-",
+            Self::Not => "!",
         }
     }
 }
@@ -0,0 +1,196 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    F64,
+    I64,
+    Bool,
+    Unit,
+    Str,
+    Array(Box<Type>),
+    /// A struct or enum declared in the module, referred to by name
+    Named(String),
+    /// A fixed number of values of possibly different types
+    Tuple(Vec<Type>),
+    /// A function value taking arguments of the given types
+    Function(Vec<Type>, Box<Type>),
+    /// What calling a function that yields values of the given type gives, e.g. `gen[i64]`.
+    /// The values are produced one at a time, as a `for` loop asks for them
+    Generator(Box<Type>),
+    /// Either a value of the first type, built with `Ok`, or an error of the second, built
+    /// with `Err`, e.g. `result[f64, str]`
+    Result(Box<Type>, Box<Type>),
+    /// A type the inference pass has not worked out yet
+    Variable(usize),
+    /// The type parameter of a polymorphic signature at the given index, replaced by a fresh
+    /// variable every time the signature is used
+    Parameter(usize),
+    /// A type parameter declared by the function being checked, e.g. `T` in
+    /// `def id<T>(x: T) -> T`. The body has to work for any type, so it only equals itself
+    Generic(String),
+}
+
+/// A requirement on a type that is not known yet, e.g. the operands of `+` must be numeric
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    Numeric,
+    /// Anything with a length: strings and arrays
+    Collection,
+    /// Anything a for loop can go over, arrays and generators, whose elements are of the type
+    /// given
+    Iterable(Box<Type>),
+}
+
+impl Constraint {
+    pub fn accepts(&self, t: &Type) -> bool {
+        match self {
+            Constraint::Numeric => t.is_numeric(),
+            Constraint::Collection => matches!(t, Type::Str | Type::Array(_)),
+            Constraint::Iterable(_) => matches!(t, Type::Array(_) | Type::Generator(_)),
+        }
+    }
+
+    /// The same constraint with the types it refers to passed through `map`
+    pub fn map_types(&self, map: &mut impl FnMut(&Type) -> Type) -> Constraint {
+        match self {
+            Constraint::Iterable(element) => Constraint::Iterable(Box::new(map(element))),
+            constraint => constraint.clone(),
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::Numeric => write!(f, "numeric"),
+            Constraint::Collection => write!(f, "collection"),
+            Constraint::Iterable(_) => write!(f, "iterable"),
+        }
+    }
+}
+
+impl Type {
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "f64" => Some(Type::F64),
+            "i64" => Some(Type::I64),
+            "bool" => Some(Type::Bool),
+            "unit" => Some(Type::Unit),
+            "str" => Some(Type::Str),
+            _ => None,
+        }
+    }
+
+    pub fn is_variable(&self) -> bool {
+        matches!(self, Type::Variable(_))
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::F64 | Type::I64)
+    }
+
+    /// Every inference variable mentioned anywhere in this type, in order of appearance
+    pub fn variables(&self) -> Vec<usize> {
+        match self {
+            Type::Variable(id) => vec![*id],
+            Type::Array(element) | Type::Generator(element) => element.variables(),
+            Type::Result(value, error) => {
+                [value, error].iter().flat_map(|t| t.variables()).collect()
+            }
+            Type::Tuple(elements) => elements.iter().flat_map(|t| t.variables()).collect(),
+            Type::Function(parameters, return_type) => parameters
+                .iter()
+                .chain(std::iter::once(return_type.as_ref()))
+                .flat_map(|t| t.variables())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The same type with every variable and parameter passed through `map`
+    pub fn map_variables(&self, map: &mut impl FnMut(&Type) -> Option<Type>) -> Type {
+        if let Some(mapped) = map(self) {
+            return mapped;
+        }
+        match self {
+            Type::Array(element) => Type::Array(Box::new(element.map_variables(map))),
+            Type::Generator(element) => Type::Generator(Box::new(element.map_variables(map))),
+            Type::Result(value, error) => Type::Result(
+                Box::new(value.map_variables(map)),
+                Box::new(error.map_variables(map)),
+            ),
+            Type::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(|t| t.map_variables(map)).collect())
+            }
+            Type::Function(parameters, return_type) => Type::Function(
+                parameters.iter().map(|t| t.map_variables(map)).collect(),
+                Box::new(return_type.map_variables(map)),
+            ),
+            t => t.clone(),
+        }
+    }
+
+    /// Whether two values of this type can be compared with `==`. Functions and generators
+    /// cannot, and neither can anything holding one
+    pub fn supports_equality(&self) -> bool {
+        match self {
+            Type::Function(..) | Type::Generator(_) => false,
+            Type::Array(element) => element.supports_equality(),
+            Type::Result(value, error) => value.supports_equality() && error.supports_equality(),
+            Type::Tuple(elements) => elements.iter().all(|t| t.supports_equality()),
+            _ => true,
+        }
+    }
+
+    /// Whether a value of this type can be used where `target` is expected. The only implicit
+    /// conversion is widening an i64 to an f64; narrowing and anything involving bool or unit
+    /// must match exactly
+    pub fn can_convert_to(&self, target: &Type) -> bool {
+        self == target || (*self == Type::I64 && *target == Type::F64)
+    }
+
+    /// The type both numeric operands of an arithmetic or comparison operator are converted to
+    /// before it is applied
+    pub fn common_numeric_type(&self, other: &Type) -> Option<Type> {
+        match (self, other) {
+            (Type::I64, Type::I64) => Some(Type::I64),
+            (left, right) if left.is_numeric() && right.is_numeric() => Some(Type::F64),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name: &str = match self {
+            Type::F64 => "f64",
+            Type::I64 => "i64",
+            Type::Bool => "bool",
+            Type::Unit => "unit",
+            Type::Str => "str",
+            Type::Array(element) => return write!(f, "[{}]", element),
+            Type::Generator(element) => return write!(f, "gen[{}]", element),
+            Type::Result(value, error) => return write!(f, "result[{}, {}]", value, error),
+            Type::Named(name) | Type::Generic(name) => name,
+            Type::Tuple(elements) if elements.len() == 1 => {
+                return write!(f, "({},)", elements[0]);
+            }
+            Type::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|t| t.to_string()).collect();
+                return write!(f, "({})", elements.join(", "));
+            }
+            Type::Function(parameters, return_type) => {
+                let parameters: Vec<String> = parameters.iter().map(|t| t.to_string()).collect();
+                return write!(f, "({}) -> {}", parameters.join(", "), return_type);
+            }
+            Type::Variable(id) => return write!(f, "'t{}", id),
+            Type::Parameter(index) => {
+                return match u8::try_from(*index) {
+                    Ok(index) if index < 26 => write!(f, "'{}", (b'a' + index) as char),
+                    _ => write!(f, "'p{}", index),
+                }
+            }
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::error::CompilerError;
+
+/// A unit of measure such as `m/s^2`, kept as the power each base unit is raised to. Units are
+/// compared by those powers, so `m*s/s` and `m` are the same unit
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Unit {
+    powers: BTreeMap<String, i32>,
+}
+
+impl Unit {
+    /// The unit of plain numbers, written `1`
+    pub fn dimensionless() -> Self {
+        Self::default()
+    }
+
+    pub fn base(name: &str) -> Self {
+        Self {
+            powers: BTreeMap::from([(name.to_string(), 1)]),
+        }
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.powers.is_empty()
+    }
+
+    /// The unit of the product of a value measured in `self` and one measured in `other`,
+    /// which cannot be worked out if the power of a base unit no longer fits an `i32`
+    pub fn multiply(&self, other: &Unit) -> Result<Unit, CompilerError> {
+        let mut powers: BTreeMap<String, i32> = self.powers.clone();
+        for (name, power) in &other.powers {
+            let total: i32 = powers
+                .get(name)
+                .copied()
+                .unwrap_or(0)
+                .checked_add(*power)
+                .ok_or_else(|| CompilerError::UnitOverflowError(name.clone()))?;
+            if total == 0 {
+                powers.remove(name);
+            } else {
+                powers.insert(name.clone(), total);
+            }
+        }
+        Ok(Unit { powers })
+    }
+
+    /// The unit of the quotient of a value measured in `self` by one measured in `other`
+    pub fn divide(&self, other: &Unit) -> Result<Unit, CompilerError> {
+        self.multiply(&other.power(-1)?)
+    }
+
+    pub fn power(&self, exponent: i32) -> Result<Unit, CompilerError> {
+        let mut powers: BTreeMap<String, i32> = BTreeMap::new();
+        if exponent != 0 {
+            for (name, power) in &self.powers {
+                let total: i32 = power
+                    .checked_mul(exponent)
+                    .ok_or_else(|| CompilerError::UnitOverflowError(name.clone()))?;
+                powers.insert(name.clone(), total);
+            }
+        }
+        Ok(Unit { powers })
+    }
+
+    /// Read a unit written as base units separated by `*` or `/`, each one optionally raised
+    /// to an integer power with `^`, e.g. `kg*m/s^2`. A `/` only divides by the base unit right
+    /// after it, so `m/s/s` is `m/s^2`
+    pub fn parse(text: &str) -> Result<Unit, CompilerError> {
+        let invalid = || CompilerError::InvalidUnitError(text.to_string());
+
+        let mut unit: Unit = Unit::dimensionless();
+        let mut dividing: bool = false;
+        let mut rest: &str = text;
+        loop {
+            let end: usize = rest.find(['*', '/']).unwrap_or(rest.len());
+            let (name, exponent) = match rest[..end].split_once('^') {
+                Some((name, exponent)) => (
+                    name.trim(),
+                    exponent.trim().parse::<i32>().map_err(|_| invalid())?,
+                ),
+                None => (rest[..end].trim(), 1),
+            };
+
+            let factor: Unit = if name == "1" {
+                Unit::dimensionless()
+            } else if Self::is_base_name(name) {
+                Unit::base(name).power(exponent)?
+            } else {
+                return Err(invalid());
+            };
+            unit = match dividing {
+                true => unit.divide(&factor)?,
+                false => unit.multiply(&factor)?,
+            };
+
+            if end == rest.len() {
+                return Ok(unit);
+            }
+            dividing = rest[end..].starts_with('/');
+            rest = &rest[end + 1..];
+        }
+    }
+
+    /// Whether `name` can name a base unit, following the same rules as identifiers
+    fn is_base_name(name: &str) -> bool {
+        let mut characters = name.chars();
+        characters
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && characters.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let factor = |name: &str, power: i32| match power {
+            1 => name.to_string(),
+            power => format!("{}^{}", name, power),
+        };
+        let numerator: Vec<String> = self
+            .powers
+            .iter()
+            .filter(|(_, power)| **power > 0)
+            .map(|(name, power)| factor(name, *power))
+            .collect();
+        let denominator: Vec<String> = self
+            .powers
+            .iter()
+            .filter(|(_, power)| **power < 0)
+            .map(|(name, power)| format!("/{}", factor(name, -power)))
+            .collect();
+
+        match numerator.is_empty() {
+            true => write!(f, "1{}", denominator.concat()),
+            false => write!(f, "{}{}", numerator.join("*"), denominator.concat()),
+        }
+    }
+}
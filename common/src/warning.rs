@@ -0,0 +1,71 @@
+use std::fmt::{Debug, Display};
+
+use crate::source_location::SourceLocation;
+
+/// What a warning points out
+pub enum WarningKind {
+    /// A call to a function marked @deprecated, with the message the attribute gives, if any
+    DeprecatedCall(String, Option<String>),
+    /// A function marked @inline that calls itself
+    RecursiveInline(String),
+    /// A try whose body never hands an error on with `?`
+    UnusedCatch,
+}
+
+/// Something worth pointing out in a program that does not stop it from compiling. Unlike an
+/// error, a warning always knows where it was found
+pub struct CompilerWarning {
+    kind: WarningKind,
+    location: SourceLocation,
+}
+
+impl CompilerWarning {
+    pub fn new(kind: WarningKind, location: &SourceLocation) -> Self {
+        Self {
+            kind,
+            location: location.clone(),
+        }
+    }
+
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    fn warning_message(&self) -> String {
+        match &self.kind {
+            WarningKind::DeprecatedCall(function, message) => match message {
+                Some(message) => format!("{} is deprecated: {}", function, message),
+                None => format!("{} is deprecated", function),
+            },
+            WarningKind::RecursiveInline(function) => format!(
+                "{} calls itself, so it cannot be inlined as @inline asks",
+                function
+            ),
+            WarningKind::UnusedCatch => String::from(
+                "nothing in the body of this try hands an error on with ?, so its catch never runs",
+            ),
+        }
+    }
+}
+
+impl Display for CompilerWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: Warning: {}{}",
+            self.location,
+            self.warning_message(),
+            self.location.expansion_notes()
+        )
+    }
+}
+
+impl Debug for CompilerWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
@@ -3,12 +3,16 @@ use std::path::PathBuf;
 use argparse::{ArgumentParser, Collect, StoreTrue};
 use common::error::CompilerError;
 use lexer::lexer::Lexer;
-use parser::ast::Ast;
+use parser::module_loader::ModuleLoader;
 
 fn main() -> Result<(), CompilerError> {
     let mut files: Vec<PathBuf> = Vec::new();
+    let mut search_roots: Vec<PathBuf> = Vec::new();
     let mut lex_only: bool = false;
     let mut verbose: bool = false;
+    let mut print_types: bool = false;
+    let mut no_contracts: bool = false;
+    let mut derivatives: Vec<String> = Vec::new();
 
     {
         let mut argument_parse: ArgumentParser = ArgumentParser::new();
@@ -24,6 +28,30 @@ fn main() -> Result<(), CompilerError> {
             "Indicate to only lex and display tokens",
         );
 
+        argument_parse.refer(&mut search_roots).add_option(
+            &["-I", "--search-root"],
+            Collect,
+            "Directory to look for imported modules in, after the importing file's own",
+        );
+
+        argument_parse.refer(&mut print_types).add_option(
+            &["-t", "--types"],
+            StoreTrue,
+            "Print the value of every constant and the inferred signature of every function",
+        );
+
+        argument_parse.refer(&mut no_contracts).add_option(
+            &["--no-contracts"],
+            StoreTrue,
+            "Leave out every requires and ensures contract, as a release build does",
+        );
+
+        argument_parse.refer(&mut derivatives).add_option(
+            &["--derive"],
+            Collect,
+            "Print the derivative of a function, given as NAME or NAME:PARAMETER, and check it along with the module",
+        );
+
         argument_parse.refer(&mut verbose).add_option(
             &["--verbose"],
             StoreTrue,
@@ -50,10 +78,63 @@ fn main() -> Result<(), CompilerError> {
         lexer.lex()?;
     } else {
         println!("Compiling files: {:?}\n", &files);
+        let mut failed_file: Option<(String, usize)> = None;
+        let mut loader: ModuleLoader =
+            ModuleLoader::new(search_roots, verbose).with_contracts(!no_contracts);
         for file in files {
-            lexer.new_file(file)?;
-            let mut ast: Ast = Ast::new(&mut lexer, verbose);
-            ast.parse_tokens()?;
+            let file_name: String = file.display().to_string();
+            let (mut module, mut errors) = loader.load(file)?;
+            // derivatives are worked out of well formed functions, then checked like any other
+            if errors.is_empty() {
+                for derivative in &derivatives {
+                    let (name, parameter) = match derivative.split_once(':') {
+                        Some((name, parameter)) => (name, Some(parameter)),
+                        None => (derivative.as_str(), None),
+                    };
+                    match module.derivative(name, parameter) {
+                        Ok(function) => {
+                            // derivatives are only ever made of nodes that can be
+                            // written out as source
+                            let source: String =
+                                function.to_source().unwrap_or_else(|| function.to_string());
+                            println!("{}\n", source);
+                            module.add_function(function);
+                        }
+                        Err(error) => errors.push(error),
+                    }
+                }
+            }
+            // types are only meaningful once the file is known to be well formed
+            if errors.is_empty() {
+                let (signatures, type_errors, warnings) = module.check_types_with_warnings();
+                for warning in &warnings {
+                    eprintln!("{}", warning);
+                }
+                if print_types {
+                    let (values, _) = module.evaluate_constants();
+                    for (name, value) in &values {
+                        println!("const {}: {} = {}", name, value.value_type(), value);
+                    }
+                    for signature in &signatures {
+                        println!("def {}", signature);
+                    }
+                }
+                errors = type_errors;
+                // units are checked on top of well typed code
+                if errors.is_empty() {
+                    errors = module.check_units();
+                }
+            }
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            if !errors.is_empty() {
+                failed_file = Some((file_name, errors.len()));
+            }
+        }
+
+        if let Some((file, error_count)) = failed_file {
+            return Err(CompilerError::ParsingFailedError(file, error_count));
         }
     }
 
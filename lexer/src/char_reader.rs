@@ -35,6 +35,16 @@ impl CharReader {
             .to_string()
     }
 
+    /// Byte offset of the next character to be read
+    pub fn byte_position(&self) -> usize {
+        self.byte_pointer
+    }
+
+    /// Go back to a byte offset returned by `byte_position`, to read what follows it again
+    pub fn rewind(&mut self, byte_position: usize) {
+        self.byte_pointer = byte_position;
+    }
+
     pub fn getchar(&mut self) -> Option<char> {
         if let Some(c) = self.file_map.get(self.byte_pointer) {
             self.byte_pointer += 1;
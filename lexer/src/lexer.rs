@@ -7,6 +7,7 @@ use std::{
 use common::{
     error::CompilerError,
     token::{SimpleBinaryOperater, Token},
+    units::Unit,
 };
 
 use crate::char_reader::CharReader;
@@ -14,15 +15,24 @@ use crate::char_reader::CharReader;
 pub struct Lexer {
     current_char_reader: Option<CharReader>,
     current_line_number: usize,
+    line_start_position: usize,
+    token_line_number: usize,
+    token_column_number: usize,
+    /// The token returned last, which decides whether a `.` starts a number or an access
+    previous_token: Token,
 }
 
 impl Lexer {
-    const SIMPLE_BINARY_OPERATORS: [char; 6] = ['+', '-', '*', '/', '<', '>'];
+    const SIMPLE_BINARY_OPERATORS: [char; 7] = ['+', '-', '*', '/', '<', '>', '='];
 
     pub fn init() -> Lexer {
         Lexer {
             current_char_reader: None,
             current_line_number: 1,
+            line_start_position: 0,
+            token_line_number: 1,
+            token_column_number: 1,
+            previous_token: Token::BeginningOfFile,
         }
     }
 
@@ -30,6 +40,10 @@ impl Lexer {
         Ok(Lexer {
             current_char_reader: Some(CharReader::new(file)?),
             current_line_number: 1,
+            line_start_position: 0,
+            token_line_number: 1,
+            token_column_number: 1,
+            previous_token: Token::BeginningOfFile,
         })
     }
 
@@ -37,6 +51,11 @@ impl Lexer {
         self.current_line_number
     }
 
+    /// Line and column the most recently lexed token starts at
+    pub fn token_position(&self) -> (usize, usize) {
+        (self.token_line_number, self.token_column_number)
+    }
+
     pub fn current_file(&self) -> String {
         match &self.current_char_reader {
             Some(reader) => reader.current_file(),
@@ -57,23 +76,37 @@ impl Lexer {
 
         self.current_char_reader = Some(CharReader::new(file)?);
         self.current_line_number = 1;
+        self.line_start_position = 0;
+        self.token_line_number = 1;
+        self.token_column_number = 1;
+        self.previous_token = Token::BeginningOfFile;
         Ok(())
     }
 
+    /// Collect a number whose first character was already read. Right after a `.` only digits
+    /// are taken, so `t.0.1` reads as two element accesses rather than the number `0.1`
     fn collect_number(&mut self, built_lexeme: &mut String) -> Result<Token, CompilerError> {
+        let after_dot: bool = self.previous_token == Token::Dot;
         let reader: &mut CharReader = match &mut self.current_char_reader {
             Some(r) => r,
             None => return Err(CompilerError::NonExistentFileError),
         };
 
         while let Some(c) = reader.preview_char() {
-            if c.is_ascii_digit() || c == '.' {
+            if c.is_ascii_digit() || (c == '.' && !after_dot) {
                 built_lexeme.push(reader.getchar().unwrap());
             } else {
                 break;
             }
         }
 
+        if !built_lexeme.contains('.') {
+            return match built_lexeme.parse::<i64>() {
+                Ok(n) => Ok(Token::I64Literal(n)),
+                Err(_) => Err(CompilerError::InvalidNumberError(built_lexeme.to_string())),
+            };
+        }
+
         match built_lexeme.parse::<f64>() {
             Ok(n) => Ok(Token::F64Literal(n)),
             Err(_) => Err(CompilerError::ExpectedNumberError(
@@ -90,7 +123,7 @@ impl Lexer {
         };
 
         while let Some(c) = reader.preview_char() {
-            if c.is_ascii_alphabetic() {
+            if c.is_ascii_alphanumeric() || c == '_' {
                 built_lexeme.push(reader.getchar().unwrap());
                 continue;
             } else {
@@ -98,15 +131,155 @@ impl Lexer {
             }
         }
 
-        return match built_lexeme.as_str() {
+        match built_lexeme.as_str() {
             "def" => Ok(Token::Def),
             "extern" => Ok(Token::Extern),
+            "const" => Ok(Token::Const),
+            "fn" => Ok(Token::Fn),
+            "struct" => Ok(Token::Struct),
+            "enum" => Ok(Token::Enum),
+            "match" => Ok(Token::Match),
+            "if" => Ok(Token::If),
+            "module" => Ok(Token::Module),
+            "import" => Ok(Token::Import),
+            "pub" => Ok(Token::Pub),
+            "var" => Ok(Token::Var),
+            "in" => Ok(Token::In),
+            "while" => Ok(Token::While),
+            "for" => Ok(Token::For),
+            "do" => Ok(Token::Do),
+            "break" => Ok(Token::Break),
+            "continue" => Ok(Token::Continue),
+            "yield" => Ok(Token::Yield),
+            "try" => Ok(Token::Try),
+            "catch" => Ok(Token::Catch),
+            "macro" => Ok(Token::Macro),
+            "requires" => Ok(Token::Requires),
+            "ensures" => Ok(Token::Ensures),
+            "true" => Ok(Token::BooleanLiteral(true)),
+            "false" => Ok(Token::BooleanLiteral(false)),
             _ => Ok(Token::Identifier(built_lexeme.to_string())),
+        }
+    }
+
+    /// Collect a string literal whose opening quote was already read, translating escape
+    /// sequences on the way. Strings cannot span lines
+    fn collect_string(&mut self) -> Result<Token, CompilerError> {
+        let reader: &mut CharReader = match &mut self.current_char_reader {
+            Some(r) => r,
+            None => return Err(CompilerError::NonExistentFileError),
         };
+
+        // characters are read a byte at a time, so collect bytes to keep UTF-8 text intact
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut ended_line: bool = false;
+        loop {
+            let byte: u8 = match reader.getchar() {
+                Some('"') => {
+                    let text: String = String::from_utf8_lossy(&bytes).to_string();
+                    return Ok(Token::StringLiteral(text));
+                }
+                Some('\\') => match reader.getchar() {
+                    Some('n') => b'\n',
+                    Some('t') => b'\t',
+                    Some('"') => b'"',
+                    Some('\\') => b'\\',
+                    Some(c) => return Err(CompilerError::InvalidEscapeError(c)),
+                    None => break,
+                },
+                Some('\n') => {
+                    ended_line = true;
+                    break;
+                }
+                Some(c) => c as u8,
+                None => break,
+            };
+            bytes.push(byte);
+        }
+
+        let file: String = reader.current_file();
+        if ended_line {
+            self.current_line_number += 1;
+            self.line_start_position = reader.byte_position();
+        }
+        Err(CompilerError::UnterminatedStringError(
+            self.token_line_number,
+            file,
+        ))
+    }
+
+    /// Collect the unit of measure following a number, whose opening `<` was already read.
+    /// That `<` only opens a unit if a `>` closes it around something reading as one, so
+    /// `0<x` and `1<2` are still comparisons. Text without spaces is kept as a unit even if it
+    /// is malformed, for the parser to report `1.0<m/>` as a bad unit
+    fn collect_unit(&mut self) -> Result<Token, CompilerError> {
+        let reader: &mut CharReader = match &mut self.current_char_reader {
+            Some(r) => r,
+            None => return Err(CompilerError::NonExistentFileError),
+        };
+
+        let start: usize = reader.byte_position();
+        let mut unit: String = String::new();
+        while let Some(c) = reader.preview_char() {
+            if c.is_ascii_alphanumeric() || ['_', '*', '/', '^', '-', ' '].contains(&c) {
+                unit.push(reader.getchar().unwrap());
+            } else {
+                break;
+            }
+        }
+
+        let unit: &str = unit.trim();
+        let reads_as_unit: bool =
+            !unit.is_empty() && (!unit.contains(' ') || Unit::parse(unit).is_ok());
+        if reader.preview_char() == Some('>') && reads_as_unit {
+            reader.getchar();
+            return Ok(Token::Unit(unit.to_string()));
+        }
+
+        reader.rewind(start);
+        match reader.preview_char() {
+            Some('=') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::LessEqual))
+            }
+            _ => Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::LessThan)),
+        }
     }
 
     pub fn get_token(&mut self) -> Result<Token, CompilerError> {
+        let last_position: (usize, usize) = self.token_position();
+        let token: Token = self.lex_token()?;
+        if token == Token::Eof {
+            // the end of the file has no place of its own, so errors about it point at the last
+            // token instead of past the final line
+            (self.token_line_number, self.token_column_number) = last_position;
+        }
+        self.previous_token = token.clone();
+        Ok(token)
+    }
+
+    /// Whether the previous token can end an operand, in which case a `.` right after it reads
+    /// an element or field of that operand
+    fn after_operand(&self) -> bool {
+        matches!(
+            self.previous_token,
+            Token::Identifier(_)
+                | Token::I64Literal(_)
+                | Token::RightParenthesis
+                | Token::RightBracket
+                | Token::RightBrace
+        )
+    }
+
+    fn lex_token(&mut self) -> Result<Token, CompilerError> {
         let last_char: char;
+        let after_operand: bool = self.after_operand();
+        // a `<` right after a number gives its unit, while one after a space compares it
+        let after_number: bool = matches!(
+            self.previous_token,
+            Token::F64Literal(_) | Token::I64Literal(_)
+        );
+        let mut after_space: bool = false;
 
         let reader: &mut CharReader = match &mut self.current_char_reader {
             Some(r) => r,
@@ -114,16 +287,30 @@ impl Lexer {
         };
 
         loop {
-            match reader.getchar() {
-                Some(c) if c.is_ascii_whitespace() => {
+            let c: Option<char> = reader.getchar();
+            if let Some(c) = c {
+                if c.is_ascii_whitespace() {
+                    after_space = true;
                     if c == '\n' {
                         self.current_line_number += 1;
+                        self.line_start_position = reader.byte_position();
                     }
                     continue;
                 }
+            }
+
+            self.token_line_number = self.current_line_number;
+            self.token_column_number = reader.byte_position() - self.line_start_position;
+
+            match c {
                 Some('(') => return Ok(Token::LeftParenthesis),
                 Some(')') => return Ok(Token::RightParenthesis),
+                Some('{') => return Ok(Token::LeftBrace),
+                Some('}') => return Ok(Token::RightBrace),
+                Some('[') => return Ok(Token::LeftBracket),
+                Some(']') => return Ok(Token::RightBracket),
                 Some(';') => return Ok(Token::SemiColon),
+                Some(':') => return Ok(Token::Colon),
                 Some(',') => return Ok(Token::Comma),
                 Some(notspace) => {
                     last_char = notspace;
@@ -138,10 +325,24 @@ impl Lexer {
         let mut built_lexeme = String::new();
 
         match last_char {
-            character if character.is_ascii_alphabetic() => {
+            character if character.is_ascii_alphabetic() || character == '_' => {
                 built_lexeme.push(character);
                 self.collect_identifier(&mut built_lexeme)
             }
+            '$' if reader
+                .preview_char()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_') =>
+            {
+                self.collect_identifier(&mut built_lexeme)?;
+                Ok(Token::MacroVariable(built_lexeme))
+            }
+            '.' if reader.preview_char() == Some('.') => {
+                reader.getchar();
+                Ok(Token::DotDot)
+            }
+            '.' if after_operand || !reader.preview_char().is_some_and(|c| c.is_ascii_digit()) => {
+                Ok(Token::Dot)
+            }
             digit if digit.is_ascii_digit() || digit == '.' => {
                 built_lexeme.push(digit);
                 self.collect_number(&mut built_lexeme)
@@ -150,12 +351,48 @@ impl Lexer {
                 while let Some(c) = reader.getchar() {
                     if c == '\n' || c == '\r' {
                         self.current_line_number += 1;
-                        return self.get_token();
+                        self.line_start_position = reader.byte_position();
+                        return self.lex_token();
                     }
                 }
 
                 Ok(Token::Eof)
             }
+            '"' => self.collect_string(),
+            '-' if reader.preview_char() == Some('>') => {
+                reader.getchar();
+                Ok(Token::Arrow)
+            }
+            '=' if reader.preview_char() == Some('>') => {
+                reader.getchar();
+                Ok(Token::FatArrow)
+            }
+            '=' if reader.preview_char() == Some('=') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::Equality))
+            }
+            '<' if after_number && !after_space => self.collect_unit(),
+            '<' if reader.preview_char() == Some('=') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::LessEqual))
+            }
+            '>' if reader.preview_char() == Some('=') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(
+                    SimpleBinaryOperater::GreaterEqual,
+                ))
+            }
+            '&' if reader.preview_char() == Some('&') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::And))
+            }
+            '|' if reader.preview_char() == Some('|') => {
+                reader.getchar();
+                Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::Or))
+            }
+            '!' => Ok(Token::Bang),
+            '@' => Ok(Token::At),
+            '?' => Ok(Token::Question),
             c if Self::SIMPLE_BINARY_OPERATORS.contains(&c) => {
                 Ok(Token::SimpleBinaryOperator(SimpleBinaryOperater::new(c)?))
             }
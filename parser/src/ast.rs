@@ -1,25 +1,105 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+};
+
 use common::{
     error::CompilerError,
     operator_precedence::OperatorPrecedence,
-    token::{SimpleBinaryOperater, Token},
+    source_location::SourceLocation,
+    token::{SimpleBinaryOperater, Token, UnaryOperator},
+    types::Type,
+    units::Unit,
 };
 use lexer::lexer::Lexer;
 
 use crate::{
     ast_node::ASTNode,
     ast_nodes::{
+        constants::constant_definition::ConstantDefinition,
         expressions::{
-            binary_expression::BinaryExpression, function_call_expression::FunctionCallExpression,
-            numeric_expression::NumericExpression, variable_expression::VariableExpression,
+            array_expression::ArrayExpression,
+            assignment_expression::AssignmentExpression,
+            binary_expression::BinaryExpression,
+            block_expression::{BlockExpression, BlockItem},
+            boolean_expression::BooleanExpression,
+            break_expression::BreakExpression,
+            continue_expression::ContinueExpression,
+            field_expression::FieldExpression,
+            for_expression::ForExpression,
+            function_call_expression::{Argument, FunctionCallExpression},
+            index_expression::IndexExpression,
+            integer_expression::IntegerExpression,
+            lambda_expression::LambdaExpression,
+            logical_expression::LogicalExpression,
+            match_expression::{LiteralPattern, MatchArm, MatchExpression, Pattern},
+            numeric_expression::NumericExpression,
+            propagate_expression::PropagateExpression,
+            string_expression::StringExpression,
+            struct_expression::{FieldValue, StructExpression},
+            try_expression::TryExpression,
+            tuple_expression::TupleExpression,
+            tuple_index_expression::TupleIndexExpression,
+            unary_expression::UnaryExpression,
+            var_expression::{BindingPattern, VarBinding, VarExpression},
+            variable_expression::VariableExpression,
+            while_expression::WhileExpression,
+            yield_expression::YieldExpression,
+        },
+        functions::{
+            attribute::{Attribute, AttributeKind},
+            contract::{Contract, ContractKind},
+            function_definition::Function,
+            function_prototype::{FunctionPrototype, Parameter},
+        },
+        types::{
+            enum_definition::{EnumDefinition, Variant},
+            struct_definition::{Field, StructDefinition},
         },
-        functions::{function_definition::Function, function_prototype::FunctionPrototype},
     },
+    macro_definition::{
+        closes_tree, is_free_template_name, opens_tree, MacroDefinition, SourceToken,
+    },
+    module::Module,
+    module_loader::{Import, ModuleLoader},
 };
 
+/// How many macro uses can be expanded one inside the other before the expansion is assumed
+/// to never finish
+const MACRO_EXPANSION_LIMIT: usize = 64;
+
 pub struct Ast<'a> {
     lexer: &'a mut Lexer,
     current_token: Token,
     verbose: bool,
+    errors: Vec<CompilerError>,
+    brace_depth: usize,
+    /// Every struct or enum name used as a type, checked against the declared types once the
+    /// whole file has been parsed
+    type_references: Vec<(String, SourceLocation)>,
+    /// Whether a `{` after a name starts a struct construction. It does not in the value a
+    /// match inspects, where the `{` opens the arms instead
+    struct_literals: bool,
+    /// The type parameters of the function being parsed, which its annotations can refer to
+    type_parameters: Vec<String>,
+    /// For every loop around the expression being parsed, innermost last, whether a `break` can
+    /// give it a value
+    loops: Vec<bool>,
+    /// Whether the expression being parsed is in the body of a def, outside of any lambda in
+    /// it, where a `yield` can make the function a generator
+    can_yield: bool,
+    /// The macros defined so far. A macro can only be used after its definition
+    macros: HashMap<String, MacroDefinition>,
+    /// Tokens a macro use expanded to, parsed before anything else the lexer gives
+    expanded_tokens: VecDeque<SourceToken>,
+    /// Where the current token came from, if a macro expansion produced it
+    expanded_location: Option<SourceLocation>,
+    /// How many macro uses have been expanded so far
+    expansions: usize,
+    /// Whether `requires` and `ensures` clauses are kept, rather than parsed and left out
+    contracts: bool,
+    /// The attributes given to the definition or extern about to be parsed
+    attributes: Vec<Attribute>,
 }
 
 impl<'a> Ast<'a> {
@@ -28,6 +108,73 @@ impl<'a> Ast<'a> {
             lexer,
             current_token: Token::BeginningOfFile,
             verbose,
+            errors: Vec::new(),
+            brace_depth: 0,
+            type_references: Vec::new(),
+            struct_literals: true,
+            type_parameters: Vec::new(),
+            loops: Vec::new(),
+            can_yield: false,
+            macros: HashMap::new(),
+            expanded_tokens: VecDeque::new(),
+            expanded_location: None,
+            expansions: 0,
+            contracts: true,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// The same parser, keeping the contracts of functions or leaving them out
+    pub fn with_contracts(mut self, enabled: bool) -> Self {
+        self.contracts = enabled;
+        self
+    }
+
+    fn current_location(&self) -> SourceLocation {
+        if let Some(location) = &self.expanded_location {
+            return location.clone();
+        }
+        let (line, column) = self.lexer.token_position();
+        SourceLocation::new(&self.lexer.current_file(), line, column)
+    }
+
+    fn record_error(&mut self, error: CompilerError) {
+        if self.verbose {
+            println!("Recording error: {}", error);
+        }
+        let location: SourceLocation = self.current_location();
+        self.errors.push(error.with_location(&location));
+    }
+
+    /// Skip tokens until one that can start or end a top level item, so parsing can resume after a
+    /// syntax error. Anything inside an unclosed block is skipped along with it, as are the
+    /// attributes given to the item that failed to parse
+    fn synchronize(&mut self) {
+        self.attributes.clear();
+        loop {
+            match self.current_token {
+                Token::Eof => return,
+                Token::SemiColon
+                | Token::At
+                | Token::Def
+                | Token::Pub
+                | Token::Extern
+                | Token::Struct
+                | Token::Enum
+                | Token::Const
+                | Token::Macro
+                | Token::Module
+                | Token::Import
+                    if self.brace_depth == 0 =>
+                {
+                    return
+                }
+                _ => {
+                    if let Err(e) = self.eat_current_token_and_advance_lexer() {
+                        self.record_error(e);
+                    }
+                }
+            }
         }
     }
 
@@ -37,15 +184,73 @@ impl<'a> Ast<'a> {
             println!("{:?}", self.current_token);
             println!("*** Token Eaten ***\n");
         }
-        self.current_token = self.lexer.get_token()?;
+        match self.current_token {
+            Token::LeftBrace => self.brace_depth += 1,
+            Token::RightBrace => self.brace_depth = self.brace_depth.saturating_sub(1),
+            _ => {}
+        }
+        self.advance()
+    }
+
+    /// Move on to the next token, taking it from the tokens a macro expanded to if there are
+    /// any left
+    fn advance(&mut self) -> Result<(), CompilerError> {
+        match self.expanded_tokens.pop_front() {
+            Some((token, location)) => {
+                self.current_token = token;
+                self.expanded_location = Some(location);
+            }
+            None => {
+                self.current_token = self.lexer.get_token()?;
+                self.expanded_location = None;
+            }
+        }
         Ok(())
     }
 
-    fn parse_number_expression(&mut self) -> Result<Box<NumericExpression>, CompilerError> {
+    /// Collect the token tree starting at the current token without parsing it: a single
+    /// token, or a bracket along with everything up to the one closing it
+    fn parse_token_tree(&mut self) -> Result<Vec<SourceToken>, CompilerError> {
+        let mut tokens: Vec<SourceToken> = Vec::new();
+        let mut depth: usize = 0;
+        loop {
+            if self.current_token == Token::Eof || (depth == 0 && closes_tree(&self.current_token))
+            {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ));
+            }
+            if opens_tree(&self.current_token) {
+                depth += 1;
+            } else if closes_tree(&self.current_token) {
+                depth -= 1;
+            }
+            tokens.push((self.current_token.clone(), self.current_location()));
+            self.eat_current_token_and_advance_lexer()?;
+            if depth == 0 {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn parse_number_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
         match self.current_token {
             Token::F64Literal(number) => {
                 self.eat_current_token_and_advance_lexer()?;
-                Ok(Box::new(NumericExpression::new(number)))
+                let expression: NumericExpression = NumericExpression::new(number);
+                Ok(match self.parse_unit()? {
+                    Some(unit) => Box::new(expression.with_unit(unit)),
+                    None => Box::new(expression),
+                })
+            }
+            Token::I64Literal(number) => {
+                let location: SourceLocation = self.current_location();
+                self.eat_current_token_and_advance_lexer()?;
+                let expression: IntegerExpression = IntegerExpression::new(number, location);
+                Ok(match self.parse_unit()? {
+                    Some(unit) => Box::new(expression.with_unit(unit)),
+                    None => Box::new(expression),
+                })
             }
             _ => Err(CompilerError::ExpectedNumberError(
                 self.lexer.current_line(),
@@ -54,6 +259,27 @@ impl<'a> Ast<'a> {
         }
     }
 
+    /// Parse the `<unit>` following a number, if there is one. A unit that cannot be read is
+    /// recorded and left out, as the number itself is still fine
+    fn parse_unit(&mut self) -> Result<Option<Unit>, CompilerError> {
+        let text: String = match &self.current_token {
+            Token::Unit(text) => text.clone(),
+            _ => return Ok(None),
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat unit
+        self.eat_current_token_and_advance_lexer()?;
+
+        match Unit::parse(&text) {
+            Ok(unit) => Ok(Some(unit)),
+            Err(e) => {
+                self.errors.push(e.with_location(&location));
+                Ok(None)
+            }
+        }
+    }
+
     fn parse_identifier(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
         // current token should be an identifier
         let id_string: String = match &self.current_token {
@@ -65,271 +291,3774 @@ impl<'a> Ast<'a> {
             }
         };
 
+        let location: SourceLocation = self.current_location();
+
         // eat identifier
         self.eat_current_token_and_advance_lexer()?;
-        let mut expressions: Vec<Box<dyn ASTNode>> = Vec::new();
 
-        if let Token::LeftParenthesis = &self.current_token {
-            self.eat_current_token_and_advance_lexer()?; // eat '('
-
-            if self.current_token == Token::RightParenthesis {
-                loop {
-                    expressions.push(self.parse_expression()?);
+        if self.current_token == Token::LeftBrace && self.struct_literals {
+            return self.parse_struct_expression(&id_string, location);
+        }
+        if self.current_token == Token::Bang {
+            return self.parse_macro_use(&id_string, location);
+        }
+        if is_free_template_name(&id_string, &location) {
+            return Ok(Box::new(VariableExpression::top_level(
+                &id_string, location,
+            )));
+        }
+        Ok(Box::new(VariableExpression::new(&id_string, location)))
+    }
 
-                    if self.current_token == Token::RightParenthesis {
-                        break;
-                    }
+    /// Expand `name!(arguments)` and parse what it expanded to in its place
+    fn parse_macro_use(
+        &mut self,
+        name: &str,
+        call_site: SourceLocation,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        // eat '!'
+        self.eat_current_token_and_advance_lexer()?;
 
-                    if self.current_token != Token::Comma {
-                        return Err(CompilerError::UnexpectedTokenError(
-                            self.current_token.clone(),
-                        ));
-                    }
-                }
-            }
-        } else {
-            return Ok(Box::new(VariableExpression::new(&id_string)));
+        if self.current_token != Token::LeftParenthesis {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
         }
+        let mut arguments: Vec<SourceToken> = self.parse_token_tree()?;
+        arguments.pop(); // drop ')'
+        arguments.remove(0); // drop '('
 
-        self.eat_current_token_and_advance_lexer()?;
-        Ok(Box::new(FunctionCallExpression::new(
-            &id_string,
-            expressions,
-        )))
-    }
+        let definition: &MacroDefinition = self.macros.get(name).ok_or_else(|| {
+            CompilerError::UndefinedMacroError(name.to_string()).with_location(&call_site)
+        })?;
+        if call_site.expansion_depth() >= MACRO_EXPANSION_LIMIT {
+            return Err(CompilerError::MacroRecursionError(
+                name.to_string(),
+                MACRO_EXPANSION_LIMIT,
+            )
+            .with_location(&call_site));
+        }
+        self.expansions += 1;
+        let expansion: Vec<SourceToken> =
+            definition.expand(&arguments, &call_site, self.expansions)?;
 
-    fn parse_primary(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
-        match &self.current_token {
-            Token::Identifier(_) => Ok(self.parse_identifier()?),
-            Token::F64Literal(_) => Ok(self.parse_number_expression()?),
-            Token::LeftParenthesis => Ok(self.parse_parenthesis_expression()?),
-            token => Err(CompilerError::UnexpectedTokenError(token.clone())),
+        // the token after the use is parsed once everything the macro expanded to has been
+        let next: SourceToken = (self.current_token.clone(), self.current_location());
+        self.expanded_tokens.push_front(next);
+        for token in expansion.into_iter().rev() {
+            self.expanded_tokens.push_front(token);
         }
+        self.advance()?;
+        self.parse_primary()
     }
 
-    fn parse_binary_operation_rhs(
+    /// Parse the `{ field: value, ..base }` following a struct name. The base, if any, has to
+    /// come last
+    fn parse_struct_expression(
         &mut self,
-        precedence: OperatorPrecedence,
-        mut lhs: Box<dyn ASTNode>,
+        name: &str,
+        location: SourceLocation,
     ) -> Result<Box<dyn ASTNode>, CompilerError> {
-        loop {
-            if self.verbose {
-                println!("Parsing binary expression. LHS:\n{}", lhs);
+        self.type_references
+            .push((name.to_string(), location.clone()));
+        self.eat_current_token_and_advance_lexer()?; // eat '{'
+
+        let mut fields: Vec<FieldValue> = Vec::new();
+        let mut base: Option<Box<dyn ASTNode>> = None;
+        while self.current_token != Token::RightBrace {
+            if self.current_token == Token::DotDot {
+                self.eat_current_token_and_advance_lexer()?; // eat '..'
+                base = Some(self.parse_expression()?);
+                break;
             }
-            let current_token_precedence: OperatorPrecedence =
-                OperatorPrecedence::new(&self.current_token);
 
-            if current_token_precedence.get_precedence() < precedence.get_precedence() {
-                if self.verbose {
-                    println!("While parsing binop, this token:");
-                    println!("{:?}", self.current_token);
-                    println!("Had a lower precedence than:");
-                    println!("{}\n", precedence.get_precedence());
+            let field_name: String = match &self.current_token {
+                Token::Identifier(field_name) => field_name.to_string(),
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
                 }
-                return Ok(lhs);
-            } else {
-                let binary_operator: SimpleBinaryOperater =
-                    SimpleBinaryOperater::from_token(&self.current_token)?;
-
-                // eat operator
-                self.eat_current_token_and_advance_lexer()?;
+            };
+            let field_location: SourceLocation = self.current_location();
+            self.eat_current_token_and_advance_lexer()?; // eat field name
 
-                let mut rhs = self.parse_primary()?;
+            if self.current_token != Token::Colon {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ));
+            }
+            self.eat_current_token_and_advance_lexer()?; // eat ':'
 
-                let next_precedence = OperatorPrecedence::new(&self.current_token);
+            let value: Box<dyn ASTNode> = self.parse_expression()?;
+            fields.push(FieldValue::new(&field_name, value, field_location));
 
-                if current_token_precedence.get_precedence() < next_precedence.get_precedence() {
-                    rhs = self.parse_binary_operation_rhs(
-                        OperatorPrecedence::increment_other(&current_token_precedence),
-                        rhs,
-                    )?;
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBrace => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
                 }
-
-                lhs = Box::new(BinaryExpression::new(binary_operator, lhs, rhs));
             }
         }
+
+        if self.current_token != Token::RightBrace {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+        self.eat_current_token_and_advance_lexer()?; // eat '}'
+        Ok(Box::new(StructExpression::new(
+            name, fields, base, location,
+        )))
     }
 
-    fn parse_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
-        let lhs: Box<dyn ASTNode> = self.parse_primary()?;
-        self.parse_binary_operation_rhs(OperatorPrecedence::from_number(0), lhs)
+    fn parse_primary(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        match &self.current_token {
+            Token::Identifier(_) => Ok(self.parse_identifier()?),
+            Token::F64Literal(_) | Token::I64Literal(_) => Ok(self.parse_number_expression()?),
+            Token::BooleanLiteral(value) => {
+                let value: bool = *value;
+                self.eat_current_token_and_advance_lexer()?;
+                Ok(Box::new(BooleanExpression::new(value)))
+            }
+            Token::StringLiteral(value) => {
+                let value: String = value.to_string();
+                self.eat_current_token_and_advance_lexer()?;
+                Ok(Box::new(StringExpression::new(&value)))
+            }
+            Token::LeftParenthesis => {
+                self.with_struct_literals(true, Self::parse_parenthesis_expression)
+            }
+            Token::Var => Ok(self.parse_var_expression()?),
+            Token::LeftBrace => Ok(self.with_struct_literals(true, Self::parse_block_expression)?),
+            Token::LeftBracket => self.with_struct_literals(true, Self::parse_array_expression),
+            Token::Fn => Ok(self.parse_lambda_expression()?),
+            Token::Match => Ok(self.parse_match_expression()?),
+            Token::While => self.parse_while_expression(),
+            Token::For => self.parse_for_expression(),
+            Token::Yield => self.parse_yield_expression(),
+            Token::Try => self.parse_try_expression(),
+            Token::Break => self.parse_break_expression(),
+            Token::Continue => self.parse_continue_expression(),
+            token => Err(CompilerError::UnexpectedTokenError(token.clone())),
+        }
     }
 
-    fn parse_parenthesis_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
-        if let Token::LeftParenthesis = self.current_token {
-            self.eat_current_token_and_advance_lexer()?;
+    /// Run `parse` with struct constructions allowed or not, restoring the previous setting
+    /// afterwards. Brackets of any kind allow them again inside
+    fn with_struct_literals<T>(
+        &mut self,
+        allowed: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T, CompilerError>,
+    ) -> Result<T, CompilerError> {
+        let previous: bool = std::mem::replace(&mut self.struct_literals, allowed);
+        let result: Result<T, CompilerError> = parse(self);
+        self.struct_literals = previous;
+        result
+    }
 
-            let v: Box<dyn ASTNode> = self.parse_expression()?;
+    /// Run `parse` over the body of a function, which a `break` or `continue` cannot leave.
+    /// Only the body of a def can yield, not that of a lambda
+    fn function_body<T>(
+        &mut self,
+        can_yield: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T, CompilerError>,
+    ) -> Result<T, CompilerError> {
+        let previous: Vec<bool> = std::mem::take(&mut self.loops);
+        let previous_can_yield: bool = std::mem::replace(&mut self.can_yield, can_yield);
+        let result: Result<T, CompilerError> = parse(self);
+        self.loops = previous;
+        self.can_yield = previous_can_yield;
+        result
+    }
 
-            // should now be a ')' from parse_expression call
-            match self.current_token {
-                Token::RightParenthesis => {}
-                _ => {
-                    return Err(CompilerError::UnexpectedTokenError(
-                        self.current_token.clone(),
-                    ))
-                }
-            };
+    /// Parse `while condition do body`. The body extends as far to the right as it can
+    fn parse_while_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        // eat while token
+        self.eat_current_token_and_advance_lexer()?;
 
-            self.current_token = self.lexer.get_token()?;
-            Ok(v)
-        } else {
-            Err(CompilerError::UnexpectedTokenError(
+        let location: SourceLocation = self.current_location();
+        let condition: Box<dyn ASTNode> = self.parse_expression()?;
+        if self.current_token != Token::Do {
+            return Err(CompilerError::UnexpectedTokenError(
                 self.current_token.clone(),
-            ))
+            ));
         }
-    }
 
-    fn parse_extern(&mut self) -> Result<Box<FunctionPrototype>, CompilerError> {
-        // eat extern token
+        // eat do token
         self.eat_current_token_and_advance_lexer()?;
-        self.parse_prototype()
+
+        self.loops.push(condition.constant_boolean() == Some(true));
+        let body: Result<Box<dyn ASTNode>, CompilerError> = self.parse_expression();
+        self.loops.pop();
+        Ok(Box::new(WhileExpression::new(condition, body?, location)))
     }
 
-    fn handle_extern(&mut self) -> Result<(), CompilerError> {
-        let parse_node = self.parse_extern()?;
-        parse_node.print();
-        if self.verbose {
-            println!(
-                "Succesfully parsed definition! Current Token: {:?}",
-                self.current_token
-            );
+    /// Parse `for pattern in iterable do body`. The body extends as far to the right as it can
+    fn parse_for_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        // eat for token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let location: SourceLocation = self.current_location();
+        let pattern: BindingPattern = self.parse_binding_pattern()?;
+        if self.current_token != Token::In {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
         }
-        Ok(())
-    }
 
-    fn parse_top_level_expression(&mut self) -> Result<Box<Function>, CompilerError> {
-        let expression = self.parse_expression()?;
-        let prototype: Box<FunctionPrototype> = Box::new(FunctionPrototype::new("", Vec::new()));
-        Ok(Box::new(Function::new(prototype, expression)))
-    }
+        // eat in token
+        self.eat_current_token_and_advance_lexer()?;
 
-    fn handle_top_level_expression(&mut self) -> Result<(), CompilerError> {
-        let parse_node = self.parse_top_level_expression()?;
-        parse_node.print();
-        if self.verbose {
-            println!(
-                "Succesfully parsed top level expression! Current Token: {:?}",
-                self.current_token
-            );
+        let iterable: Box<dyn ASTNode> = self.parse_expression()?;
+        if self.current_token != Token::Do {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
         }
-        Ok(())
+
+        // eat do token
+        self.eat_current_token_and_advance_lexer()?;
+
+        // the loop ends once the values run out, so a `break` cannot give it a value either
+        self.loops.push(false);
+        let body: Result<Box<dyn ASTNode>, CompilerError> = self.parse_expression();
+        self.loops.pop();
+        Ok(Box::new(ForExpression::new(
+            pattern, iterable, body?, location,
+        )))
     }
 
-    fn parse_prototype(&mut self) -> Result<Box<FunctionPrototype>, CompilerError> {
-        if self.verbose {
-            println!("Start parsing prototype!");
+    /// Parse `yield value`. A misplaced yield is recorded without stopping the parse
+    fn parse_yield_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
+
+        // eat yield token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let value: Box<dyn ASTNode> = self.parse_expression()?;
+        if !self.can_yield {
+            self.errors
+                .push(CompilerError::YieldOutsideFunctionError.with_location(&location));
         }
-        if let Token::Identifier(id) = &self.current_token {
-            let function_name: String = id.to_string();
+        Ok(Box::new(YieldExpression::new(value, location)))
+    }
 
-            // eat prototype name
-            self.eat_current_token_and_advance_lexer()?;
+    /// Parse `try body catch name => handler`. The handler extends as far to the right as it
+    /// can
+    fn parse_try_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
 
-            if self.current_token != Token::LeftParenthesis {
-                return Err(CompilerError::UnexpectedTokenError(
-                    self.current_token.clone(),
-                ));
-            }
+        // eat try token
+        self.eat_current_token_and_advance_lexer()?;
 
-            self.eat_current_token_and_advance_lexer()?;
+        let body: Box<dyn ASTNode> = self.parse_expression()?;
+        if self.current_token != Token::Catch {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
 
-            let mut args: Vec<String> = Vec::new();
-            while let Token::Identifier(arg) = &self.current_token {
-                if self.verbose {
-                    println!("parse_protoype: Prototype arg found: {}\n", arg);
-                }
-                args.push(arg.to_string());
-                self.eat_current_token_and_advance_lexer()?;
-            }
+        // eat catch token
+        self.eat_current_token_and_advance_lexer()?;
 
-            if self.current_token != Token::RightParenthesis {
+        let name: String = match &self.current_token {
+            Token::Identifier(name) => name.to_string(),
+            _ => {
                 return Err(CompilerError::UnexpectedTokenError(
                     self.current_token.clone(),
-                ));
+                ))
             }
+        };
 
-            // eat ) token
-            self.eat_current_token_and_advance_lexer()?;
-            if self.verbose {
-                println!("prototype parsed!!");
-            }
-            Ok(Box::new(FunctionPrototype::new(&function_name, args)))
-        } else {
-            Err(CompilerError::FunctionNameNotFound)
-        }
-    }
+        // eat error name
+        self.eat_current_token_and_advance_lexer()?;
 
-    fn parse_definition(&mut self) -> Result<Box<Function>, CompilerError> {
-        if self.verbose {
-            println!("Start parsing definition!");
-        }
-        if self.current_token != Token::Def {
+        if self.current_token != Token::FatArrow {
             return Err(CompilerError::UnexpectedTokenError(
                 self.current_token.clone(),
             ));
         }
 
-        // eat Def token and expect func name
+        // eat '=>'
         self.eat_current_token_and_advance_lexer()?;
 
-        let prototype: Box<FunctionPrototype> = self.parse_prototype()?;
-        let definition_expression = self.parse_expression()?;
+        let handler_location: SourceLocation = self.current_location();
+        let handler: Box<dyn ASTNode> = self.parse_expression()?;
+        Ok(Box::new(TryExpression::new(
+            body,
+            &name,
+            handler,
+            location,
+            handler_location,
+        )))
+    }
+
+    /// Parse `break`, or `break value` when an expression follows. Misplaced breaks are
+    /// recorded without stopping the parse
+    fn parse_break_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
 
-        if self.verbose {
-            println!("Finished parsing definition!");
+        // eat break token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let value: Option<Box<dyn ASTNode>> = match self.current_token {
+            Token::SemiColon
+            | Token::Comma
+            | Token::RightBrace
+            | Token::RightParenthesis
+            | Token::RightBracket
+            | Token::Eof => None,
+            _ => Some(self.parse_expression()?),
+        };
+        match self.loops.last() {
+            None => self.errors.push(
+                CompilerError::OutsideLoopError(String::from("break")).with_location(&location),
+            ),
+            Some(false) if value.is_some() => self
+                .errors
+                .push(CompilerError::LoopValueError.with_location(&location)),
+            Some(_) => {}
         }
-        Ok(Box::new(Function::new(prototype, definition_expression)))
+        Ok(Box::new(BreakExpression::new(value, location)))
     }
 
-    fn handle_definition(&mut self) -> Result<(), CompilerError> {
-        if self.verbose {
-            println!("Start handling def!");
-        }
-        let defintion_node = self.parse_definition()?;
-        defintion_node.print();
+    fn parse_continue_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
 
-        if self.verbose {
-            println!(
-                "Succesfully parsed definition! Current Token: {:?}",
-                self.current_token
+        // eat continue token
+        self.eat_current_token_and_advance_lexer()?;
+
+        if self.loops.is_empty() {
+            self.errors.push(
+                CompilerError::OutsideLoopError(String::from("continue")).with_location(&location),
             );
         }
+        Ok(Box::new(ContinueExpression::new(location)))
+    }
 
-        Ok(())
+    /// Run `parse` over a function declaration, whose type parameters are only visible until
+    /// the end of it. A local function also sees those of the functions around it
+    fn with_type_parameters<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, CompilerError>,
+    ) -> Result<T, CompilerError> {
+        let previous: Vec<String> = self.type_parameters.clone();
+        let result: Result<T, CompilerError> = parse(self);
+        self.type_parameters = previous;
+        result
     }
 
-    /// Parse tokens in the source file
-    ///
-    /// # Arguments
-    /// * `self` - a mutable reference to self - a Parser that owns a lexer buffer and a current
-    /// token
-    pub fn parse_tokens(&mut self) -> Result<(), CompilerError> {
-        if self.verbose {
-            println!("***************************************************************************");
-            println!("*                       In the parsing tokens stage!                      *");
-            println!("***************************************************************************");
-            println!(" ");
+    /// Parse `match value { pattern => body, pattern if guard => body, ... }`
+    fn parse_match_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
+
+        // eat match token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let scrutinee: Box<dyn ASTNode> =
+            self.with_struct_literals(false, Self::parse_expression)?;
+
+        if self.current_token != Token::LeftBrace {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+        self.eat_current_token_and_advance_lexer()?; // eat '{'
+
+        let mut arms: Vec<MatchArm> = Vec::new();
+        while self.current_token != Token::RightBrace {
+            let arm_location: SourceLocation = self.current_location();
+            let pattern: Pattern = self.parse_pattern()?;
+
+            let mut guard: Option<Box<dyn ASTNode>> = None;
+            if self.current_token == Token::If {
+                self.eat_current_token_and_advance_lexer()?; // eat if
+                guard = Some(self.parse_expression()?);
+            }
+
+            if self.current_token != Token::FatArrow {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ));
+            }
+            self.eat_current_token_and_advance_lexer()?; // eat '=>'
+
+            let body: Box<dyn ASTNode> = self.parse_expression()?;
+            arms.push(MatchArm::new(pattern, guard, body, arm_location));
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBrace => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat '}'
+        Ok(Box::new(MatchExpression::new(scrutinee, arms, location)))
+    }
+
+    /// Parse a pattern: `_`, a literal, a name, a variant such as `Rect(w, _)`, or a tuple of
+    /// patterns
+    fn parse_pattern(&mut self) -> Result<Pattern, CompilerError> {
+        let location: SourceLocation = self.current_location();
+        let literal: LiteralPattern = match &self.current_token {
+            Token::Identifier(name) => {
+                let name: String = name.to_string();
+
+                // eat name
+                self.eat_current_token_and_advance_lexer()?;
+                if name == "_" {
+                    return Ok(Pattern::Wildcard);
+                }
+                if self.current_token != Token::LeftParenthesis {
+                    return Ok(Pattern::Name(name));
+                }
+                let (fields, _) = self.parse_pattern_list()?;
+                return Ok(Pattern::Constructor(name, fields, location));
+            }
+            Token::LeftParenthesis => {
+                let (mut elements, trailing_comma) = self.parse_pattern_list()?;
+                if elements.len() == 1 && !trailing_comma {
+                    return Ok(elements.remove(0));
+                }
+                return Ok(Pattern::Tuple(elements));
+            }
+            Token::SimpleBinaryOperator(SimpleBinaryOperater::Subtraction) => {
+                self.eat_current_token_and_advance_lexer()?; // eat '-'
+                match self.current_token {
+                    Token::I64Literal(value) => LiteralPattern::I64(-value),
+                    Token::F64Literal(value) => LiteralPattern::F64(-value),
+                    _ => {
+                        return Err(CompilerError::UnexpectedTokenError(
+                            self.current_token.clone(),
+                        ))
+                    }
+                }
+            }
+            Token::I64Literal(value) => LiteralPattern::I64(*value),
+            Token::F64Literal(value) => LiteralPattern::F64(*value),
+            Token::BooleanLiteral(value) => LiteralPattern::Bool(*value),
+            Token::StringLiteral(value) => LiteralPattern::Str(value.to_string()),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+
+        // eat literal
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(Pattern::Literal(literal))
+    }
+
+    /// Parse `(pattern, ...)`, also reporting whether the last pattern was followed by a comma
+    fn parse_pattern_list(&mut self) -> Result<(Vec<Pattern>, bool), CompilerError> {
+        self.eat_current_token_and_advance_lexer()?; // eat '('
+
+        let mut patterns: Vec<Pattern> = Vec::new();
+        let mut trailing_comma: bool = false;
+        while self.current_token != Token::RightParenthesis {
+            patterns.push(self.parse_pattern()?);
+
+            trailing_comma = self.current_token == Token::Comma;
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat ')'
+        Ok((patterns, trailing_comma))
+    }
+
+    /// Parse a primary expression, or a prefix operator applied to an operand
+    fn parse_prefix(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let precedence: OperatorPrecedence = match OperatorPrecedence::prefix(&self.current_token) {
+            Some(p) => p,
+            None => return self.parse_primary(),
+        };
+
+        let operator: UnaryOperator = UnaryOperator::from_token(&self.current_token)?;
+        let location: SourceLocation = self.current_location();
+
+        // eat operator
+        self.eat_current_token_and_advance_lexer()?;
+
+        let operand: Box<dyn ASTNode> =
+            self.parse_expression_with_binding_power(precedence.right_binding_power())?;
+        Ok(Box::new(UnaryExpression::new(operator, operand, location)))
+    }
+
+    /// Apply the postfix operator at the current token to `operand`
+    fn parse_postfix(
+        &mut self,
+        operand: Box<dyn ASTNode>,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        match self.current_token {
+            Token::LeftParenthesis => {
+                self.with_struct_literals(true, |ast| ast.parse_call(operand))
+            }
+            Token::LeftBracket => self.with_struct_literals(true, |ast| ast.parse_index(operand)),
+            Token::Dot => self.parse_field(operand),
+            Token::Question => {
+                let location: SourceLocation = self.current_location();
+                self.eat_current_token_and_advance_lexer()?; // eat '?'
+                Ok(Box::new(PropagateExpression::new(operand, location)))
+            }
+            _ => Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            )),
+        }
+    }
+
+    fn parse_call(&mut self, callee: Box<dyn ASTNode>) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let callee_name: String = match callee.variable_name() {
+            Some(name) => name.to_string(),
+            None => callee.to_s_expression(),
+        };
+
+        let location: SourceLocation = self.current_location();
+        self.eat_current_token_and_advance_lexer()?; // eat '('
+
+        let mut arguments: Vec<Argument> = Vec::new();
+        while self.current_token != Token::RightParenthesis {
+            arguments.push(self.parse_argument(&callee_name, &arguments)?);
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat ')'
+        Ok(Box::new(FunctionCallExpression::new(
+            callee, arguments, location,
+        )))
+    }
+
+    fn parse_index(&mut self, array: Box<dyn ASTNode>) -> Result<Box<dyn ASTNode>, CompilerError> {
+        self.eat_current_token_and_advance_lexer()?; // eat '['
+
+        let location: SourceLocation = self.current_location();
+        let index: Box<dyn ASTNode> = self.parse_expression()?;
+
+        if self.current_token != Token::RightBracket {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat ']'
+        Ok(Box::new(IndexExpression::new(array, index, location)))
+    }
+
+    fn parse_field(&mut self, record: Box<dyn ASTNode>) -> Result<Box<dyn ASTNode>, CompilerError> {
+        self.eat_current_token_and_advance_lexer()?; // eat '.'
+
+        let field: String = match &self.current_token {
+            Token::Identifier(field) => field.to_string(),
+            Token::I64Literal(index) => {
+                let index: usize = *index as usize;
+                let location: SourceLocation = self.current_location();
+
+                // eat element index
+                self.eat_current_token_and_advance_lexer()?;
+                return Ok(Box::new(TupleIndexExpression::new(record, index, location)));
+            }
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat field name
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(Box::new(FieldExpression::new(record, &field, location)))
+    }
+
+    /// Parse either a positional argument or a `name: value` pair. Positional arguments may not
+    /// follow named ones
+    fn parse_argument(
+        &mut self,
+        callee_name: &str,
+        previous_arguments: &[Argument],
+    ) -> Result<Argument, CompilerError> {
+        let mut location: SourceLocation = self.current_location();
+        let expression: Box<dyn ASTNode> = self.parse_expression()?;
+
+        if self.current_token == Token::Colon {
+            let name: String = match expression.variable_name() {
+                Some(name) => name.to_string(),
+                None => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            };
+
+            self.eat_current_token_and_advance_lexer()?; // eat ':'
+            location = self.current_location();
+            let value: Box<dyn ASTNode> = self.parse_expression()?;
+            return Ok(Argument::new(Some(&name), value, location));
+        }
+
+        if previous_arguments.iter().any(|arg| arg.name().is_some()) {
+            return Err(CompilerError::PositionalAfterNamedArgumentError(
+                callee_name.to_string(),
+            ));
+        }
+
+        Ok(Argument::new(None, expression, location))
+    }
+
+    fn make_binary_expression(
+        operator: SimpleBinaryOperater,
+        lhs: Box<dyn ASTNode>,
+        rhs: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        match operator {
+            SimpleBinaryOperater::Assignment => match lhs.variable_name() {
+                Some(name) => Ok(Box::new(AssignmentExpression::new(name, rhs, location))),
+                None => Err(
+                    CompilerError::InvalidAssignmentTargetError(lhs.to_s_expression())
+                        .with_location(&location),
+                ),
+            },
+            operator if operator.is_logical() => Ok(Box::new(LogicalExpression::new(
+                operator, lhs, rhs, location,
+            ))),
+            _ => Ok(Box::new(BinaryExpression::new(
+                operator, lhs, rhs, location,
+            ))),
         }
-        self.eat_current_token_and_advance_lexer()?; // eat the beginning of file token
+    }
+
+    /// Parse an expression whose operators all bind at least as tightly as
+    /// `minimum_binding_power`. Anything looser is left for the caller to pick up
+    fn parse_expression_with_binding_power(
+        &mut self,
+        minimum_binding_power: isize,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let mut lhs: Box<dyn ASTNode> = self.parse_prefix()?;
 
         loop {
             if self.verbose {
-                println!("*********************************************************************\n");
+                println!("Parsing expression. LHS:\n{}", lhs);
+            }
+
+            if let Some(precedence) = OperatorPrecedence::postfix(&self.current_token) {
+                if precedence.left_binding_power() < minimum_binding_power {
+                    break;
+                }
+                lhs = self.parse_postfix(lhs)?;
+                continue;
+            }
+
+            let precedence: OperatorPrecedence =
+                match OperatorPrecedence::infix(&self.current_token) {
+                    Some(p) => p,
+                    None => break,
+                };
+
+            if precedence.left_binding_power() < minimum_binding_power {
+                if self.verbose {
+                    println!("While parsing expression, this token:");
+                    println!("{:?}", self.current_token);
+                    println!("Bound less tightly than:");
+                    println!("{}\n", minimum_binding_power);
+                }
+                break;
             }
+
+            let binary_operator: SimpleBinaryOperater =
+                SimpleBinaryOperater::from_token(&self.current_token)?;
+            let location: SourceLocation = self.current_location();
+
+            // eat operator
+            self.eat_current_token_and_advance_lexer()?;
+
+            let rhs: Box<dyn ASTNode> =
+                self.parse_expression_with_binding_power(precedence.right_binding_power())?;
+
+            lhs = Self::make_binary_expression(binary_operator, lhs, rhs, location)?;
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        self.parse_expression_with_binding_power(0)
+    }
+
+    /// Parse an array literal such as `[1, 2, 3]`. A trailing comma is allowed
+    fn parse_array_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
+        self.eat_current_token_and_advance_lexer()?; // eat '['
+
+        let mut elements: Vec<Box<dyn ASTNode>> = Vec::new();
+        while self.current_token != Token::RightBracket {
+            elements.push(self.parse_expression()?);
+
             match self.current_token {
-                Token::Eof => break,
-                Token::SemiColon => {
-                    self.eat_current_token_and_advance_lexer()?;
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBracket => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
                 }
-                Token::Def => {
-                    self.handle_definition()?;
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat ']'
+        Ok(Box::new(ArrayExpression::new(elements, location)))
+    }
+
+    /// Parse `(expr)`, or a tuple `(a, b)` when the first element is followed by a comma. A
+    /// single element tuple needs a trailing comma, `(a,)`
+    fn parse_parenthesis_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        if let Token::LeftParenthesis = self.current_token {
+            self.eat_current_token_and_advance_lexer()?;
+
+            let v: Box<dyn ASTNode> = self.parse_expression()?;
+            if self.current_token == Token::Comma {
+                return self.parse_tuple_elements(v);
+            }
+
+            // should now be a ')' from parse_expression call
+            match self.current_token {
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
                 }
-                Token::Extern => self.handle_extern()?,
-                _ => self.handle_top_level_expression()?,
             };
+
+            self.eat_current_token_and_advance_lexer()?; // eat ')'
+            Ok(v)
+        } else {
+            Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ))
         }
+    }
 
-        Ok(())
+    /// Parse the rest of a tuple expression, starting at the comma after its first element
+    fn parse_tuple_elements(
+        &mut self,
+        first: Box<dyn ASTNode>,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let mut elements: Vec<Box<dyn ASTNode>> = vec![first];
+        while self.current_token == Token::Comma {
+            self.eat_current_token_and_advance_lexer()?; // eat ','
+            if self.current_token == Token::RightParenthesis {
+                break;
+            }
+            elements.push(self.parse_expression()?);
+        }
+
+        if self.current_token != Token::RightParenthesis {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat ')'
+        Ok(Box::new(TupleExpression::new(elements)))
+    }
+
+    /// Parse the `x = init, y` bindings following a var keyword
+    fn parse_var_bindings(&mut self) -> Result<Vec<VarBinding>, CompilerError> {
+        // eat var token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        loop {
+            let location: SourceLocation = self.current_location();
+            let pattern: BindingPattern = self.parse_binding_pattern()?;
+
+            let annotation: Option<Type> = self.parse_optional_type_annotation()?;
+
+            let mut initializer: Option<Box<dyn ASTNode>> = None;
+            if self.current_token == Token::SimpleBinaryOperator(SimpleBinaryOperater::Assignment) {
+                // eat '='
+                self.eat_current_token_and_advance_lexer()?;
+                initializer = Some(self.parse_expression()?);
+            }
+
+            if self.verbose {
+                println!("parse_var_expression: binding found: {}", pattern);
+            }
+            bindings.push(VarBinding::new(pattern, annotation, initializer, location));
+
+            if self.current_token != Token::Comma {
+                break;
+            }
+
+            // eat ','
+            self.eat_current_token_and_advance_lexer()?;
+        }
+
+        Ok(bindings)
+    }
+
+    /// Parse the name of a var binding, or a tuple of patterns such as `(q, (r, s))`
+    fn parse_binding_pattern(&mut self) -> Result<BindingPattern, CompilerError> {
+        if let Token::Identifier(name) = &self.current_token {
+            let name: String = name.to_string();
+
+            // eat variable name
+            self.eat_current_token_and_advance_lexer()?;
+            return Ok(BindingPattern::Name(name));
+        }
+
+        if self.current_token != Token::LeftParenthesis {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        let location: SourceLocation = self.current_location();
+        self.eat_current_token_and_advance_lexer()?; // eat '('
+        let mut elements: Vec<BindingPattern> = Vec::new();
+        while self.current_token != Token::RightParenthesis {
+            elements.push(self.parse_binding_pattern()?);
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+        self.eat_current_token_and_advance_lexer()?; // eat ')'
+
+        let pattern: BindingPattern = BindingPattern::Tuple(elements);
+        let names: Vec<&str> = pattern.names();
+        if let Some(name) = names
+            .iter()
+            .enumerate()
+            .find_map(|(i, name)| names[..i].contains(name).then_some(name))
+        {
+            return Err(
+                CompilerError::DuplicateBindingError(name.to_string()).with_location(&location)
+            );
+        }
+        Ok(pattern)
+    }
+
+    fn parse_var_expression(&mut self) -> Result<Box<VarExpression>, CompilerError> {
+        let bindings: Vec<VarBinding> = self.parse_var_bindings()?;
+        self.parse_var_body(bindings)
+    }
+
+    fn parse_var_body(
+        &mut self,
+        bindings: Vec<VarBinding>,
+    ) -> Result<Box<VarExpression>, CompilerError> {
+        if self.current_token != Token::In {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        // eat in token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let body: Box<dyn ASTNode> = self.parse_expression()?;
+        Ok(Box::new(VarExpression::new(bindings, body)))
+    }
+
+    /// Parse one entry of a block: a local definition, a var declaration or an expression
+    fn parse_block_item(&mut self) -> Result<BlockItem, CompilerError> {
+        match self.current_token {
+            Token::Def => Ok(BlockItem::Definition(*self.parse_definition()?)),
+            Token::At => {
+                self.parse_attributes()?;
+                Ok(BlockItem::Definition(*self.parse_definition()?))
+            }
+            Token::Var => {
+                let bindings: Vec<VarBinding> = self.parse_var_bindings()?;
+                match self.current_token {
+                    Token::SemiColon | Token::RightBrace => Ok(BlockItem::Declaration(bindings)),
+                    _ => Ok(BlockItem::Expression(self.parse_var_body(bindings)?)),
+                }
+            }
+            _ => Ok(BlockItem::Expression(self.parse_expression()?)),
+        }
+    }
+
+    fn parse_block_expression(&mut self) -> Result<Box<BlockExpression>, CompilerError> {
+        // eat '{'
+        self.eat_current_token_and_advance_lexer()?;
+
+        let mut items: Vec<BlockItem> = Vec::new();
+        while self.current_token != Token::RightBrace {
+            if self.current_token == Token::SemiColon {
+                self.eat_current_token_and_advance_lexer()?;
+                continue;
+            }
+
+            items.push(self.parse_block_item()?);
+
+            match self.current_token {
+                Token::SemiColon => self.eat_current_token_and_advance_lexer()?,
+                Token::RightBrace => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        if !matches!(items.last(), Some(BlockItem::Expression(_))) {
+            return Err(CompilerError::BlockWithoutValueError);
+        }
+
+        // eat '}'
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(Box::new(BlockExpression::new(items)))
+    }
+
+    fn parse_extern(&mut self) -> Result<Box<FunctionPrototype>, CompilerError> {
+        // eat extern token
+        self.eat_current_token_and_advance_lexer()?;
+        let attributes: Vec<Attribute> = self.take_attributes(true);
+        let prototype: Box<FunctionPrototype> =
+            self.with_type_parameters(|ast| ast.parse_prototype())?;
+
+        // only a float can be NaN, which is how a host function reports its failures
+        let checked = attributes
+            .iter()
+            .find(|attribute| attribute.kind() == AttributeKind::Checked);
+        if let Some(checked) = checked.filter(|_| prototype.return_type() != Type::F64) {
+            self.errors.push(
+                CompilerError::AttributeTargetError(
+                    String::from(checked.kind().name()),
+                    "an extern not returning f64",
+                )
+                .with_location(checked.location()),
+            );
+        }
+        Ok(Box::new(prototype.with_attributes(attributes)))
+    }
+
+    /// Parse `@name` or `@name("message")` attributes, up to the definition or extern they are
+    /// given to. Attributes the compiler does not know of are reported and left out
+    fn parse_attributes(&mut self) -> Result<(), CompilerError> {
+        let mut attributes: Vec<Attribute> = Vec::new();
+        while self.current_token == Token::At {
+            // eat '@'
+            self.eat_current_token_and_advance_lexer()?;
+
+            let location: SourceLocation = self.current_location();
+            let name: String = match &self.current_token {
+                Token::Identifier(name) => name.to_string(),
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            };
+            // eat attribute name
+            self.eat_current_token_and_advance_lexer()?;
+
+            let mut message: Option<String> = None;
+            if self.current_token == Token::LeftParenthesis {
+                self.eat_current_token_and_advance_lexer()?; // eat '('
+                match &self.current_token {
+                    Token::StringLiteral(text) => message = Some(text.to_string()),
+                    _ => {
+                        return Err(CompilerError::UnexpectedTokenError(
+                            self.current_token.clone(),
+                        ))
+                    }
+                }
+                // eat message
+                self.eat_current_token_and_advance_lexer()?;
+                if self.current_token != Token::RightParenthesis {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ));
+                }
+                self.eat_current_token_and_advance_lexer()?; // eat ')'
+            }
+
+            match AttributeKind::from_name(&name) {
+                Some(kind) => attributes.push(Attribute::new(kind, message, location)),
+                None => self
+                    .errors
+                    .push(CompilerError::UnknownAttributeError(name).with_location(&location)),
+            }
+        }
+
+        match self.current_token {
+            Token::Def | Token::Pub | Token::Extern => {
+                self.attributes = attributes;
+                Ok(())
+            }
+            _ => Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            )),
+        }
+    }
+
+    /// The attributes parsed ahead of the definition or extern being parsed, reporting any
+    /// that cannot be given to it
+    fn take_attributes(&mut self, is_extern: bool) -> Vec<Attribute> {
+        let attributes: Vec<Attribute> = std::mem::take(&mut self.attributes);
+        self.errors
+            .append(&mut Attribute::validate(&attributes, is_extern));
+        attributes
+    }
+
+    fn handle_extern(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let parse_node = self.parse_extern()?;
+        if !self.declare_function_name(module, &parse_node) {
+            return Ok(());
+        }
+        parse_node.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed definition! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_extern(*parse_node);
+        Ok(())
+    }
+
+    /// Parse `field` or `field: type` in a struct or enum variant declaration, `owner` being
+    /// the name of whatever declares it
+    fn parse_field_declaration(
+        &mut self,
+        owner: &str,
+        fields: &[Field],
+    ) -> Result<Field, CompilerError> {
+        let field_name: String = match &self.current_token {
+            Token::Identifier(field_name) => field_name.to_string(),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        if fields.iter().any(|field| field.name() == field_name) {
+            return Err(CompilerError::DuplicateFieldError(
+                owner.to_string(),
+                field_name,
+            ));
+        }
+        let field_location: SourceLocation = self.current_location();
+
+        // eat field name
+        self.eat_current_token_and_advance_lexer()?;
+
+        let annotation: Option<Type> = self.parse_optional_type_annotation()?;
+        Ok(Field::new(&field_name, annotation, field_location))
+    }
+
+    /// Parse the name following a struct or enum keyword
+    fn parse_type_name(&mut self) -> Result<(String, SourceLocation), CompilerError> {
+        // eat struct or enum token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let name: String = match &self.current_token {
+            Token::Identifier(name) => name.to_string(),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat type name
+        self.eat_current_token_and_advance_lexer()?;
+
+        if self.current_token != Token::LeftBrace {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+        Ok((name, location))
+    }
+
+    /// Parse `struct Name { field, field: type, ... }`
+    fn parse_struct_definition(&mut self) -> Result<StructDefinition, CompilerError> {
+        let (name, location) = self.parse_type_name()?;
+        self.eat_current_token_and_advance_lexer()?; // eat '{'
+
+        let mut fields: Vec<Field> = Vec::new();
+        while self.current_token != Token::RightBrace {
+            let field: Field = self.parse_field_declaration(&name, &fields)?;
+            fields.push(field);
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBrace => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat '}'
+        Ok(StructDefinition::new(&name, fields, location))
+    }
+
+    /// Parse `enum Name { Variant, Variant(field, field: type), ... }`
+    fn parse_enum_definition(&mut self) -> Result<EnumDefinition, CompilerError> {
+        let (name, location) = self.parse_type_name()?;
+        self.eat_current_token_and_advance_lexer()?; // eat '{'
+
+        let mut variants: Vec<Variant> = Vec::new();
+        while self.current_token != Token::RightBrace {
+            let variant_name: String = match &self.current_token {
+                Token::Identifier(variant_name) => variant_name.to_string(),
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            };
+            if variants
+                .iter()
+                .any(|variant| variant.name() == variant_name)
+            {
+                return Err(CompilerError::DuplicateVariantError(variant_name));
+            }
+            let variant_location: SourceLocation = self.current_location();
+
+            // eat variant name
+            self.eat_current_token_and_advance_lexer()?;
+
+            let mut fields: Vec<Field> = Vec::new();
+            if self.current_token == Token::LeftParenthesis {
+                self.eat_current_token_and_advance_lexer()?; // eat '('
+                while self.current_token != Token::RightParenthesis {
+                    let field: Field = self.parse_field_declaration(&variant_name, &fields)?;
+                    fields.push(field);
+
+                    match self.current_token {
+                        Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                        Token::RightParenthesis => {}
+                        _ => {
+                            return Err(CompilerError::UnexpectedTokenError(
+                                self.current_token.clone(),
+                            ))
+                        }
+                    }
+                }
+                self.eat_current_token_and_advance_lexer()?; // eat ')'
+            }
+            variants.push(Variant::new(&variant_name, fields, variant_location));
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBrace => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat '}'
+        Ok(EnumDefinition::new(&name, variants, location))
+    }
+
+    fn handle_struct(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let definition: StructDefinition = self.parse_struct_definition()?;
+        if module.declares_type(definition.name()) {
+            return Err(
+                CompilerError::DuplicateTypeError(definition.name().to_string())
+                    .with_location(definition.location()),
+            );
+        }
+
+        definition.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed struct! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_struct(definition);
+        Ok(())
+    }
+
+    fn handle_enum(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let definition: EnumDefinition = self.parse_enum_definition()?;
+        if module.declares_type(definition.name()) {
+            return Err(
+                CompilerError::DuplicateTypeError(definition.name().to_string())
+                    .with_location(definition.location()),
+            );
+        }
+        // variants are used by name alone, so they have to be unique across enums
+        for variant in definition.variants() {
+            let declared: bool = module
+                .enums()
+                .iter()
+                .any(|e| e.variants().iter().any(|v| v.name() == variant.name()));
+            if declared {
+                return Err(
+                    CompilerError::DuplicateVariantError(variant.name().to_string())
+                        .with_location(variant.location()),
+                );
+            }
+        }
+
+        definition.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed enum! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_enum(definition);
+        Ok(())
+    }
+
+    /// Parse `const NAME = value` or `const NAME: type = value`
+    fn parse_constant(&mut self) -> Result<ConstantDefinition, CompilerError> {
+        // eat const token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let name: String = match &self.current_token {
+            Token::Identifier(name) => name.to_string(),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat constant name
+        self.eat_current_token_and_advance_lexer()?;
+
+        let annotation: Option<Type> = self.parse_optional_type_annotation()?;
+
+        if self.current_token != Token::SimpleBinaryOperator(SimpleBinaryOperater::Assignment) {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        // eat '='
+        self.eat_current_token_and_advance_lexer()?;
+        let initializer: Box<dyn ASTNode> = self.parse_expression()?;
+        Ok(ConstantDefinition::new(
+            &name,
+            annotation,
+            initializer,
+            location,
+        ))
+    }
+
+    fn handle_constant(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let definition: ConstantDefinition = self.parse_constant()?;
+        if module
+            .constants()
+            .iter()
+            .any(|constant| constant.name() == definition.name())
+        {
+            return Err(
+                CompilerError::DuplicateConstantError(definition.name().to_string())
+                    .with_location(definition.location()),
+            );
+        }
+
+        definition.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed constant! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_constant(definition);
+        Ok(())
+    }
+
+    /// Parse `macro name(pattern) => template`. The template is either a block, or every
+    /// token up to the end of the item
+    fn parse_macro_definition(&mut self) -> Result<MacroDefinition, CompilerError> {
+        // eat macro token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let name: String = match &self.current_token {
+            Token::Identifier(name) => name.to_string(),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat macro name
+        self.eat_current_token_and_advance_lexer()?;
+
+        if self.current_token != Token::LeftParenthesis {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+        let mut pattern: Vec<SourceToken> = self.parse_token_tree()?;
+        pattern.pop(); // drop ')'
+        pattern.remove(0); // drop '('
+
+        if self.current_token != Token::FatArrow {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+        // eat '=>'
+        self.eat_current_token_and_advance_lexer()?;
+
+        let mut template: Vec<SourceToken> = Vec::new();
+        if self.current_token == Token::LeftBrace {
+            template = self.parse_token_tree()?;
+        } else {
+            while !matches!(
+                self.current_token,
+                Token::SemiColon
+                    | Token::Eof
+                    | Token::At
+                    | Token::Def
+                    | Token::Pub
+                    | Token::Extern
+                    | Token::Struct
+                    | Token::Enum
+                    | Token::Const
+                    | Token::Macro
+                    | Token::Module
+                    | Token::Import
+            ) {
+                template.append(&mut self.parse_token_tree()?);
+            }
+        }
+        if template.is_empty() {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        MacroDefinition::new(&name, pattern, template, location)
+    }
+
+    fn handle_macro(&mut self) -> Result<(), CompilerError> {
+        let definition: MacroDefinition = self.parse_macro_definition()?;
+        if self.macros.contains_key(definition.name()) {
+            return Err(
+                CompilerError::DuplicateMacroError(definition.name().to_string())
+                    .with_location(definition.location()),
+            );
+        }
+
+        if self.verbose {
+            println!(
+                "Succesfully parsed macro {}! Current Token: {:?}",
+                definition.name(),
+                self.current_token
+            );
+        }
+        self.macros
+            .insert(definition.name().to_string(), definition);
+        Ok(())
+    }
+
+    /// Report every name used as a type that no struct or enum declaration introduced
+    fn check_type_references(&self, module: &Module) -> Vec<CompilerError> {
+        self.type_references
+            .iter()
+            .filter(|(name, _)| !module.declares_type(name))
+            .map(|(name, location)| {
+                CompilerError::UnknownTypeError(name.clone()).with_location(location)
+            })
+            .collect()
+    }
+
+    fn parse_top_level_expression(&mut self) -> Result<Box<Function>, CompilerError> {
+        let location: SourceLocation = self.current_location();
+        let expression = self.parse_expression()?;
+        let prototype: Box<FunctionPrototype> = Box::new(FunctionPrototype::new(
+            "",
+            Vec::new(),
+            None,
+            location.clone(),
+        ));
+        Ok(Box::new(Function::new(prototype, expression, location)))
+    }
+
+    fn handle_top_level_expression(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let parse_node = self.parse_top_level_expression()?;
+        parse_node.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed top level expression! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_top_level_expression(*parse_node);
+        Ok(())
+    }
+
+    fn parse_prototype(&mut self) -> Result<Box<FunctionPrototype>, CompilerError> {
+        if self.verbose {
+            println!("Start parsing prototype!");
+        }
+        if let Token::Identifier(id) = &self.current_token {
+            let function_name: String = id.to_string();
+            let location: SourceLocation = self.current_location();
+
+            // eat prototype name
+            self.eat_current_token_and_advance_lexer()?;
+
+            let type_parameters: Vec<String> = self.parse_type_parameter_list(&function_name)?;
+            self.type_parameters.extend(type_parameters.iter().cloned());
+            let args: Vec<Parameter> = self.parse_parameter_list(&function_name)?;
+            let return_type: Option<Type> = self.parse_optional_return_type()?;
+            let mut contracts: Vec<Contract> = self.parse_contracts()?;
+            if !self.contracts {
+                contracts.clear();
+            }
+
+            if self.verbose {
+                println!("prototype parsed!!");
+            }
+            Ok(Box::new(
+                FunctionPrototype::new(&function_name, args, return_type, location)
+                    .with_type_parameters(type_parameters)
+                    .with_contracts(contracts),
+            ))
+        } else {
+            Err(CompilerError::FunctionNameNotFound)
+        }
+    }
+
+    /// Parse `<T, U>` if the current token starts a list of type parameters
+    fn parse_type_parameter_list(
+        &mut self,
+        function_name: &str,
+    ) -> Result<Vec<String>, CompilerError> {
+        let mut type_parameters: Vec<String> = Vec::new();
+        if self.current_token != Token::SimpleBinaryOperator(SimpleBinaryOperater::LessThan) {
+            return Ok(type_parameters);
+        }
+
+        // eat '<'
+        self.eat_current_token_and_advance_lexer()?;
+
+        while self.current_token != Token::SimpleBinaryOperator(SimpleBinaryOperater::GreaterThan) {
+            let name: String = match &self.current_token {
+                Token::Identifier(name) => name.to_string(),
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            };
+            if type_parameters.contains(&name) {
+                return Err(CompilerError::DuplicateTypeParameterError(
+                    function_name.to_string(),
+                    name,
+                ));
+            }
+            type_parameters.push(name);
+
+            // eat type parameter name
+            self.eat_current_token_and_advance_lexer()?;
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::SimpleBinaryOperator(SimpleBinaryOperater::GreaterThan) => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        // eat '>'
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(type_parameters)
+    }
+
+    /// Parse a parenthesized, comma separated list of parameters
+    fn parse_parameter_list(
+        &mut self,
+        function_name: &str,
+    ) -> Result<Vec<Parameter>, CompilerError> {
+        if self.current_token != Token::LeftParenthesis {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        self.eat_current_token_and_advance_lexer()?;
+
+        let mut args: Vec<Parameter> = Vec::new();
+        while self.current_token != Token::RightParenthesis {
+            let arg: Parameter = self.parse_parameter()?;
+            if self.verbose {
+                println!("parse_protoype: Prototype arg found: {}\n", arg);
+            }
+            if args.iter().any(|a| a.name() == arg.name()) {
+                return Err(CompilerError::DuplicateParameterError(
+                    function_name.to_string(),
+                    arg.name().to_string(),
+                )
+                .with_location(arg.location()));
+            }
+            if arg.default().is_none() && args.iter().any(|a| a.default().is_some()) {
+                return Err(CompilerError::DefaultParameterOrderError(
+                    function_name.to_string(),
+                    arg.name().to_string(),
+                ));
+            }
+            args.push(arg);
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+
+        // eat ) token
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(args)
+    }
+
+    /// Parse `-> type` if the current token starts one
+    fn parse_optional_return_type(&mut self) -> Result<Option<Type>, CompilerError> {
+        if self.current_token != Token::Arrow {
+            return Ok(None);
+        }
+
+        // eat '->'
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(Some(self.parse_type()?))
+    }
+
+    /// Parse any number of `requires condition` and `ensures condition` clauses. A condition
+    /// cannot be an assignment, so a `=` after it starts the function body
+    fn parse_contracts(&mut self) -> Result<Vec<Contract>, CompilerError> {
+        let mut contracts: Vec<Contract> = Vec::new();
+        loop {
+            let kind: ContractKind = match self.current_token {
+                Token::Requires => ContractKind::Requires,
+                Token::Ensures => ContractKind::Ensures,
+                _ => return Ok(contracts),
+            };
+            let location: SourceLocation = self.current_location();
+
+            // eat requires or ensures token
+            self.eat_current_token_and_advance_lexer()?;
+
+            let condition: Box<dyn ASTNode> =
+                self.parse_expression_with_binding_power(OperatorPrecedence::WITHOUT_ASSIGNMENT)?;
+            contracts.push(Contract::new(kind, condition, location));
+        }
+    }
+
+    /// Parse an anonymous function, `fn(params) body` with an optional `-> type` before the
+    /// body. The body extends as far to the right as it can
+    fn parse_lambda_expression(&mut self) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let location: SourceLocation = self.current_location();
+
+        // eat fn token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let args: Vec<Parameter> = self.parse_parameter_list("fn")?;
+        if let Some(arg) = args.iter().find(|arg| arg.default().is_some()) {
+            return Err(CompilerError::LambdaDefaultParameterError(
+                arg.name().to_string(),
+            ));
+        }
+        let return_type: Option<Type> = self.parse_optional_return_type()?;
+        let prototype: Box<FunctionPrototype> =
+            Box::new(FunctionPrototype::new("", args, return_type, location));
+
+        let body_location: SourceLocation = self.current_location();
+        let body: Box<dyn ASTNode> = self.function_body(false, Self::parse_expression)?;
+        Ok(Box::new(LambdaExpression::new(
+            prototype,
+            body,
+            body_location,
+        )))
+    }
+
+    /// Parse `name` or `name = default`
+    fn parse_parameter(&mut self) -> Result<Parameter, CompilerError> {
+        let name: String = match &self.current_token {
+            Token::Identifier(name) => name.to_string(),
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+        let location: SourceLocation = self.current_location();
+
+        // eat parameter name
+        self.eat_current_token_and_advance_lexer()?;
+
+        let annotation: Option<Type> = self.parse_optional_type_annotation()?;
+
+        if self.current_token != Token::SimpleBinaryOperator(SimpleBinaryOperater::Assignment) {
+            return Ok(Parameter::new(&name, annotation, None, location));
+        }
+
+        // eat '='
+        self.eat_current_token_and_advance_lexer()?;
+        let default: Box<dyn ASTNode> = self.parse_expression()?;
+        Ok(Parameter::new(&name, annotation, Some(default), location))
+    }
+
+    /// Parse a type such as `f64`, `[bool]`, `(i64, str)`, `gen[f64]`, the name of a struct or
+    /// a type parameter of the function being parsed
+    fn parse_type(&mut self) -> Result<Type, CompilerError> {
+        if self.current_token == Token::LeftBracket {
+            self.eat_current_token_and_advance_lexer()?; // eat '['
+            let element_type: Type = self.parse_type()?;
+
+            if self.current_token != Token::RightBracket {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ));
+            }
+
+            self.eat_current_token_and_advance_lexer()?; // eat ']'
+            return Ok(Type::Array(Box::new(element_type)));
+        }
+
+        if self.current_token == Token::LeftParenthesis {
+            return self.parse_parenthesized_type();
+        }
+
+        let parsed_type: Type = match &self.current_token {
+            Token::Identifier(name) if name == "gen" || name == "result" => {
+                let name: String = name.to_string();
+                return self.parse_builtin_type(&name);
+            }
+            Token::Identifier(name) if self.type_parameters.contains(name) => {
+                Type::Generic(name.to_string())
+            }
+            Token::Identifier(name) => match Type::from_name(name) {
+                Some(parsed_type) => parsed_type,
+                None => {
+                    self.type_references
+                        .push((name.to_string(), self.current_location()));
+                    Type::Named(name.to_string())
+                }
+            },
+            _ => {
+                return Err(CompilerError::UnexpectedTokenError(
+                    self.current_token.clone(),
+                ))
+            }
+        };
+
+        // eat type name
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(parsed_type)
+    }
+
+    /// Parse `gen[T]`, the type of a generator yielding values of type `T`, or `result[T, E]`.
+    /// Without the `[` the name refers to a declared type like any other
+    fn parse_builtin_type(&mut self, name: &str) -> Result<Type, CompilerError> {
+        let location: SourceLocation = self.current_location();
+
+        // eat type name
+        self.eat_current_token_and_advance_lexer()?;
+
+        if self.current_token != Token::LeftBracket {
+            self.type_references.push((name.to_string(), location));
+            return Ok(Type::Named(name.to_string()));
+        }
+
+        self.eat_current_token_and_advance_lexer()?; // eat '['
+        let mut arguments: Vec<Type> = Vec::new();
+        while self.current_token != Token::RightBracket {
+            arguments.push(self.parse_type()?);
+
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightBracket => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+        self.eat_current_token_and_advance_lexer()?; // eat ']'
+
+        let (parsed_type, expected): (Option<Type>, usize) = match (name, arguments.as_slice()) {
+            ("gen", [element]) => (Some(Type::Generator(Box::new(element.clone()))), 1),
+            ("gen", _) => (None, 1),
+            (_, [value, error]) => (
+                Some(Type::Result(
+                    Box::new(value.clone()),
+                    Box::new(error.clone()),
+                )),
+                2,
+            ),
+            (_, _) => (None, 2),
+        };
+        parsed_type.ok_or_else(|| {
+            CompilerError::TypeArgumentCountError(name.to_string(), expected, arguments.len())
+                .with_location(&location)
+        })
+    }
+
+    /// Parse a type starting with '(': a function type such as `(f64, f64) -> bool`, a tuple
+    /// type such as `(i64, str)` or `(i64,)`, or a single type in parentheses
+    fn parse_parenthesized_type(&mut self) -> Result<Type, CompilerError> {
+        self.eat_current_token_and_advance_lexer()?; // eat '('
+
+        let mut element_types: Vec<Type> = Vec::new();
+        let mut trailing_comma: bool = false;
+        while self.current_token != Token::RightParenthesis {
+            element_types.push(self.parse_type()?);
+
+            trailing_comma = self.current_token == Token::Comma;
+            match self.current_token {
+                Token::Comma => self.eat_current_token_and_advance_lexer()?, // eat ','
+                Token::RightParenthesis => {}
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+        }
+        self.eat_current_token_and_advance_lexer()?; // eat ')'
+
+        if let Some(return_type) = self.parse_optional_return_type()? {
+            return Ok(Type::Function(element_types, Box::new(return_type)));
+        }
+        match element_types.len() {
+            0 => Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            )),
+            1 if !trailing_comma => Ok(element_types.remove(0)),
+            _ => Ok(Type::Tuple(element_types)),
+        }
+    }
+
+    /// Parse `: type` if the current token starts one
+    fn parse_optional_type_annotation(&mut self) -> Result<Option<Type>, CompilerError> {
+        if self.current_token != Token::Colon {
+            return Ok(None);
+        }
+
+        // eat ':'
+        self.eat_current_token_and_advance_lexer()?;
+        Ok(Some(self.parse_type()?))
+    }
+
+    fn parse_definition(&mut self) -> Result<Box<Function>, CompilerError> {
+        if self.verbose {
+            println!("Start parsing definition!");
+        }
+        if self.current_token != Token::Def {
+            return Err(CompilerError::UnexpectedTokenError(
+                self.current_token.clone(),
+            ));
+        }
+
+        // eat Def token and expect func name
+        self.eat_current_token_and_advance_lexer()?;
+        let attributes: Vec<Attribute> = self.take_attributes(false);
+
+        let (prototype, body_location, definition_expression) =
+            self.with_type_parameters(|ast| {
+                let prototype: Box<FunctionPrototype> = ast.parse_prototype()?;
+                // a `=` can separate the body from the prototype, which reads better after
+                // contracts
+                if ast.current_token
+                    == Token::SimpleBinaryOperator(SimpleBinaryOperater::Assignment)
+                {
+                    ast.eat_current_token_and_advance_lexer()?; // eat '='
+                }
+                let body_location: SourceLocation = ast.current_location();
+                Ok((
+                    prototype,
+                    body_location,
+                    ast.function_body(true, Self::parse_expression)?,
+                ))
+            })?;
+
+        if self.verbose {
+            println!("Finished parsing definition!");
+        }
+        Ok(Box::new(Function::new(
+            Box::new(prototype.with_attributes(attributes)),
+            definition_expression,
+            body_location,
+        )))
+    }
+
+    fn handle_definition(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        if self.verbose {
+            println!("Start handling def!");
+        }
+        let defintion_node = self.parse_definition()?;
+        if !self.declare_function_name(module, defintion_node.prototype()) {
+            return Ok(());
+        }
+        defintion_node.print();
+
+        if self.verbose {
+            println!(
+                "Succesfully parsed definition! Current Token: {:?}",
+                self.current_token
+            );
+        }
+
+        module.add_function(*defintion_node);
+        Ok(())
+    }
+
+    /// Functions and externs share one table, so no two of them can have the same name. The
+    /// item was parsed in full, so the error is recorded and parsing carries on after it
+    fn declare_function_name(&mut self, module: &Module, prototype: &FunctionPrototype) -> bool {
+        if module.declares_function(prototype.name()) {
+            self.errors.push(
+                CompilerError::DuplicateFunctionError(prototype.name().to_string())
+                    .with_location(prototype.location()),
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Parse `pub def ...`, a definition other modules can use once they import this one
+    fn handle_public_definition(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        // eat pub token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let definition: Box<Function> = self.parse_definition()?;
+        if !self.declare_function_name(module, definition.prototype()) {
+            return Ok(());
+        }
+        definition.print();
+        if self.verbose {
+            println!(
+                "Succesfully parsed public definition! Current Token: {:?}",
+                self.current_token
+            );
+        }
+        module.add_function(definition.into_public());
+        Ok(())
+    }
+
+    /// Parse a module path such as `math.helpers`
+    fn parse_module_path(&mut self) -> Result<Vec<String>, CompilerError> {
+        let mut path: Vec<String> = Vec::new();
+        loop {
+            match &self.current_token {
+                Token::Identifier(segment) => path.push(segment.to_string()),
+                _ => {
+                    return Err(CompilerError::UnexpectedTokenError(
+                        self.current_token.clone(),
+                    ))
+                }
+            }
+
+            // eat path segment
+            self.eat_current_token_and_advance_lexer()?;
+
+            if self.current_token != Token::Dot {
+                return Ok(path);
+            }
+            self.eat_current_token_and_advance_lexer()?; // eat '.'
+        }
+    }
+
+    /// Parse `module math.helpers`, naming the module the file defines. It has to come first
+    fn handle_module_declaration(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let location: SourceLocation = self.current_location();
+        if !module.is_empty() || module.name().is_some() {
+            return Err(CompilerError::MisplacedModuleDeclarationError.with_location(&location));
+        }
+
+        // eat module token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let path: Vec<String> = self.parse_module_path()?;
+        module.set_name(&path.join("."));
+        Ok(())
+    }
+
+    /// Parse `import math.helpers`
+    fn handle_import(&mut self, module: &mut Module) -> Result<(), CompilerError> {
+        let location: SourceLocation = self.current_location();
+
+        // eat import token
+        self.eat_current_token_and_advance_lexer()?;
+
+        let path: Vec<String> = self.parse_module_path()?;
+        if self.verbose {
+            println!("Found import of {}", path.join("."));
+        }
+        module.add_import(Import::new(path, location));
+        Ok(())
+    }
+
+    /// Load every module `module` imports, making what they export available to it. Errors
+    /// found inside the imported modules are returned, to be reported ahead of this file's own
+    fn resolve_imports(
+        &mut self,
+        module: &mut Module,
+        loader: &mut ModuleLoader,
+    ) -> Vec<CompilerError> {
+        let file: PathBuf = PathBuf::from(self.lexer.current_file());
+        let name: String = match module.name() {
+            Some(name) => name.to_string(),
+            None => file
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().to_string()),
+        };
+
+        let mut imported_errors: Vec<CompilerError> = Vec::new();
+        loader.enter(&file, &name);
+        for import in module.imports().to_vec() {
+            match loader.import(&import, &file) {
+                Ok((interface, mut errors)) => {
+                    imported_errors.append(&mut errors);
+                    let mut conflicts: Vec<CompilerError> =
+                        module.check_import(&interface, import.location());
+                    self.errors.append(&mut conflicts);
+                    module.add_interface(interface);
+                }
+                Err(e) => self.errors.push(e.with_location(import.location())),
+            }
+        }
+        loader.leave();
+        imported_errors
+    }
+
+    /// Parse tokens in the source file. Errors do not stop parsing: each one is recorded, the
+    /// parser skips ahead to the next `;`, `def` or `extern`, and carries on. Items that parsed
+    /// are returned in the module alongside every error collected on the way, in the order they
+    /// appear in the file
+    ///
+    /// # Arguments
+    /// * `self` - a mutable reference to self - a Parser that owns a lexer buffer and a current
+    ///   token
+    pub fn parse_tokens(&mut self) -> (Module, Vec<CompilerError>) {
+        let mut loader: ModuleLoader = ModuleLoader::new(Vec::new(), self.verbose);
+        self.parse_tokens_with(&mut loader)
+    }
+
+    /// Parse tokens in the source file as `parse_tokens` does, loading the modules it imports
+    /// through `loader`
+    pub fn parse_tokens_with(&mut self, loader: &mut ModuleLoader) -> (Module, Vec<CompilerError>) {
+        let mut module: Module = Module::new();
+
+        if self.verbose {
+            println!("***************************************************************************");
+            println!("*                       In the parsing tokens stage!                      *");
+            println!("***************************************************************************");
+            println!(" ");
+        }
+        // eat the beginning of file token
+        if let Err(e) = self.eat_current_token_and_advance_lexer() {
+            self.record_error(e);
+            return (module, std::mem::take(&mut self.errors));
+        }
+
+        loop {
+            if self.verbose {
+                println!("*********************************************************************\n");
+            }
+            let result: Result<(), CompilerError> = match self.current_token {
+                Token::Eof => break,
+                Token::SemiColon => self.eat_current_token_and_advance_lexer(),
+                Token::Def => self.handle_definition(&mut module),
+                Token::Pub => self.handle_public_definition(&mut module),
+                Token::Module => self.handle_module_declaration(&mut module),
+                Token::Import => self.handle_import(&mut module),
+                Token::Extern => self.handle_extern(&mut module),
+                Token::Struct => self.handle_struct(&mut module),
+                Token::Enum => self.handle_enum(&mut module),
+                Token::Const => self.handle_constant(&mut module),
+                Token::Macro => self.handle_macro(),
+                Token::At => self.parse_attributes(),
+                _ => self.handle_top_level_expression(&mut module),
+            };
+
+            if let Err(e) = result {
+                self.record_error(e);
+                self.synchronize();
+            }
+        }
+
+        let mut errors: Vec<CompilerError> = self.resolve_imports(&mut module, loader);
+
+        let mut type_errors: Vec<CompilerError> = self.check_type_references(&module);
+        self.errors.append(&mut type_errors);
+        self.errors.append(&mut module.check_recursive_structs());
+        self.errors.append(&mut module.check_scope());
+        self.errors.append(&mut module.check_calls());
+        self.errors.sort_by_key(|e| {
+            e.location()
+                .map_or((0, 0), |location| (location.line(), location.column()))
+        });
+
+        errors.append(&mut self.errors);
+        (module, errors)
+    }
+}
+
+#[cfg(test)]
+mod ast_tests {
+    use std::{env::current_dir, path::PathBuf};
+
+    use common::{
+        error::CompilerError,
+        source_location::SourceLocation,
+        token::Token,
+        types::{Constraint, Type},
+        warning::{CompilerWarning, WarningKind},
+    };
+    use lexer::lexer::Lexer;
+
+    use crate::{
+        ast::Ast,
+        ast_node::ASTNode,
+        ast_nodes::functions::{attribute::AttributeKind, function_definition::Function},
+        function_signature::FunctionSignature,
+        module::Module,
+        module_loader::ModuleLoader,
+    };
+
+    fn parse_test_file(file_name: &str) -> (Module, Vec<CompilerError>) {
+        let mut path: PathBuf = current_dir().unwrap();
+        path.push("..");
+        path.push("test_utils");
+        path.push(file_name);
+
+        let mut lexer: Lexer = Lexer::new(path).unwrap();
+        let mut ast: Ast = Ast::new(&mut lexer, false);
+        ast.parse_tokens()
+    }
+
+    /// Split a located error into its line, column and underlying cause
+    fn located_diagnostic(error: &CompilerError) -> (usize, usize, &CompilerError) {
+        match error {
+            CompilerError::LocatedError(location, cause) => {
+                (location.line(), location.column(), cause.root_cause())
+            }
+            _ => panic!("type errors should carry a location"),
+        }
+    }
+
+    /// Split a warning into its line, column and what it points out
+    fn located_warning(warning: &CompilerWarning) -> (usize, usize, &WarningKind) {
+        let location: &SourceLocation = warning.location();
+        (location.line(), location.column(), warning.kind())
+    }
+
+    #[test]
+    fn test_mutable_variables() {
+        let (module, errors) = parse_test_file("mutable_variables.fr");
+        assert!(errors.is_empty());
+        assert_eq!(module.functions().len(), 2);
+    }
+
+    #[test]
+    fn test_assign_to_parameter() {
+        let (_, errors) = parse_test_file("assign_to_parameter.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (2, 5, CompilerError::AssignmentToImmutableError(name)) if name == "x"
+        ));
+        // every unbound name of a function is reported where it is used
+        assert!(matches!(
+            diagnostics[1],
+            (4, 8, CompilerError::UndefinedVariableError(name)) if name == "zz"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (4, 17, CompilerError::UndefinedVariableError(name)) if name == "zz"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (4, 22, CompilerError::UndefinedVariableError(name)) if name == "yy"
+        ));
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let (module, errors) = parse_test_file("error_recovery.fr");
+        assert_eq!(errors.len(), 5);
+        assert!(errors
+            .iter()
+            .all(|e| matches!(e, CompilerError::LocatedError(..))));
+        assert!(matches!(
+            located_diagnostic(&errors[3]),
+            (18, 9, CompilerError::InvalidAssignmentTargetError(target)) if target == "(+ 1 2)"
+        ));
+        // running out of tokens is reported at the last one rather than past the final line
+        assert!(matches!(
+            located_diagnostic(&errors[4]),
+            (20, 21, CompilerError::UnexpectedTokenError(Token::Eof))
+        ));
+        assert_eq!(module.functions().len(), 2);
+        assert_eq!(module.externs().len(), 1);
+        assert_eq!(module.top_level_expressions().len(), 1);
+    }
+
+    #[test]
+    fn test_expression_precedence() {
+        let expected: [&str; 16] = [
+            "(+ 1 (* 2 3))",
+            "(- (- a b) c)",
+            "(/ (/ a b) c)",
+            "(> a (+ b 1))",
+            "(< a b)",
+            "(- x)",
+            "(* (- 1) 2)",
+            "(- (- x))",
+            "(* a (- b))",
+            "(- (f x (+ y 1)))",
+            "(var ((x) (y)) (= x (= y (* 2 3))))",
+            "(* (+ 1 2) 3)",
+            "(g)",
+            "(< 1 2)",
+            "(&& (< 0 x) (< x 10))",
+            "(<= 0 x)",
+        ];
+
+        let (module, _) = parse_test_file("expressions.fr");
+        let parsed: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_call_arguments() {
+        let (module, errors) = parse_test_file("call_arguments.fr");
+        assert_eq!(module.top_level_expressions().len(), 9);
+        assert_eq!(
+            module.top_level_expressions()[1].body().to_s_expression(),
+            "(fall 2 drag:0.5 gravity:1.62)"
+        );
+
+        let root_causes: Vec<&CompilerError> = errors.iter().map(|e| e.root_cause()).collect();
+        assert_eq!(root_causes.len(), 6);
+        assert!(matches!(
+            root_causes[0],
+            CompilerError::PositionalAfterNamedArgumentError(_)
+        ));
+        assert!(matches!(
+            root_causes[1],
+            CompilerError::MissingArgumentError(_, p) if p == "time"
+        ));
+        assert!(matches!(
+            root_causes[2],
+            CompilerError::TooManyArgumentsError(_, 3, 4)
+        ));
+        assert!(matches!(
+            root_causes[3],
+            CompilerError::UnknownNamedArgumentError(_, a) if a == "mass"
+        ));
+        assert!(matches!(
+            root_causes[4],
+            CompilerError::DuplicateArgumentError(_, p) if p == "time"
+        ));
+        assert!(matches!(
+            root_causes[5],
+            CompilerError::UndefinedFunctionError(f) if f == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_block_expressions() {
+        let (module, errors) = parse_test_file("blocks.fr");
+        assert_eq!(module.functions().len(), 2);
+        assert_eq!(
+            module.functions()[0].body().to_s_expression(),
+            "(block (log mass) (var ((half 0.5) (squared))) (= squared (* velocity velocity)) \
+             (def square (x) (* x x)) (+ (* (* half mass) (square velocity)) (* 0 squared)))"
+        );
+        assert_eq!(
+            module.top_level_expressions()[0].body().to_s_expression(),
+            "(block (var ((counter 1))) (= counter (+ counter 1)) counter)"
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0].root_cause(),
+            CompilerError::UndefinedVariableError(name) if name == "x"
+        ));
+        assert!(matches!(
+            errors[1].root_cause(),
+            CompilerError::BlockWithoutValueError
+        ));
+    }
+
+    #[test]
+    fn test_type_checking() {
+        let (module, errors) = parse_test_file("types.fr");
+        assert!(errors.is_empty());
+        assert_eq!(
+            module.functions()[0].prototype().signature_s_expression(),
+            "(x:i64 y:f64) -> bool"
+        );
+
+        let (_signatures, errors) = module.check_types();
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                20,
+                31,
+                CompilerError::InvalidOperandTypesError("+", Type::F64, Type::Bool)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (21, 29, CompilerError::TypeConflictError(Type::I64, Type::F64, origin))
+                if (origin.line(), origin.column()) == (21, 5)
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (22, 40, CompilerError::TypeConflictError(Type::I64, Type::F64, origin))
+                if (origin.line(), origin.column()) == (22, 26)
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (19, 7, CompilerError::TypeConflictError(Type::I64, Type::F64, origin))
+                if (origin.line(), origin.column()) == (4, 11)
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (
+                23,
+                1,
+                CompilerError::InvalidUnaryOperandTypeError("-", Type::Bool)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_type_inference() {
+        let (module, errors) = parse_test_file("inference.fr");
+        assert!(errors.is_empty());
+
+        let (signatures, errors) = module.check_types();
+        let signatures: Vec<String> = signatures
+            .iter()
+            .map(|signature| signature.to_string())
+            .collect();
+        assert_eq!(
+            signatures[..10],
+            [
+                "identity(x: 'a) -> 'a",
+                "add(x: 'a, y: 'a) -> 'a where 'a: numeric",
+                "below(x: 'a, y: 'a) -> bool where 'a: numeric",
+                "pick(flag: 'a) -> f64",
+                "countdown(n: 'a) -> 'b where 'a: numeric",
+                "ping(n: 'a) -> 'b",
+                "pong(n: 'a) -> 'b",
+                "twice(x: 'a) -> 'a where 'a: numeric",
+                "uses_later(x: i64) -> i64",
+                "later(y: 'a) -> 'a",
+            ]
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                20,
+                37,
+                CompilerError::InvalidOperandTypesError("+", Type::Bool, Type::I64)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (21, 42, CompilerError::TypeConflictError(Type::Bool, Type::I64, origin))
+                if (origin.line(), origin.column()) == (21, 26)
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (22, 39, CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Bool, origin))
+                if (origin.line(), origin.column()) == (22, 31)
+        ));
+        // types inference could not work out are named in the order the message mentions them
+        assert_eq!(diagnostics[3].0, 24);
+        assert_eq!(
+            diagnostics[3].2.to_string(),
+            "Operator + cannot be applied to values of type ['a] and str"
+        );
+        assert!(matches!(
+            diagnostics[4],
+            (23, 8, CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Bool, origin))
+                if (origin.line(), origin.column()) == (23, 5)
+        ));
+    }
+
+    #[test]
+    fn test_strings() {
+        let (module, errors) = parse_test_file("strings.fr");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            located_diagnostic(&errors[0]),
+            (23, 9, CompilerError::UnterminatedStringError(23, _))
+        ));
+
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[2], r#"(< "tab\tquote\"backslash\\" "z")"#);
+        assert_eq!(top_level.len(), 6);
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "greeting(name: str) -> str");
+        assert_eq!(
+            signatures[3].to_string(),
+            "report(label: str, value: 'a) -> i64"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                20,
+                11,
+                CompilerError::InvalidOperandTypesError("+", Type::Str, Type::I64)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (
+                21,
+                1,
+                CompilerError::InvalidUnaryOperandTypeError("-", Type::Str)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (
+                22,
+                5,
+                CompilerError::UnsatisfiedConstraintError(Constraint::Collection, Type::I64, _)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_arrays() {
+        let (module, errors) = parse_test_file("arrays.fr");
+        assert!(errors.is_empty());
+
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[0], "(sum_first (array 1.5 2 3))");
+        assert_eq!(top_level[3], "(index (index (grid) 1) 0)");
+        assert_eq!(top_level[4], "(array)");
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "sum_first(xs: [f64]) -> f64");
+        assert_eq!(signatures[1].to_string(), "last(xs: ['a]) -> 'a");
+        assert_eq!(signatures[2].to_string(), "grid() -> [[i64]]");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (13, 11, CompilerError::IndexOutOfBoundsError(3, 3))
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (14, 3, CompilerError::NotAnArrayError(Type::I64))
+        ));
+        // an integer literal is a number of some type, so anything else conflicts with that
+        assert!(matches!(
+            diagnostics[2],
+            (15, 1, CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, origin))
+                if (origin.line(), origin.column()) == (15, 2)
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (
+                16,
+                8,
+                CompilerError::TypeMismatchError(Type::I64, Type::F64)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_structs() {
+        let (module, errors) = parse_test_file("structs.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (19, 15, CompilerError::UnknownTypeError(name)) if name == "Pointt"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (20, 8, CompilerError::DuplicateTypeError(name)) if name == "Point"
+        ));
+        // a struct can only hold itself in an array, also when it does so through other types
+        assert!(matches!(
+            diagnostics[2],
+            (21, 27, CompilerError::RecursiveFieldError(name, field))
+                if name == "Node" && field == "next"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (22, 15, CompilerError::RecursiveFieldError(name, field))
+                if name == "Pair" && field == "left"
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (23, 15, CompilerError::RecursiveFieldError(name, field))
+                if name == "Tree" && field == "pair"
+        ));
+        assert_eq!(
+            module.structs()[1].to_s_expression(),
+            "(struct Labelled (label:str at:Point weight:i64))"
+        );
+
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(
+            top_level[1],
+            "(. (. (struct Labelled at:(struct Point y:1 x:2) label:\"a\" weight:1) at) y)"
+        );
+        assert_eq!(
+            module.functions()[2].body().to_s_expression(),
+            "(struct Point x:(+ (. p x) dx) ..p)"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[1].to_string(), "norm(p: Point) -> f64");
+        assert_eq!(
+            signatures[2].to_string(),
+            "shift(p: Point, dx: f64) -> Point"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (14, 1, CompilerError::MissingFieldError(name, field))
+                if name == "Point" && field == "y"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (15, 21, CompilerError::UnknownFieldError(name, field))
+                if name == "Point" && field == "z"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (16, 9, CompilerError::TypeConflictError(Type::F64, Type::Str, origin))
+                if (origin.line(), origin.column()) == (2, 16)
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (17, 10, CompilerError::UnknownFieldError(name, field))
+                if name == "Point" && field == "z"
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (18, 5, CompilerError::NotAStructError(Type::I64, field)) if field == "x"
+        ));
+    }
+
+    #[test]
+    fn test_integer_literals() {
+        let (module, errors) = parse_test_file("literals.fr");
+        assert!(errors.is_empty());
+
+        // an integer literal takes the type of the number it is used with, so only a function
+        // returning nothing but the literal is fixed to i64
+        let (signatures, errors) = module.check_types();
+        assert_eq!(
+            signatures[0].to_string(),
+            "increment(x: 'a) -> 'a where 'a: numeric"
+        );
+        assert_eq!(signatures[1].to_string(), "one() -> i64");
+        assert_eq!(
+            signatures[2].to_string(),
+            "integrate(f: (f64) -> f64, a: f64, b: f64) -> f64"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            (13, 11, CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, origin))
+                if (origin.line(), origin.column()) == (2, 5)
+        ));
+    }
+
+    #[test]
+    fn test_lambdas() {
+        let (module, errors) = parse_test_file("lambdas.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(matches!(
+            diagnostics[0],
+            (20, _, CompilerError::LambdaDefaultParameterError(name)) if name == "x"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (21, 8, CompilerError::UndefinedFunctionError(name)) if name == "unknown"
+        ));
+
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[1], "((compose double (adder 1)) 4)");
+        assert_eq!(
+            top_level[3],
+            "(var ((scale 3)) (apply (fn (x) (* x scale)) 2))"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(
+            signatures[0].to_string(),
+            "apply(f: ('a) -> 'b, x: 'a) -> 'b"
+        );
+        assert_eq!(
+            signatures[1].to_string(),
+            "compose(f: ('a) -> 'b, g: ('c) -> 'a) -> ('c) -> 'b"
+        );
+        assert_eq!(
+            signatures[3].to_string(),
+            "integrate(f: (f64) -> f64, a: f64, b: f64) -> f64"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                16,
+                7,
+                CompilerError::TypeConflictError(Type::Function(..), Type::I64, _)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (17, 9, CompilerError::ArgumentCountError(1, 2))
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (
+                18,
+                20,
+                CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, _)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (19, 23, CompilerError::NotAFunctionError(Type::I64))
+        ));
+    }
+
+    #[test]
+    fn test_tuples() {
+        let (module, errors) = parse_test_file("tuples.fr");
+        assert!(errors.is_empty());
+
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[0], "(var (((q,r) (divmod 17 5))) (+ (* q 5) r))");
+        assert_eq!(
+            top_level[1],
+            "(var ((nested (tuple 1 (tuple \"two\" 3)))) (. (. nested 1) 0))"
+        );
+        assert_eq!(top_level[4], "(tuple 42)");
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(
+            signatures[0].to_string(),
+            "divmod(a: i64, b: i64) -> (i64, i64)"
+        );
+        assert_eq!(
+            signatures[1].to_string(),
+            "swap(pair: (i64, str)) -> (str, i64)"
+        );
+        assert_eq!(signatures[2].to_string(), "first(t: (f64, str)) -> f64");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (16, 17, CompilerError::UnknownTupleTypeError(1))
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (13, 8, CompilerError::TupleIndexOutOfBoundsError(2, 2))
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (14, 16, CompilerError::NotATupleError(Type::I64, 0))
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (
+                15,
+                5,
+                CompilerError::TypeMismatchError(Type::Tuple(..), Type::Tuple(..))
+            )
+        ));
+    }
+
+    #[test]
+    fn test_enums() {
+        let (module, errors) = parse_test_file("enums.fr");
+        assert!(errors.is_empty());
+        assert_eq!(
+            module.enums()[0].to_s_expression(),
+            "(enum Shape (Circle r) (Rect w h) Empty)"
+        );
+        assert_eq!(
+            module.functions()[0].body().to_s_expression(),
+            "(match s ((Circle r) (* (* 3.14 r) r)) ((Rect w h) (if (> w h)) (* w h)) \
+             ((Rect w h) (* h w)) (Empty 0))"
+        );
+        assert_eq!(
+            module.functions()[3].body().to_s_expression(),
+            "(match (tuple a b) ((tuple true true) 1) ((tuple false _) 2) ((tuple _ false) 3))"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "area(s: Shape) -> f64");
+        assert_eq!(signatures[1].to_string(), "next(l: Light) -> Light");
+        assert_eq!(signatures[2].to_string(), "describe(n: i64) -> str");
+        assert_eq!(signatures[3].to_string(), "both(a: bool, b: bool) -> i64");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 7);
+        assert!(matches!(
+            diagnostics[0],
+            (19, 23, CompilerError::NonExhaustiveMatchError(missing)) if missing == "Empty"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (20, 42, CompilerError::UnreachableArmError)
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (21, 21, CompilerError::NonExhaustiveMatchError(missing)) if missing == "_"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (22, 30, CompilerError::NonExhaustiveMatchError(missing))
+                if missing == "(false, Amber)"
+        ));
+        // a capitalized name is a variant, so a misspelled one does not bind the value
+        assert!(matches!(
+            diagnostics[4],
+            (25, 36, CompilerError::UnknownVariantError(name)) if name == "Circl"
+        ));
+        assert!(matches!(
+            diagnostics[5],
+            (23, 15, CompilerError::UnknownVariantError(name)) if name == "Square"
+        ));
+        assert!(matches!(
+            diagnostics[6],
+            (24, 15, CompilerError::PatternArityError(name, 1, 0)) if name == "Circle"
+        ));
+    }
+
+    #[test]
+    fn test_modules() {
+        let (module, errors) = parse_test_file("modules.fr");
+        assert_eq!(module.name(), Some("modules"));
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (1, 1, CompilerError::CircularImportError(cycle))
+                if cycle.join(" -> ") == "cycle_a -> cycle_b -> cycle_a"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (4, 1, CompilerError::ModuleNotFoundError(name, _)) if name == "geometry.missing"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (9, 12, CompilerError::PrivateFunctionError(name, module))
+                if name == "unit_circle" && module == "geometry.circles"
+        ));
+        // circles imports square but does not pass it on
+        assert!(matches!(
+            diagnostics[3],
+            (10, 7, CompilerError::UndefinedFunctionError(name)) if name == "square"
+        ));
+
+        // the calls to unit_circle and square stay unresolved
+        let (signatures, errors) = module.check_types();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(signatures[0].to_string(), "double_area(r: f64) -> f64");
+
+        let mut path: PathBuf = current_dir().unwrap();
+        path.push("..");
+        path.push("test_utils");
+        let mut search_root: PathBuf = path.clone();
+        search_root.push("geometry");
+        path.push("search_roots.fr");
+
+        let mut loader: ModuleLoader = ModuleLoader::new(Vec::new(), false);
+        let (_, errors) = loader.load(path.clone()).unwrap();
+        assert!(matches!(
+            errors[0].root_cause(),
+            CompilerError::ModuleNotFoundError(name, _) if name == "circles"
+        ));
+
+        let mut loader: ModuleLoader = ModuleLoader::new(vec![search_root], false);
+        let (module, errors) = loader.load(path).unwrap();
+        assert!(errors.is_empty());
+        assert!(module.check_types().1.is_empty());
+    }
+
+    #[test]
+    fn test_import_conflicts() {
+        let (_, errors) = parse_test_file("import_conflicts.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(matches!(
+            diagnostics[0],
+            (4, 1, CompilerError::AmbiguousImportError(name, first, second))
+                if name == "square" && first == "geometry.shapes" && second == "geometry.polygons"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (6, 5, CompilerError::ImportConflictError(name, module))
+                if name == "area" && module == "geometry.polygons"
+        ));
+    }
+
+    #[test]
+    fn test_generics() {
+        let (module, errors) = parse_test_file("generics.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            (19, 14, CompilerError::DuplicateTypeParameterError(function, parameter))
+                if function == "twice" && parameter == "T"
+        ));
+        assert_eq!(
+            module.functions()[0].to_s_expression(),
+            "(def id<T> (x:T) -> T x)"
+        );
+        assert_eq!(
+            module.externs()[0].to_s_expression(),
+            "(extern choose<T> (a:T b:T) -> T)"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "id<T>(x: T) -> T");
+        assert_eq!(
+            signatures[2].to_string(),
+            "pair<A, B>(a: A, b: B) -> (A, B)"
+        );
+        assert_eq!(
+            signatures[3].to_string(),
+            "apply<T, R>(f: (T) -> R, x: T) -> R"
+        );
+        // inferred type parameters are numbered after the declared ones
+        assert_eq!(signatures[4].to_string(), "keep<T>(x: T, n: 'b) -> T");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[0],
+            (16, 25, CompilerError::InvalidOperandTypesError("+", Type::Generic(name), Type::I64))
+                if name == "T"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (17, 28, CompilerError::TypeConflictError(Type::Generic(expected), Type::Generic(found), _))
+                if expected == "U" && found == "T"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (
+                18,
+                14,
+                CompilerError::TypeConflictError(Type::Bool, Type::Str, _)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_loops() {
+        let (module, errors) = parse_test_file("loops.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[0],
+            (23, 1, CompilerError::OutsideLoopError(keyword)) if keyword == "break"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (24, 38, CompilerError::LoopValueError)
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (25, 20, CompilerError::OutsideLoopError(keyword)) if keyword == "continue"
+        ));
+
+        assert_eq!(
+            module.functions()[0].body().to_s_expression(),
+            "(block (var ((i 0))) (while (< i n) (= i (+ i 1))) i)"
+        );
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[1], "(while false 1)");
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "count(n: i64) -> i64");
+        assert_eq!(signatures[1].to_string(), "root(x: f64) -> f64");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert!(diagnostics.iter().any(|diagnostic| matches!(
+            diagnostic,
+            (
+                26,
+                7,
+                CompilerError::TypeMismatchError(Type::Bool, Type::I64)
+            )
+        )));
+        assert!(diagnostics.iter().any(|diagnostic| matches!(
+            diagnostic,
+            (
+                27,
+                26,
+                CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, _)
+            )
+        )));
+    }
+
+    #[test]
+    fn test_logic() {
+        let (module, errors) = parse_test_file("logic.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            (19, 25, CompilerError::LoopValueError)
+        ));
+
+        assert_eq!(
+            module.functions()[0].body().to_s_expression(),
+            "(&& (! (< x low)) (! (> x high)))"
+        );
+        let top_level: Vec<String> = module
+            .top_level_expressions()
+            .iter()
+            .map(|expression| expression.body().to_s_expression())
+            .collect();
+        assert_eq!(top_level[2], "(|| true (&& false false))");
+        assert_eq!(
+            top_level[3],
+            "(var ((x 3)) (|| (! (== x 3)) (&& (> x 10) (< x 20))))"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(
+            signatures[0].to_string(),
+            "between(x: 'a, low: 'a, high: 'a) -> bool where 'a: numeric"
+        );
+        assert_eq!(signatures[1].to_string(), "same(a: str, b: str) -> bool");
+        assert_eq!(
+            signatures[2].to_string(),
+            "zero_at(items: [i64], i: i64) -> bool"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        // the value broken with on the last line also conflicts with the unit the loop gives
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                15,
+                3,
+                CompilerError::InvalidOperandTypesError("&&", Type::I64, Type::Bool)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (
+                16,
+                1,
+                CompilerError::InvalidUnaryOperandTypeError("!", Type::I64)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (
+                17,
+                9,
+                CompilerError::InvalidOperandTypesError(
+                    "==",
+                    Type::Function(..),
+                    Type::Function(..)
+                )
+            )
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (
+                18,
+                5,
+                CompilerError::TypeMismatchError(Type::Str, Type::I64)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_constants() {
+        let (module, errors) = parse_test_file("constants.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(matches!(
+            diagnostics[0],
+            (23, 7, CompilerError::DuplicateConstantError(name)) if name == "G"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (24, 3, CompilerError::AssignmentToImmutableError(name)) if name == "G"
+        ));
+
+        let constants: Vec<String> = module
+            .constants()
+            .iter()
+            .map(|constant| constant.to_s_expression())
+            .collect();
+        assert_eq!(constants[2], "(const PI:f64 3.14159)");
+        assert_eq!(constants[5], "(const SAFE (&& false (== (/ 1 0) 0)))");
+
+        // constants can be used before they are declared, and `&&` never evaluates `1 / 0`
+        let (values, _) = module.evaluate_constants();
+        let values: Vec<String> = values
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "G = 9.81",
+                "TAU = 6.28318",
+                "PI = 3.14159",
+                "ORIGIN = (0, 0)",
+                "GREETING = \"hello, world\"",
+                "SAFE = false",
+                "NEXT = 1",
+            ]
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[0].to_string(), "fall(t: f64) -> f64");
+        assert_eq!(signatures[1].to_string(), "wrap(r: f64) -> f64");
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (18, 7, CompilerError::CircularConstantError(cycle)) if cycle == &["A", "B", "A"]
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (19, 7, CompilerError::NonConstantExpressionError(name, expression))
+                if name == "NOW" && expression == "(fall 1)"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (20, 18, CompilerError::DivisionByZeroError)
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (
+                21,
+                7,
+                CompilerError::TypeMismatchError(Type::Bool, Type::I64)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (
+                22,
+                33,
+                CompilerError::ConstantOverflowError(i64::MAX, "+", 1)
+            )
+        ));
+    }
+
+    #[test]
+    fn test_macros() {
+        let (module, errors) = parse_test_file("macros.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 7);
+        assert!(matches!(
+            diagnostics[0],
+            (24, 7, CompilerError::DuplicateMacroError(name)) if name == "square"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (25, 16, CompilerError::DuplicateMacroVariableError(_, variable)) if variable == "a"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (26, 24, CompilerError::UnknownMacroVariableError(_, variable)) if variable == "b"
+        ));
+        // reported inside the template, with every expansion leading there
+        assert!(matches!(
+            diagnostics[3],
+            (27, 22, CompilerError::MacroRecursionError(name, 64)) if name == "forever"
+        ));
+        assert!(errors[3].to_string().ends_with("macros.fr:31:1"));
+        assert!(matches!(
+            diagnostics[4],
+            (29, 1, CompilerError::UndefinedMacroError(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            diagnostics[5],
+            (30, 1, CompilerError::MacroArgumentsError(name, definition))
+                if name == "clamp" && definition.line() == 5
+        ));
+        // a single variable matches a single expression
+        assert!(matches!(
+            diagnostics[6],
+            (33, 1, CompilerError::MacroArgumentsError(name, definition))
+                if name == "square" && definition.line() == 3
+        ));
+
+        let bodies: Vec<String> = module
+            .functions()
+            .iter()
+            .map(|function| function.body().to_s_expression())
+            .collect();
+        assert_eq!(bodies[0], "(* 3.14 (* r r))");
+        // arguments keep their grouping wherever the template puts them
+        assert_eq!(bodies[1], "(* (+ n 1) (+ n 1))");
+        // the template's own `t` is renamed, so it cannot capture the caller's
+        assert_eq!(
+            bodies[2],
+            "(block (var ((t 1))) (var ((u 2))) (block (var ((t#3 t))) (= t u) (= u t#3)) t)"
+        );
+        assert_eq!(
+            bodies[3],
+            "(match (< x 0) (true 0) (false (match (> x 10) (true 10) (false x))))"
+        );
+        // the names a match arm of the template binds are renamed as well
+        assert_eq!(bodies[5], "(match n (n#6 (if (> n#6 0)) 1) (_ 0))");
+        assert_eq!(
+            module.top_level_expressions()[0].body().to_s_expression(),
+            "(+ (* 2 (* 2 2)) ((fn (n) (+ n 1)) ((fn (n) (+ n 1)) 3)))"
+        );
+
+        let (signatures, errors) = module.check_types();
+        assert_eq!(signatures[2].to_string(), "exchange() -> i64");
+        assert_eq!(signatures[3].to_string(), "limit(x: i64) -> i64");
+        // the SCALE in the template is the constant, whatever the call site calls SCALE
+        assert_eq!(signatures[4].to_string(), "shadowing() -> f64");
+
+        // errors in the code a macro wrote point at its definition and at the use
+        assert_eq!(errors.len(), 1);
+        let location: &SourceLocation = errors[0].location().unwrap();
+        assert_eq!((location.line(), location.column()), (28, 23));
+        let (macro_name, call_site) = location.expansion().unwrap();
+        assert_eq!(macro_name, "mixed");
+        assert_eq!((call_site.line(), call_site.column()), (32, 14));
+        assert!(matches!(
+            errors[0].root_cause(),
+            CompilerError::InvalidOperandTypesError("+", Type::I64, Type::Str)
+        ));
+    }
+
+    #[test]
+    fn test_contracts() {
+        let (module, errors) = parse_test_file("contracts.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        // arguments known at compile time are checked against the preconditions
+        assert!(matches!(
+            diagnostics[0],
+            (14, 5, CompilerError::ContractViolationError(name, contract, declared))
+                if name == "sqrt" && contract == "(requires (>= x 0))" && declared.line() == 2
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (15, 6, CompilerError::ContractViolationError(name, contract, _))
+                if name == "clamp" && contract == "(requires (<= low high))"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (17, 24, CompilerError::UndefinedVariableError(name)) if name == "y"
+        ));
+
+        let signatures: Vec<String> = module
+            .functions()
+            .iter()
+            .map(|function| function.prototype().signature_s_expression())
+            .collect();
+        assert_eq!(
+            signatures[0],
+            "(x) (requires (>= x 0)) (ensures (>= result 0))"
+        );
+        assert_eq!(
+            signatures[1],
+            "(x low high) (requires (<= low high)) (ensures (&& (>= result low) (<= result high)))"
+        );
+
+        let (signatures, errors) = module.check_types();
+        // integer literals in the contracts and body leave sqrt working on any number
+        assert_eq!(
+            signatures[0].to_string(),
+            "sqrt(x: 'a) -> 'a where 'a: numeric"
+        );
+        assert_eq!(
+            signatures[1].to_string(),
+            "clamp(x: 'a, low: 'a, high: 'a) -> 'a where 'a: numeric"
+        );
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (
+                16,
+                12,
+                CompilerError::TypeMismatchError(Type::Bool, Type::I64)
+            )
+        ));
+
+        // the values of constants are known once types are checked, unless a parameter hides
+        // them as in root
+        assert!(matches!(
+            diagnostics[2],
+            (21, 36, CompilerError::PostconditionViolationError(name, contract, _))
+                if name == "broken" && contract == "(ensures (> result 0))"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (20, 5, CompilerError::ContractViolationError(name, _, _)) if name == "sqrt"
+        ));
+
+        // a release build leaves every contract out
+        let mut path: PathBuf = current_dir().unwrap();
+        path.push("../test_utils/contracts.fr");
+        let mut lexer: Lexer = Lexer::new(path).unwrap();
+        let (module, errors) = Ast::new(&mut lexer, false)
+            .with_contracts(false)
+            .parse_tokens();
+        assert!(errors.is_empty());
+        assert_eq!(
+            module.functions()[0].prototype().signature_s_expression(),
+            "(x)"
+        );
+    }
+
+    #[test]
+    fn test_units() {
+        let (module, errors) = parse_test_file("units.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            (23, 4, CompilerError::InvalidUnitError(unit)) if unit == "m/"
+        ));
+
+        let constants: Vec<String> = module
+            .constants()
+            .iter()
+            .map(|constant| constant.to_s_expression())
+            .collect();
+        assert_eq!(constants[0], "(const G 9.81<m/s^2>)");
+
+        let (_, errors) = module.check_types();
+        assert!(errors.is_empty());
+
+        let errors: Vec<CompilerError> = module.check_units();
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 9);
+        assert!(matches!(
+            diagnostics[0],
+            (19, 13, CompilerError::UnitMismatchError("+", left, right))
+                if left.to_string() == "m/s^2" && right.to_string() == "m"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (20, 23, CompilerError::InconsistentUnitError(expected, found))
+                if expected.to_string() == "m/s" && found.to_string() == "s"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (21, 30, CompilerError::InconsistentUnitError(expected, found))
+                if expected.to_string() == "m" && found.to_string() == "s"
+        ));
+        // numbers written without a unit are dimensionless
+        assert!(matches!(
+            diagnostics[3],
+            (22, 9, CompilerError::UnitMismatchError(">", left, right))
+                if left.to_string() == "kg" && right.is_dimensionless()
+        ));
+        // the unit of a constant is derived from those it uses, declared before it or after
+        assert!(matches!(
+            diagnostics[4],
+            (24, 8, CompilerError::UnitMismatchError("+", left, right))
+                if left.to_string() == "m" && right.to_string() == "s"
+        ));
+        assert!(matches!(
+            diagnostics[5],
+            (25, 19, CompilerError::UnitOverflowError(name)) if name == "m"
+        ));
+        assert!(matches!(
+            diagnostics[6],
+            (26, 5, CompilerError::UnitOverflowError(name)) if name == "m"
+        ));
+        // a call gives what the body works out to with the units of the arguments
+        assert!(matches!(
+            diagnostics[7],
+            (27, 14, CompilerError::UnitMismatchError("+", left, right))
+                if left.to_string() == "m" && right.to_string() == "s"
+        ));
+        assert!(matches!(
+            diagnostics[8],
+            (28, 7, CompilerError::UnitMismatchError("+", left, right))
+                if left.to_string() == "s" && right.to_string() == "m"
+        ));
+    }
+
+    #[test]
+    fn test_derivatives() {
+        let (mut module, errors) = parse_test_file("derivatives.fr");
+        assert!(errors.is_empty());
+
+        let derivative = |module: &Module, name: &str, parameter: Option<&str>| {
+            module
+                .derivative(name, parameter)
+                .map(|function| function.to_s_expression())
+        };
+        assert_eq!(
+            derivative(&module, "cube", None).unwrap(),
+            "(def dcube_dx (x) -> f64 (var ((u#1 (* x x)) (du#2 (+ x x))) (+ (* du#2 x) u#1)))"
+        );
+        assert_eq!(
+            derivative(&module, "quotient", Some("y")).unwrap(),
+            "(def dquotient_dy (x y) -> f64 (- (/ x (* y y))))"
+        );
+        // the values of math functions are reused by their derivatives
+        assert_eq!(
+            derivative(&module, "wave", None).unwrap(),
+            "(def dwave_dt (t) -> f64 (var ((u#1 (* 2 t)) (u#2 (sin u#1)) (du#4 (* (cos u#1) 2)) (u#3 (exp t))) (+ du#4 u#3)))"
+        );
+        // calls that do not depend on the variable are still evaluated
+        assert_eq!(
+            derivative(&module, "smooth", None).unwrap(),
+            "(def dsmooth_dx (x) -> f64 (var ((y#1 (* x x)) (du#2 (+ x x)) (z#3 2) (w#4 (/ y#1 z#3)) (du#5 (/ du#2 z#3)) (unused#6 (print z#3))) (- du#5 1)))"
+        );
+        assert_eq!(
+            derivative(&module, "relu", None).unwrap(),
+            "(def drelu_dx (x) -> f64 (var ((u#1 (> x 0)) (m#2 (match u#1 (true (tuple x 1)) (false (tuple 0 0))))) (. m#2 1)))"
+        );
+
+        // derivatives are checked like any other function of the module
+        for (name, parameter) in [
+            ("cube", None),
+            ("wave", None),
+            ("relu", None),
+            ("trig", None),
+        ] {
+            let function: Function = module.derivative(name, parameter).unwrap();
+            module.add_function(function);
+        }
+        let function: Function = module.derivative("scaled", Some("x")).unwrap();
+        module.add_function(function);
+        let (_, errors) = module.check_types();
+        assert!(errors.is_empty());
+
+        let error: CompilerError = module.derivative("repeat", None).err().unwrap();
+        assert!(matches!(
+            located_diagnostic(&error),
+            (19, 20, CompilerError::NonDifferentiableError(expression)) if expression == "(print x)"
+        ));
+        let error: CompilerError = module.derivative("count", None).err().unwrap();
+        assert!(matches!(
+            located_diagnostic(&error),
+            (20, 5, CompilerError::NonDifferentiableError(_))
+        ));
+        let error: CompilerError = module.derivative("scaled", Some("k")).err().unwrap();
+        assert!(matches!(
+            located_diagnostic(&error),
+            (16, 37, CompilerError::NonDifferentiableError(expression)) if expression == "(print k)"
+        ));
+        assert!(matches!(
+            module.derivative("scaled", None),
+            Err(CompilerError::DerivativeParameterError(name)) if name == "scaled"
+        ));
+        assert!(matches!(
+            module.derivative("cube", Some("z")),
+            Err(CompilerError::UnknownNamedArgumentError(name, parameter))
+                if name == "cube" && parameter == "z"
+        ));
+        assert!(matches!(
+            module.derivative("nothing", None),
+            Err(CompilerError::UndefinedFunctionError(name)) if name == "nothing"
+        ));
+        // a derivative already added is not made a second time
+        let error: CompilerError = module.derivative("cube", None).err().unwrap();
+        assert!(matches!(
+            located_diagnostic(&error),
+            (10, 5, CompilerError::DuplicateFunctionError(name)) if name == "dcube_dx"
+        ));
+    }
+
+    #[test]
+    fn test_derivative_source() {
+        let (module, errors) = parse_test_file("derivatives.fr");
+        assert!(errors.is_empty());
+
+        let source = |name: &str| {
+            let function: Function = module.derivative(name, None).unwrap();
+            function.to_source().unwrap()
+        };
+        assert_eq!(
+            source("cube"),
+            "def dcube_dx(x) -> f64 (var u_1 = (x * x), du_2 = (x + x) in ((du_2 * x) + u_1))"
+        );
+        assert_eq!(
+            source("relu"),
+            "def drelu_dx(x) -> f64 (var u_1 = (x > 0.0), m_2 = match u_1 { true => (x, 1.0), false => (0.0, 0.0) } in m_2.1)"
+        );
+
+        // the printed derivatives parse and check like the functions they were made from
+        let mut path: PathBuf = current_dir().unwrap();
+        path.push("../test_utils/derivatives.fr");
+        let mut printed: String = std::fs::read_to_string(&path).unwrap();
+        for name in ["cube", "wave", "smooth", "relu", "trig"] {
+            printed = format!("{}\n{}\n", printed, source(name));
+        }
+        let path: PathBuf = std::env::temp_dir().join("printed_derivatives.fr");
+        std::fs::write(&path, printed).unwrap();
+        let mut lexer: Lexer = Lexer::new(path).unwrap();
+        let (module, errors) = Ast::new(&mut lexer, false).parse_tokens();
+        assert!(errors.is_empty());
+        assert_eq!(module.functions().len(), 14);
+        let (_, errors) = module.check_types();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_derivative_externs() {
+        let (module, errors) = parse_test_file("partial_math.fr");
+        assert!(errors.is_empty());
+
+        // tan' is written with tan alone, while sin' needs cos
+        assert_eq!(
+            module.derivative("slope", None).unwrap().to_s_expression(),
+            "(def dslope_dx (x) -> f64 (var ((u#1 (* x x)) (du#2 (+ x x)) (u#3 (tan u#1))) (* (+ 1 (* u#3 u#3)) du#2)))"
+        );
+        let error: CompilerError = module.derivative("wave", None).err().unwrap();
+        assert!(matches!(
+            located_diagnostic(&error),
+            (5, 22, CompilerError::MissingExternError(function, needed))
+                if function == "sin" && needed == "cos"
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let (module, errors) = parse_test_file("duplicate_names.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (7, 5, CompilerError::DuplicateFunctionError(name)) if name == "f"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (8, 8, CompilerError::DuplicateFunctionError(name)) if name == "sin"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (9, 10, CompilerError::DuplicateParameterError(function, parameter))
+                if function == "fn" && parameter == "y"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (10, 5, CompilerError::DuplicateBindingError(name)) if name == "a"
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (11, 13, CompilerError::DuplicateParameterError(function, parameter))
+                if function == "h" && parameter == "x"
+        ));
+
+        assert_eq!(module.functions().len(), 1);
+        assert_eq!(module.top_level_expressions().len(), 1);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let (module, errors) = parse_test_file("attributes.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 6);
+        assert!(matches!(
+            diagnostics[0],
+            (16, 2, CompilerError::UnknownAttributeError(name)) if name == "cached"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (17, 8, CompilerError::DuplicateAttributeError(name)) if name == "memo"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (18, 8, CompilerError::ConflictingAttributesError(first, second))
+                if first == "inline" && second == "memo"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (19, 2, CompilerError::AttributeTargetError(name, "an extern")) if name == "memo"
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (20, 2, CompilerError::AttributeArgumentError(name)) if name == "inline"
+        ));
+        // attributes can only be given to functions
+        assert!(matches!(
+            diagnostics[5],
+            (21, 7, CompilerError::UnexpectedTokenError(Token::Const))
+        ));
+
+        let functions: Vec<String> = module
+            .functions()
+            .iter()
+            .map(|function| function.to_s_expression())
+            .collect();
+        assert_eq!(
+            functions[1],
+            "(def slow_fib (x) @deprecated(\"use fib instead\") (fib x))"
+        );
+        assert_eq!(
+            functions[4],
+            "(def helper (x) (block (def twice (y) @inline (* y 2)) (twice x)))"
+        );
+
+        // side effects are found through the functions called, externs having them unless
+        // they are declared @pure
+        let (signatures, errors, warnings) = module.check_types_with_warnings();
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        assert!(matches!(
+            diagnostics[0],
+            (22, 2, CompilerError::SideEffectError(function, "memo", callee))
+                if function == "f" && callee == "log"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (23, 2, CompilerError::SideEffectError(function, "pure", callee))
+                if function == "g" && callee == "println"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (24, 2, CompilerError::SideEffectError(function, "memo", callee))
+                if function == "h" && callee == "f"
+        ));
+        // a function taken as a value may be called anywhere, and what a function value does
+        // when called is not known
+        assert!(matches!(
+            diagnostics[3],
+            (27, 2, CompilerError::SideEffectError(function, "pure", callee))
+                if function == "sneaky" && callee == "println"
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (28, 2, CompilerError::SideEffectError(function, "pure", callee))
+                if function == "through" && callee == "p"
+        ));
+        let fib: &FunctionSignature = signatures
+            .iter()
+            .find(|signature| signature.name() == "fib")
+            .unwrap();
+        assert!(fib.attribute(AttributeKind::Memo).is_some());
+        assert!(fib.side_effect().is_none());
+
+        let warnings: Vec<(usize, usize, &WarningKind)> =
+            warnings.iter().map(located_warning).collect();
+        assert_eq!(warnings.len(), 3);
+        assert!(matches!(
+            warnings[0],
+            (25, 2, WarningKind::RecursiveInline(function)) if function == "spin"
+        ));
+        assert!(matches!(
+            warnings[1],
+            (12, 9, WarningKind::DeprecatedCall(function, Some(message)))
+                if function == "slow_fib" && message == "use fib instead"
+        ));
+        assert!(matches!(
+            warnings[2],
+            (13, 8, WarningKind::DeprecatedCall(function, None)) if function == "old_log"
+        ));
+    }
+
+    #[test]
+    fn test_generators() {
+        let (module, errors) = parse_test_file("generators.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[0],
+            (38, 1, CompilerError::YieldOutsideFunctionError)
+        ));
+        // the body of a lambda cannot yield, even inside a def
+        assert!(matches!(
+            diagnostics[1],
+            (39, 17, CompilerError::YieldOutsideFunctionError)
+        ));
+        // the names a for loop binds are immutable
+        assert!(matches!(
+            diagnostics[2],
+            (43, 39, CompilerError::AssignmentToImmutableError(name)) if name == "x"
+        ));
+
+        assert_eq!(
+            module.functions()[4].body().to_s_expression(),
+            "(block (var ((total 0))) (for (i, x) (indexed xs) (= total (+ total (* i x)))) total)"
+        );
+        assert_eq!(
+            module.top_level_expressions()[0].body().to_s_expression(),
+            "(for x (samples 0 0.5 4) (println (to_string x)))"
+        );
+
+        // a function that yields returns a generator of what it yields
+        let (signatures, errors) = module.check_types();
+        assert_eq!(
+            signatures[0].to_string(),
+            "samples(start: f64, step: f64, count: i64) -> gen[f64]"
+        );
+        // k counts in the same type as n, and is divided into an f64
+        assert_eq!(signatures[1].to_string(), "terms(n: f64) -> gen[f64]");
+        assert_eq!(signatures[2].to_string(), "series(n: i64) -> f64");
+        assert_eq!(
+            signatures[3].to_string(),
+            "indexed(xs: [f64]) -> gen[(i64, f64)]"
+        );
+        assert_eq!(signatures[4].to_string(), "weighted(xs: [f64]) -> f64");
+        // a parameter only gone over by a for loop can be an array or a generator
+        assert_eq!(
+            signatures[5].to_string(),
+            "sum(values: 'a) -> 'b where 'a: iterable, 'b: numeric"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 5);
+        // the yield left in the lambda still makes lazy a generator, whose body has no value
+        assert!(matches!(
+            diagnostics[0],
+            (
+                39,
+                12,
+                CompilerError::TypeMismatchError(Type::Unit, Type::Function(..))
+            )
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (
+                40,
+                24,
+                CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, _)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (
+                41,
+                23,
+                CompilerError::TypeConflictError(Type::I64, Type::Generator(_), _)
+            )
+        ));
+        // top level expressions are checked after every function
+        assert!(matches!(
+            diagnostics[3],
+            (38, 1, CompilerError::YieldOutsideFunctionError)
+        ));
+        assert!(matches!(
+            diagnostics[4],
+            (42, 5, CompilerError::NotIterableError(Type::I64))
+        ));
+    }
+
+    #[test]
+    fn test_results() {
+        let (module, errors) = parse_test_file("results.fr");
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            diagnostics[0],
+            (30, 2, CompilerError::AttributeTargetError(name, "a definition")) if name == "checked"
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (31, 2, CompilerError::AttributeTargetError(name, "an extern not returning f64"))
+                if name == "checked"
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (32, 14, CompilerError::TypeArgumentCountError(name, 2, 1)) if name == "result"
+        ));
+
+        assert_eq!(
+            module.functions()[2].body().to_s_expression(),
+            "(Ok (? (log (? (ratio a b)))))"
+        );
+        assert_eq!(
+            module.functions()[3].body().to_s_expression(),
+            "(try (? (log_ratio text \"2\")) (catch e 0))"
+        );
+
+        // a function handing errors on with ? returns them, and a @checked extern returns its
+        // NaN as an error
+        let (signatures, errors, warnings) = module.check_types_with_warnings();
+        assert_eq!(
+            signatures[1].to_string(),
+            "ratio(a: str, b: str) -> result[f64, str]"
+        );
+        assert_eq!(signatures[3].to_string(), "or_zero(text: str) -> f64");
+        assert_eq!(
+            signatures[5].to_string(),
+            "total(texts: [str]) -> result[i64, str]"
+        );
+
+        let diagnostics: Vec<(usize, usize, &CompilerError)> =
+            errors.iter().map(located_diagnostic).collect();
+        assert_eq!(diagnostics.len(), 4);
+        assert!(matches!(
+            diagnostics[0],
+            (26, 26, CompilerError::NotAResultError(Type::F64))
+        ));
+        assert!(matches!(
+            diagnostics[1],
+            (
+                27,
+                19,
+                CompilerError::UnsatisfiedConstraintError(Constraint::Numeric, Type::Str, _)
+            )
+        ));
+        assert!(matches!(
+            diagnostics[2],
+            (28, 36, CompilerError::NonExhaustiveMatchError(missing)) if missing == "Err(_)"
+        ));
+        assert!(matches!(
+            diagnostics[3],
+            (25, 15, CompilerError::PropagationOutsideHandlerError)
+        ));
+
+        let warnings: Vec<(usize, usize, &WarningKind)> =
+            warnings.iter().map(located_warning).collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0], (29, 22, WarningKind::UnusedCatch)));
+        assert!(module.check_units().is_empty());
     }
 }
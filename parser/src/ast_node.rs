@@ -1,5 +1,89 @@
 use std::fmt::Display;
 
+use common::{error::CompilerError, types::Type};
+
+use crate::{
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
 pub trait ASTNode: Display {
     fn print(&self);
+
+    /// Render the node as a compact S-expression, e.g. `(+ 1 (* 2 x))`
+    fn to_s_expression(&self) -> String;
+
+    /// Render the node as Fragment source that parses back to it, e.g. `(1 + (2 * x))`. Only
+    /// the nodes a derivative can be made of know how to
+    fn to_source(&self) -> Option<String> {
+        None
+    }
+
+    /// Verify that every variable this node reads or writes is bound in `scope`, and that
+    /// assignments only target mutable variables
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError>;
+
+    /// Verify that every call made by this node names a known function and supplies a valid set
+    /// of arguments for it
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError>;
+
+    /// Work out the type of the value this node produces, checking that every operand, argument
+    /// and assignment along the way has a compatible type
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError>;
+
+    /// Work out the unit of measure of the value this node produces, checking that values
+    /// added, subtracted, compared or assigned to each other are measured in the same unit
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError>;
+
+    /// Work out the value of this node at compile time, as part of the initializer of a
+    /// constant. Only literals, operators, tuples and other constants can be evaluated this way
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        Err(CompilerError::NonConstantExpressionError(
+            evaluator.current().to_string(),
+            self.to_s_expression(),
+        ))
+    }
+
+    /// The value of this node along with its derivative, as forward mode automatic
+    /// differentiation works them out. Only arithmetic, calls to known math functions,
+    /// conditionals and local variables can be differentiated
+    fn differentiate(&self, _differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        Err(CompilerError::NonDifferentiableError(
+            self.to_s_expression(),
+        ))
+    }
+
+    /// The name this node refers to if it is a plain variable reference. Only such nodes can be
+    /// assigned to or called
+    fn variable_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether this node is a name referring to what it means at the top level of the module,
+    /// skipping any local variable of the same name
+    fn is_top_level_name(&self) -> bool {
+        false
+    }
+
+    /// The value of this node if it is an integer known at compile time
+    fn constant_integer(&self) -> Option<i64> {
+        None
+    }
+
+    /// The value of this node if it is a boolean literal
+    fn constant_boolean(&self) -> Option<bool> {
+        None
+    }
+
+    /// The number of elements this node produces if it is an array literal
+    fn constant_length(&self) -> Option<usize> {
+        None
+    }
 }
@@ -0,0 +1,98 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    module::FunctionTable,
+    scope::Scope,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `const G = 9.81`, a top level name for a value worked out at compile time. The initializer
+/// can use literals, operators and other constants, declared before or after it
+pub struct ConstantDefinition {
+    name: String,
+    annotation: Option<Type>,
+    initializer: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl ConstantDefinition {
+    pub fn new(
+        name: &str,
+        annotation: Option<Type>,
+        initializer: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            annotation,
+            initializer,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn annotation(&self) -> Option<&Type> {
+        self.annotation.as_ref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    fn annotated_name(&self) -> String {
+        match &self.annotation {
+            Some(annotation) => format!("{}: {}", self.name, annotation),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn print(&self) {
+        println!("Node: Constant Definition");
+        println!("{}", self);
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        format!(
+            "(const {} {})",
+            self.annotated_name().replace(' ', ""),
+            self.initializer.to_s_expression()
+        )
+    }
+
+    pub fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.initializer.check_scope(scope)
+    }
+
+    pub fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.initializer.check_calls(functions)
+    }
+
+    pub fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.initializer.check_units(context)
+    }
+
+    /// The value of the initializer, converted to the annotated type if there is one
+    pub fn evaluate(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        let value: ConstantValue = self.initializer.evaluate_constant(evaluator)?;
+        match &self.annotation {
+            Some(annotation) => value.convert_to(annotation),
+            None => Ok(value),
+        }
+    }
+}
+
+impl Display for ConstantDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "const {} = {}", self.annotated_name(), self.initializer)
+    }
+}
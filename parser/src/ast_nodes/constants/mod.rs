@@ -0,0 +1 @@
+pub mod constant_definition;
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+pub struct ArrayExpression {
+    elements: Vec<Box<dyn ASTNode>>,
+    location: SourceLocation,
+}
+
+impl ArrayExpression {
+    pub fn new(elements: Vec<Box<dyn ASTNode>>, location: SourceLocation) -> Self {
+        Self { elements, location }
+    }
+}
+
+impl Display for ArrayExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ArrayExpression: [")?;
+        for (i, element) in self.elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl ASTNode for ArrayExpression {
+    fn print(&self) {
+        println!("Node: Array Expression");
+        println!("Array Expression Length: {}", self.elements.len());
+        for element in &self.elements {
+            println!("Array Expression Element: {}", element);
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut parts: Vec<String> = vec![String::from("array")];
+        parts.extend(
+            self.elements
+                .iter()
+                .map(|element| element.to_s_expression()),
+        );
+        format!("({})", parts.join(" "))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for element in &self.elements {
+            element.check_scope(scope)?;
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        for element in &self.elements {
+            element.check_calls(functions)?;
+        }
+        Ok(())
+    }
+
+    /// Every element must have the same type, except that a mix of i64 and f64 numbers is
+    /// widened to an array of f64
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let mut element_types: Vec<Type> = Vec::new();
+        for element in &self.elements {
+            element_types.push(element.check_types(context)?);
+        }
+
+        let resolved: Vec<Type> = element_types.iter().map(|t| context.resolve(t)).collect();
+        if !resolved.is_empty() && resolved.iter().all(|t| t.is_numeric()) {
+            let element_type: Type = match resolved.iter().all(|t| *t == Type::I64) {
+                true => Type::I64,
+                false => Type::F64,
+            };
+            return Ok(Type::Array(Box::new(element_type)));
+        }
+
+        let element_type: Type = context.fresh_variable();
+        for found in &element_types {
+            context.coerce(&element_type, found, &self.location)?;
+        }
+        Ok(Type::Array(Box::new(element_type)))
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        for element in &self.elements {
+            element.check_units(context)?;
+        }
+        Ok(Measure::Unknown)
+    }
+
+    fn constant_length(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
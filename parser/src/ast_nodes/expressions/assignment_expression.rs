@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `name = value`. Evaluates to the assigned value
+pub struct AssignmentExpression {
+    name: String,
+    value: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl AssignmentExpression {
+    pub fn new(name: &str, value: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            location,
+        }
+    }
+}
+
+impl Display for AssignmentExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AssignmentExpression target: {}\nAssignmentExpression value: {}",
+            self.name, self.value
+        )
+    }
+}
+
+impl ASTNode for AssignmentExpression {
+    fn print(&self) {
+        println!("Node: AssignmentExpression");
+        println!("AssignmentExpression target: {}", self.name);
+        println!("AssignmentExpression value: {}", self.value);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("(= {} {})", self.name, self.value.to_s_expression())
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.value.check_scope(scope)?;
+        if let Err(e) = scope.check_write(&self.name) {
+            scope.record_error(e.with_location(&self.location));
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.value.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let variable_type: Type = context.variable_type(&self.name)?;
+        let value_type: Type = self.value.check_types(context)?;
+        context.coerce(&variable_type, &value_type, &self.location)?;
+        Ok(variable_type)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let value: Measure = self.value.check_units(context)?;
+        context
+            .lookup(&self.name)
+            .agree(&value)
+            .map_err(|(expected, found)| {
+                CompilerError::InconsistentUnitError(expected, found).with_location(&self.location)
+            })?;
+        Ok(value)
+    }
+}
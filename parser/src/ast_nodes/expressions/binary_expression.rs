@@ -1,13 +1,28 @@
 use std::fmt::Display;
 
-use common::token::SimpleBinaryOperater;
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    token::SimpleBinaryOperater,
+    types::{Constraint, Type},
+};
 
-use crate::ast_node::ASTNode;
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::expressions::var_expression::VarBinding,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{difference, divide, multiply, sum, Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
 
 pub struct BinaryExpression {
     operator: SimpleBinaryOperater,
     left_hand_side: Box<dyn ASTNode>,
     right_hand_side: Box<dyn ASTNode>,
+    location: SourceLocation,
 }
 
 impl BinaryExpression {
@@ -15,11 +30,69 @@ impl BinaryExpression {
         operator: SimpleBinaryOperater,
         left_hand_side: Box<dyn ASTNode>,
         right_hand_side: Box<dyn ASTNode>,
+        location: SourceLocation,
     ) -> Self {
         Self {
             operator,
             left_hand_side,
             right_hand_side,
+            location,
+        }
+    }
+
+    /// The type both operands of `==` are converted to, `None` when they cannot be compared.
+    /// Numbers of either type compare with each other, anything else only with its own type
+    fn equality_type(
+        &self,
+        left_type: Type,
+        right_type: Type,
+        context: &mut TypeContext,
+    ) -> Result<Option<Type>, CompilerError> {
+        let left: Type = context.resolve(&left_type);
+        let right: Type = context.resolve(&right_type);
+        if let Some(common_type) = left.common_numeric_type(&right) {
+            return Ok(Some(common_type));
+        }
+
+        context.unify(&left_type, &right_type, &self.location)?;
+        let operand_type: Type = context.resolve_deep(&left_type);
+        Ok(operand_type.supports_equality().then_some(operand_type))
+    }
+
+    /// The type both operands are converted to before the operator is applied, `None` when the
+    /// operator cannot be applied to them. Operands whose types are both known follow the usual
+    /// widening rules, otherwise they are unified with each other. An integer literal can only
+    /// be unified with another number
+    fn operand_type(
+        &self,
+        left_type: Type,
+        right_type: Type,
+        context: &mut TypeContext,
+    ) -> Result<Option<Type>, CompilerError> {
+        let left: Type = context.resolve(&left_type);
+        let right: Type = context.resolve(&right_type);
+        if !left.is_variable() && !right.is_variable() {
+            return Ok(match (left, right) {
+                (Type::Str, Type::Str) if self.operator.applies_to_strings() => Some(Type::Str),
+                (left, right) => left.common_numeric_type(&right),
+            });
+        }
+        let left_shape: Type = context.resolve_shape(&left_type);
+        let right_shape: Type = context.resolve_shape(&right_type);
+        if !left_shape.is_variable()
+            && !right_shape.is_variable()
+            && left_shape.common_numeric_type(&right_shape).is_none()
+        {
+            return Ok(None);
+        }
+
+        context.unify(&left_type, &right_type, &self.location)?;
+        match context.resolve(&left_type) {
+            Type::Str if self.operator.applies_to_strings() => Ok(Some(Type::Str)),
+            _ if context.require(Constraint::Numeric, &left_type, &self.location)? => {
+                Ok(Some(left_type))
+            }
+            _ => Ok(None),
         }
     }
 }
@@ -29,7 +102,7 @@ impl Display for BinaryExpression {
         write!(
             f,
             "Operator: {}\nLHS: {}\nRHS: {}",
-            self.operator.to_char(),
+            self.operator.symbol(),
             self.left_hand_side,
             self.right_hand_side
         )
@@ -39,8 +112,109 @@ impl Display for BinaryExpression {
 impl ASTNode for BinaryExpression {
     fn print(&self) {
         println!("Node: Binary Expression");
-        println!("Binary Expression Operator: {}", self.operator.to_char());
+        println!("Binary Expression Operator: {}", self.operator.symbol());
         println!("Binary Expression LHS: {}", self.left_hand_side);
         println!("Binary Expression RHS: {}", self.right_hand_side);
     }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "({} {} {})",
+            self.operator.symbol(),
+            self.left_hand_side.to_s_expression(),
+            self.right_hand_side.to_s_expression()
+        )
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(format!(
+            "({} {} {})",
+            self.left_hand_side.to_source()?,
+            self.operator.symbol(),
+            self.right_hand_side.to_source()?
+        ))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.left_hand_side.check_scope(scope)?;
+        self.right_hand_side.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.left_hand_side.check_calls(functions)?;
+        self.right_hand_side.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let left_type: Type = self.left_hand_side.check_types(context)?;
+        let right_type: Type = self.right_hand_side.check_types(context)?;
+
+        let operand_type: Option<Type> = match self.operator {
+            SimpleBinaryOperater::Equality => {
+                self.equality_type(left_type.clone(), right_type.clone(), context)?
+            }
+            _ => self.operand_type(left_type.clone(), right_type.clone(), context)?,
+        };
+        match operand_type {
+            Some(_) if self.operator.is_comparison() => Ok(Type::Bool),
+            Some(operand_type) => Ok(operand_type),
+            None => Err(CompilerError::InvalidOperandTypesError(
+                self.operator.symbol(),
+                context.resolve_shape(&left_type),
+                context.resolve_shape(&right_type),
+            )
+            .with_location(&self.location)),
+        }
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let left: Measure = self.left_hand_side.check_units(context)?;
+        let right: Measure = self.right_hand_side.check_units(context)?;
+        Measure::binary(&self.operator, &left, &right).map_err(|e| e.with_location(&self.location))
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        let left: ConstantValue = self.left_hand_side.evaluate_constant(evaluator)?;
+        let right: ConstantValue = self.right_hand_side.evaluate_constant(evaluator)?;
+        ConstantValue::binary(&self.operator, left, right)
+            .map_err(|e| e.with_location(&self.location))
+    }
+
+    /// Sums and differences are differentiated term by term, products with the product rule
+    /// and quotients with the quotient rule. Comparisons give a bool, which has no derivative
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        let left: Dual = self.left_hand_side.differentiate(differentiator)?;
+        let right: Dual = self.right_hand_side.differentiate(differentiator)?;
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        let (l, dl) = differentiator.atoms(left, &mut bindings);
+        let (r, dr) = differentiator.atoms(right, &mut bindings);
+
+        let value: Box<dyn ASTNode> = Box::new(BinaryExpression::new(
+            self.operator.clone(),
+            l.node(),
+            r.node(),
+            self.location.clone(),
+        ));
+        let tangent: Option<Box<dyn ASTNode>> = match self.operator {
+            SimpleBinaryOperater::Addition => {
+                sum(vec![dl.map(|dl| dl.node()), dr.map(|dr| dr.node())])
+            }
+            SimpleBinaryOperater::Subtraction => {
+                difference(dl.map(|dl| dl.node()), dr.map(|dr| dr.node()))
+            }
+            SimpleBinaryOperater::Multiplication => sum(vec![
+                dl.map(|dl| multiply(dl.node(), r.node())),
+                dr.map(|dr| multiply(l.node(), dr.node())),
+            ]),
+            SimpleBinaryOperater::Division => difference(
+                dl.map(|dl| divide(dl.node(), r.node())),
+                dr.map(|dr| divide(multiply(l.node(), dr.node()), multiply(r.node(), r.node()))),
+            ),
+            _ => None,
+        };
+        Ok(Dual::new(value, tangent).after(bindings))
+    }
 }
@@ -0,0 +1,274 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::functions::function_definition::Function,
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+use super::var_expression::VarBinding;
+
+/// One `;` separated entry of a block
+pub enum BlockItem {
+    Expression(Box<dyn ASTNode>),
+    /// `var x = init, y` without an `in` body: the bindings stay visible until the end of the
+    /// block
+    Declaration(Vec<VarBinding>),
+    /// A local `def`, callable from the rest of the block. It does not see the block's variables
+    Definition(Function),
+}
+
+impl BlockItem {
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        match self {
+            BlockItem::Expression(expression) => expression.check_scope(scope),
+            BlockItem::Declaration(bindings) => {
+                for binding in bindings {
+                    binding.check_scope(scope)?;
+                }
+                Ok(())
+            }
+            BlockItem::Definition(function) => {
+                scope.declare_function(function.prototype().name());
+                let mut definition_scope: Scope = scope.definition_scope();
+                let result = function.check_scope(&mut definition_scope);
+                for error in definition_scope.take_errors() {
+                    scope.record_error(error);
+                }
+                result
+            }
+        }
+    }
+
+    /// The type of the item's value. Declarations and definitions have none, they only add to
+    /// the context
+    fn check_types(&self, context: &mut TypeContext) -> Result<Option<Type>, CompilerError> {
+        match self {
+            BlockItem::Expression(expression) => Ok(Some(expression.check_types(context)?)),
+            BlockItem::Declaration(bindings) => {
+                for binding in bindings {
+                    binding.check_types(context)?;
+                }
+                Ok(None)
+            }
+            BlockItem::Definition(function) => {
+                function.check_types(context)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// The unit of the item's value, if it has one
+    fn check_units(&self, context: &mut UnitContext) -> Result<Option<Measure>, CompilerError> {
+        match self {
+            BlockItem::Expression(expression) => Ok(Some(expression.check_units(context)?)),
+            BlockItem::Declaration(bindings) => {
+                for binding in bindings {
+                    binding.check_units(context)?;
+                }
+                Ok(None)
+            }
+            BlockItem::Definition(function) => {
+                function.check_units(&mut context.definition_context())?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        match self {
+            BlockItem::Expression(expression) => expression.to_s_expression(),
+            BlockItem::Declaration(bindings) => {
+                let bindings: Vec<String> = bindings
+                    .iter()
+                    .map(|binding| binding.to_s_expression())
+                    .collect();
+                format!("(var ({}))", bindings.join(" "))
+            }
+            BlockItem::Definition(function) => function.to_s_expression(),
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match self {
+            BlockItem::Expression(expression) => expression.to_source(),
+            BlockItem::Declaration(bindings) => {
+                let bindings: Option<Vec<String>> =
+                    bindings.iter().map(|binding| binding.to_source()).collect();
+                Some(format!("var {}", bindings?.join(", ")))
+            }
+            BlockItem::Definition(function) => function.to_source(),
+        }
+    }
+}
+
+impl Display for BlockItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockItem::Expression(expression) => write!(f, "{}", expression),
+            BlockItem::Declaration(bindings) => {
+                let bindings: Vec<String> =
+                    bindings.iter().map(|binding| binding.to_string()).collect();
+                write!(f, "var {}", bindings.join(", "))
+            }
+            BlockItem::Definition(function) => write!(f, "{}", function),
+        }
+    }
+}
+
+/// `{ e1; e2; e3 }`. The items run in order and the block evaluates to its final expression
+pub struct BlockExpression {
+    items: Vec<BlockItem>,
+}
+
+impl BlockExpression {
+    pub fn new(items: Vec<BlockItem>) -> Self {
+        Self { items }
+    }
+
+    fn check_items(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for item in &self.items {
+            item.check_scope(scope)?;
+        }
+        Ok(())
+    }
+
+    fn check_item_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let mut block_type: Option<Type> = None;
+        for item in &self.items {
+            block_type = item.check_types(context)?;
+        }
+        block_type.ok_or(CompilerError::BlockWithoutValueError)
+    }
+
+    /// Every item's bindings are hoisted in order. Values before the last are still evaluated,
+    /// bound to a name nothing uses
+    fn differentiate_items(
+        &self,
+        differentiator: &mut Differentiator,
+    ) -> Result<Dual, CompilerError> {
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        for (index, item) in self.items.iter().enumerate() {
+            match item {
+                BlockItem::Expression(expression) => {
+                    let dual: Dual = expression.differentiate(differentiator)?;
+                    if index == self.items.len() - 1 {
+                        return Ok(dual.after(bindings));
+                    }
+                    let (mut own_bindings, value, _) = dual.into_parts();
+                    bindings.append(&mut own_bindings);
+                    differentiator.bind("unused", value, &mut bindings);
+                }
+                BlockItem::Declaration(declared) => {
+                    for binding in declared {
+                        binding.differentiate(differentiator, &mut bindings)?;
+                    }
+                }
+                BlockItem::Definition(function) => {
+                    return Err(CompilerError::NonDifferentiableError(
+                        function.prototype().name().to_string(),
+                    ))
+                }
+            }
+        }
+        Err(CompilerError::BlockWithoutValueError)
+    }
+
+    fn check_item_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let mut measure: Option<Measure> = None;
+        for item in &self.items {
+            measure = item.check_units(context)?;
+        }
+        Ok(measure.unwrap_or(Measure::Unknown))
+    }
+}
+
+impl Display for BlockExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut items_string: String = String::new();
+        for item in &self.items {
+            items_string = format!("{}\n{};", items_string, item);
+        }
+
+        write!(f, "BlockExpression items: {}", items_string)
+    }
+}
+
+impl ASTNode for BlockExpression {
+    fn print(&self) {
+        println!("Node: BlockExpression");
+        for item in &self.items {
+            println!("BlockExpression item: {}", item);
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut s_expression: String = String::from("(block");
+        for item in &self.items {
+            s_expression = format!("{} {}", s_expression, item.to_s_expression());
+        }
+        format!("{})", s_expression)
+    }
+
+    fn to_source(&self) -> Option<String> {
+        let items: Option<Vec<String>> = self.items.iter().map(|item| item.to_source()).collect();
+        Some(format!("{{ {} }}", items?.join("; ")))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        scope.push_frame();
+        let result = self.check_items(scope);
+        scope.pop_frame();
+        result
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        let mut local_functions: FunctionTable = functions.clone();
+        for item in &self.items {
+            match item {
+                BlockItem::Expression(expression) => expression.check_calls(&local_functions)?,
+                BlockItem::Declaration(bindings) => {
+                    for binding in bindings {
+                        binding.check_calls(&local_functions)?;
+                        for name in binding.names() {
+                            local_functions.remove(name);
+                        }
+                    }
+                }
+                BlockItem::Definition(function) => {
+                    let prototype = function.prototype();
+                    local_functions.insert(prototype.name().to_string(), prototype.signature());
+                    function.check_calls(&local_functions)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        context.push_frame();
+        let result = self.check_item_types(context);
+        context.pop_frame();
+        result
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        context.push_frame();
+        let result = self.check_item_units(context);
+        context.pop_frame();
+        result
+    }
+
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        differentiator.push_frame();
+        let result = self.differentiate_items(differentiator);
+        differentiator.pop_frame();
+        result
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+pub struct BooleanExpression {
+    value: bool,
+}
+
+impl BooleanExpression {
+    pub fn new(value: bool) -> Self {
+        Self { value }
+    }
+}
+
+impl Display for BooleanExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BooleanExpression: {}", self.value)
+    }
+}
+
+impl ASTNode for BooleanExpression {
+    fn print(&self) {
+        println!("Node: BooleanExpression");
+        println!("BooleanExpression value: {}", self.value);
+    }
+
+    fn to_s_expression(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(self.value.to_string())
+    }
+
+    fn check_scope(&self, _scope: &mut Scope) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_types(&self, _context: &mut TypeContext) -> Result<Type, CompilerError> {
+        Ok(Type::Bool)
+    }
+
+    fn check_units(&self, _context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        Ok(Measure::Unknown)
+    }
+
+    fn constant_boolean(&self) -> Option<bool> {
+        Some(self.value)
+    }
+
+    fn evaluate_constant(
+        &self,
+        _evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        Ok(ConstantValue::Bool(self.value))
+    }
+
+    fn differentiate(&self, _differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        Ok(Dual::constant(Box::new(Self::new(self.value))))
+    }
+}
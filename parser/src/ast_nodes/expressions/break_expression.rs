@@ -0,0 +1,84 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `break` or `break value`, leaving the innermost loop. Every `break` of a loop has to give a
+/// value of the same type, unit when it gives none
+pub struct BreakExpression {
+    value: Option<Box<dyn ASTNode>>,
+    location: SourceLocation,
+}
+
+impl BreakExpression {
+    pub fn new(value: Option<Box<dyn ASTNode>>, location: SourceLocation) -> Self {
+        Self { value, location }
+    }
+}
+
+impl Display for BreakExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "Break: {}", value),
+            None => write!(f, "Break"),
+        }
+    }
+}
+
+impl ASTNode for BreakExpression {
+    fn print(&self) {
+        println!("Node: Break Expression");
+        if let Some(value) = &self.value {
+            println!("Break Expression Value: {}", value);
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        match &self.value {
+            Some(value) => format!("(break {})", value.to_s_expression()),
+            None => String::from("(break)"),
+        }
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        match &self.value {
+            Some(value) => value.check_scope(scope),
+            None => Ok(()),
+        }
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        match &self.value {
+            Some(value) => value.check_calls(functions),
+            None => Ok(()),
+        }
+    }
+
+    /// Control never continues past a `break`, so it fits wherever a value of any type is
+    /// expected
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let value_type: Type = match &self.value {
+            Some(value) => value.check_types(context)?,
+            None => Type::Unit,
+        };
+        let loop_type: Type = context.loop_type().cloned().ok_or_else(|| {
+            CompilerError::OutsideLoopError(String::from("break")).with_location(&self.location)
+        })?;
+        context.coerce(&loop_type, &value_type, &self.location)?;
+        Ok(context.fresh_variable())
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        if let Some(value) = &self.value {
+            value.check_units(context)?;
+        }
+        Ok(Measure::Unknown)
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `continue`, skipping the rest of the innermost loop's body and checking its condition again
+pub struct ContinueExpression {
+    location: SourceLocation,
+}
+
+impl ContinueExpression {
+    pub fn new(location: SourceLocation) -> Self {
+        Self { location }
+    }
+}
+
+impl Display for ContinueExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Continue")
+    }
+}
+
+impl ASTNode for ContinueExpression {
+    fn print(&self) {
+        println!("Node: Continue Expression");
+    }
+
+    fn to_s_expression(&self) -> String {
+        String::from("(continue)")
+    }
+
+    fn check_scope(&self, _scope: &mut Scope) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    /// Like `break`, control never continues past it
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        if context.loop_type().is_none() {
+            return Err(CompilerError::OutsideLoopError(String::from("continue"))
+                .with_location(&self.location));
+        }
+        Ok(context.fresh_variable())
+    }
+
+    fn check_units(&self, _context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        Ok(Measure::Unknown)
+    }
+}
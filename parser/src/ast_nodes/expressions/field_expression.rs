@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    struct_signature::StructSignature,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `record.field`
+pub struct FieldExpression {
+    record: Box<dyn ASTNode>,
+    field: String,
+    location: SourceLocation,
+}
+
+impl FieldExpression {
+    pub fn new(record: Box<dyn ASTNode>, field: &str, location: SourceLocation) -> Self {
+        Self {
+            record,
+            field: field.to_string(),
+            location,
+        }
+    }
+
+    /// The name of the struct the field is read from. A record whose type is not known yet is
+    /// taken to be the only struct with a field of that name
+    fn struct_name(
+        &self,
+        record_type: &Type,
+        context: &mut TypeContext,
+    ) -> Result<String, CompilerError> {
+        match context.resolve_shape(record_type) {
+            Type::Named(name) if context.struct_signature(&name).is_some() => Ok(name),
+            Type::Variable(_) => {
+                let candidates: Vec<String> = context
+                    .structs_with_field(&self.field)
+                    .iter()
+                    .map(|signature| signature.name().to_string())
+                    .collect();
+                match candidates.as_slice() {
+                    [name] => {
+                        context.unify(&Type::Named(name.clone()), record_type, &self.location)?;
+                        Ok(name.clone())
+                    }
+                    _ => Err(CompilerError::UnknownRecordTypeError(self.field.clone())
+                        .with_location(&self.location)),
+                }
+            }
+            other => Err(CompilerError::NotAStructError(
+                context.resolve_deep(&other),
+                self.field.clone(),
+            )
+            .with_location(&self.location)),
+        }
+    }
+}
+
+impl Display for FieldExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Record: {}\nField: {}", self.record, self.field)
+    }
+}
+
+impl ASTNode for FieldExpression {
+    fn print(&self) {
+        println!("Node: Field Expression");
+        println!("Field Expression Record: {}", self.record);
+        println!("Field Expression Field: {}", self.field);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("(. {} {})", self.record.to_s_expression(), self.field)
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.record.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.record.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let record_type: Type = self.record.check_types(context)?;
+        let name: String = self.struct_name(&record_type, context)?;
+
+        let signature: &StructSignature = match context.struct_signature(&name) {
+            Some(signature) => signature,
+            None => return Err(CompilerError::UnknownTypeError(name).with_location(&self.location)),
+        };
+        match signature.field(&self.field) {
+            Some(field) => Ok(field.field_type().clone()),
+            None => Err(CompilerError::UnknownFieldError(name, self.field.clone())
+                .with_location(&self.location)),
+        }
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.record.check_units(context)?;
+        Ok(Measure::Unknown)
+    }
+}
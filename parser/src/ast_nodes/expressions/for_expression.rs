@@ -0,0 +1,147 @@
+use std::fmt::Display;
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    types::{Constraint, Type},
+};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+use super::var_expression::BindingPattern;
+
+/// `for pattern in iterable do body`, running the body once for every element of an array or
+/// every value a generator yields, with the pattern bound to it. The loop gives unit
+pub struct ForExpression {
+    pattern: BindingPattern,
+    iterable: Box<dyn ASTNode>,
+    body: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl ForExpression {
+    pub fn new(
+        pattern: BindingPattern,
+        iterable: Box<dyn ASTNode>,
+        body: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            pattern,
+            iterable,
+            body,
+            location,
+        }
+    }
+
+    /// The type of the values going over `iterable_type` gives. A value whose type is not
+    /// known yet is only required to be iterable, so it can still be an array or a generator
+    fn element_type(
+        &self,
+        iterable_type: &Type,
+        context: &mut TypeContext,
+    ) -> Result<Type, CompilerError> {
+        match context.resolve_shape(iterable_type) {
+            Type::Array(element_type) | Type::Generator(element_type) => Ok(*element_type),
+            Type::Variable(_) => {
+                let element_type: Type = context.fresh_variable();
+                let constraint: Constraint = Constraint::Iterable(Box::new(element_type.clone()));
+                context.require(constraint, iterable_type, &self.location)?;
+                Ok(element_type)
+            }
+            other => Err(
+                CompilerError::NotIterableError(context.resolve_deep(&other))
+                    .with_location(&self.location),
+            ),
+        }
+    }
+
+    fn check_pattern_and_body_types(
+        &self,
+        element_type: Type,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        self.pattern
+            .check_types(element_type, context, &self.location)?;
+        self.body.check_types(context)?;
+        Ok(())
+    }
+}
+
+impl Display for ForExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "For: {}\nIn: {}\nDo: {}",
+            self.pattern, self.iterable, self.body
+        )
+    }
+}
+
+impl ASTNode for ForExpression {
+    fn print(&self) {
+        println!("Node: For Expression");
+        println!("For Expression Pattern: {}", self.pattern);
+        println!("For Expression Iterable: {}", self.iterable);
+        println!("For Expression Body: {}", self.body);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(for {} {} {})",
+            self.pattern,
+            self.iterable.to_s_expression(),
+            self.body.to_s_expression()
+        )
+    }
+
+    /// The pattern is only visible in the body, and a fresh binding is made for every element,
+    /// so it cannot be assigned to
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.iterable.check_scope(scope)?;
+        scope.push_frame();
+        self.pattern.declare(scope, Mutability::Immutable);
+        let result = self.body.check_scope(scope);
+        scope.pop_frame();
+        result
+    }
+
+    /// The names the pattern binds hide any function with the same name from the body
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.iterable.check_calls(functions)?;
+
+        let mut visible_functions: FunctionTable = functions.clone();
+        for name in self.pattern.names() {
+            visible_functions.remove(name);
+        }
+        self.body.check_calls(&visible_functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let iterable_type: Type = self.iterable.check_types(context)?;
+        let element_type: Type = self.element_type(&iterable_type, context)?;
+
+        context.push_frame();
+        context.push_loop(Type::Unit);
+        let result = self.check_pattern_and_body_types(element_type, context);
+        context.pop_loop();
+        context.pop_frame();
+        result?;
+        Ok(Type::Unit)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.iterable.check_units(context)?;
+        context.push_frame();
+        self.pattern.declare_units(Measure::Unknown, context);
+        let result = self.body.check_units(context);
+        context.pop_frame();
+        result.map(|_| Measure::Unknown)
+    }
+}
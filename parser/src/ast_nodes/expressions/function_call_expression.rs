@@ -1,45 +1,413 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
-use crate::ast_node::ASTNode;
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
 
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::{
+        expressions::var_expression::VarBinding, functions::function_definition::Function,
+    },
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{multiply, Atom, Differentiator, Dual},
+    function_signature::FunctionSignature,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// A call argument, either positional or given as `name: value`
+pub struct Argument {
+    name: Option<String>,
+    value: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl Argument {
+    pub fn new(name: Option<&str>, value: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self {
+            name: name.map(|n| n.to_string()),
+            value,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn to_s_expression(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}:{}", name, self.value.to_s_expression()),
+            None => self.value.to_s_expression(),
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match &self.name {
+            Some(name) => Some(format!("{}: {}", name, self.value.to_source()?)),
+            None => self.value.to_source(),
+        }
+    }
+}
+
+impl Display for Argument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}: {}", name, self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// A call to whatever `callee` evaluates to. Calls to a function by name can use named
+/// arguments and defaults, calls through a function value have to pass every argument in order
 pub struct FunctionCallExpression {
-    calle: String,
-    args: Vec<Box<dyn ASTNode>>,
+    callee: Box<dyn ASTNode>,
+    args: Vec<Argument>,
+    location: SourceLocation,
 }
 
 impl FunctionCallExpression {
-    pub fn new(calle: &str, args: Vec<Box<dyn ASTNode>>) -> Self {
+    pub fn new(callee: Box<dyn ASTNode>, args: Vec<Argument>, location: SourceLocation) -> Self {
         Self {
-            calle: calle.to_string(),
+            callee,
             args,
+            location,
         }
     }
-}
 
-impl Display for FunctionCallExpression {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn bind_arguments(
+        &self,
+        signature: &FunctionSignature,
+    ) -> Result<Vec<Option<usize>>, CompilerError> {
+        let argument_names: Vec<Option<&str>> = self.args.iter().map(|arg| arg.name()).collect();
+        signature.bind_arguments(&argument_names)
+    }
+
+    /// Report a precondition of the called function that the arguments already break when
+    /// they are known at compile time, from literals and the values of `constants`.
+    /// Preconditions depending on anything else are left to be checked when the program runs
+    fn check_preconditions(
+        &self,
+        signature: &FunctionSignature,
+        bound_arguments: &[Option<usize>],
+        constants: &HashMap<String, ConstantValue>,
+    ) -> Result<(), CompilerError> {
+        if signature.preconditions().is_empty() {
+            return Ok(());
+        }
+
+        let mut arguments: HashMap<String, ConstantValue> = HashMap::new();
+        for (parameter, bound_argument) in signature.parameters().iter().zip(bound_arguments) {
+            let value = bound_argument.and_then(|index| {
+                let mut evaluator: ConstantEvaluator =
+                    ConstantEvaluator::new(&[]).with_locals(constants.clone());
+                self.args[index]
+                    .value
+                    .evaluate_constant(&mut evaluator)
+                    .ok()
+            });
+            if let Some(value) = value {
+                arguments.insert(parameter.name().to_string(), value);
+            }
+        }
+
+        let mut evaluator: ConstantEvaluator = ConstantEvaluator::new(&[]).with_locals(arguments);
+        for contract in signature.preconditions() {
+            if let Ok(ConstantValue::Bool(false)) =
+                contract.condition().evaluate_constant(&mut evaluator)
+            {
+                return Err(CompilerError::ContractViolationError(
+                    signature.name().to_string(),
+                    contract.to_s_expression(),
+                    contract.location().clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn args_string(&self) -> String {
         let mut args_string: String = String::with_capacity(self.args.len());
         for arg in &self.args {
             args_string = format!("{} {}", args_string, arg);
         }
+        args_string
+    }
+
+    /// Call the function named `name` directly, matching the arguments against its parameters
+    fn check_named_call_types(
+        &self,
+        name: &str,
+        argument_types: &[Type],
+        context: &mut TypeContext,
+    ) -> Result<Type, CompilerError> {
+        let signature: FunctionSignature = context
+            .function(name)
+            .map_err(|e| e.with_location(&self.location))?;
+        context.record_call(&signature, &self.location);
+        let bound_arguments: Vec<Option<usize>> = self
+            .bind_arguments(&signature)
+            .map_err(|e| e.with_location(&self.location))?;
+
+        // arguments made of literals alone were checked along with the calls, the values of
+        // constants are only known now
+        if self
+            .check_preconditions(&signature, &bound_arguments, &HashMap::new())
+            .is_ok()
+        {
+            let constants: HashMap<String, ConstantValue> = context.constant_values();
+            if let Err(e) = self.check_preconditions(&signature, &bound_arguments, &constants) {
+                context.record_error(e.with_location(&self.location));
+            }
+        }
+
+        for (parameter, bound_argument) in signature.parameters().iter().zip(bound_arguments) {
+            if let Some(index) = bound_argument {
+                context.coerce(
+                    parameter.parameter_type(),
+                    &argument_types[index],
+                    &self.args[index].location,
+                )?;
+            }
+        }
+
+        Ok(signature.return_type().clone())
+    }
+
+    /// Call a function value. Its type is only known to be a function once it is called, so a
+    /// callee of unknown type is taken to be one accepting these arguments. What the value
+    /// does is not known either, so the call is taken to have side effects
+    fn check_value_call_types(
+        &self,
+        argument_types: &[Type],
+        context: &mut TypeContext,
+    ) -> Result<Type, CompilerError> {
+        if let Some(name) = self.args.iter().find_map(|arg| arg.name()) {
+            return Err(CompilerError::NamedArgumentToValueError(name.to_string())
+                .with_location(&self.location));
+        }
+
+        let callee_type: Type = self.callee.check_types(context)?;
+        context.record_value_call(&self.callee.to_s_expression());
+        let (parameter_types, return_type): (Vec<Type>, Type) =
+            match context.resolve_shape(&callee_type) {
+                Type::Function(parameter_types, return_type) => (parameter_types, *return_type),
+                Type::Variable(_) => {
+                    let parameter_types: Vec<Type> = argument_types
+                        .iter()
+                        .map(|_| context.fresh_variable())
+                        .collect();
+                    let return_type: Type = context.fresh_variable();
+                    let function_type: Type =
+                        Type::Function(parameter_types.clone(), Box::new(return_type.clone()));
+                    context.unify(&function_type, &callee_type, &self.location)?;
+                    (parameter_types, return_type)
+                }
+                other => {
+                    return Err(
+                        CompilerError::NotAFunctionError(context.resolve_deep(&other))
+                            .with_location(&self.location),
+                    )
+                }
+            };
+
+        if parameter_types.len() != argument_types.len() {
+            return Err(CompilerError::ArgumentCountError(
+                parameter_types.len(),
+                argument_types.len(),
+            )
+            .with_location(&self.location));
+        }
+        for ((parameter_type, argument_type), arg) in
+            parameter_types.iter().zip(argument_types).zip(&self.args)
+        {
+            context.coerce(parameter_type, argument_type, &arg.location)?;
+        }
+        Ok(return_type)
+    }
+}
 
+impl Display for FunctionCallExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "FunctionCallExpression calle: {}\nFunctionCallExpression args: {}",
-            self.calle, args_string
+            "FunctionCallExpression callee: {}\nFunctionCallExpression args: {}",
+            self.callee,
+            self.args_string()
         )
     }
 }
 
 impl ASTNode for FunctionCallExpression {
     fn print(&self) {
-        let mut args_string: String = String::with_capacity(self.args.len());
+        println!("Node: FunctionCallExpression");
+        println!("FunctionCallExpression callee: {}", self.callee);
+        println!("FunctionCallExpression args: {}", self.args_string());
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut s_expression: String = format!("({}", self.callee.to_s_expression());
         for arg in &self.args {
-            args_string = format!("{} {}", args_string, arg);
+            s_expression = format!("{} {}", s_expression, arg.to_s_expression());
         }
+        format!("{})", s_expression)
+    }
 
-        println!("Node: FunctionCallExpression");
-        println!("FunctionCallExpression calle: {}", self.calle);
-        println!("FunctionCallExpression args: {}", args_string);
+    fn to_source(&self) -> Option<String> {
+        let args: Option<Vec<String>> = self.args.iter().map(|arg| arg.to_source()).collect();
+        Some(format!(
+            "{}({})",
+            self.callee.to_source()?,
+            args?.join(", ")
+        ))
+    }
+
+    /// A callee that is a bare name has to be bound, but is reported as an unknown function
+    /// rather than an unknown variable when it is not
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        let lookup = |name: &str| match self.callee.is_top_level_name() {
+            true => scope.lookup_top_level(name),
+            false => scope.lookup(name),
+        };
+        match self.callee.variable_name() {
+            Some(name) if lookup(name).is_none() => scope.record_error(
+                CompilerError::UndefinedFunctionError(name.to_string())
+                    .with_location(&self.location),
+            ),
+            Some(_) => {}
+            None => self.callee.check_scope(scope)?,
+        }
+
+        for arg in &self.args {
+            arg.value.check_scope(scope)?;
+        }
+        Ok(())
+    }
+
+    /// Only calls to a function by name can be checked before types are known. Anything else
+    /// is called through a value, which is checked along with its type
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        match self.callee.variable_name() {
+            Some(name) => {
+                if let Some(signature) = functions.get(name) {
+                    self.bind_arguments(signature)
+                        .and_then(|bound_arguments| {
+                            self.check_preconditions(signature, &bound_arguments, &HashMap::new())
+                        })
+                        .map_err(|e| e.with_location(&self.location))?;
+                }
+            }
+            None => self.callee.check_calls(functions)?,
+        }
+
+        for arg in &self.args {
+            arg.value.check_calls(functions)?;
+        }
+        Ok(())
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let mut argument_types: Vec<Type> = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            argument_types.push(arg.value.check_types(context)?);
+        }
+
+        match self.callee.variable_name() {
+            Some(name) if self.callee.is_top_level_name() || !context.is_variable(name) => {
+                self.check_named_call_types(name, &argument_types, context)
+            }
+            _ => self.check_value_call_types(&argument_types, context),
+        }
+    }
+
+    /// Calls to a known math function with one argument follow the chain rule. Any other call
+    /// is only differentiable when nothing passed to it depends on the variable, as its body
+    /// is not differentiated along with the caller's
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        if let (Some(name), [argument]) = (self.callee.variable_name(), self.args.as_slice()) {
+            if argument.name.is_none() && differentiator.is_math_function(name) {
+                let argument: Dual = argument.value.differentiate(differentiator)?;
+                let (u, du) = differentiator.atoms(argument, &mut bindings);
+                let value: Box<dyn ASTNode> = differentiator.call(name, u.node());
+                let Some(du) = du else {
+                    return Ok(Dual::constant(value).after(bindings));
+                };
+                let value: Atom = differentiator.bind("u", value, &mut bindings);
+                let derivative: Box<dyn ASTNode> = differentiator
+                    .math_derivative(name, &u, &value)
+                    .map_err(|e| e.with_location(&self.location))?;
+                let tangent: Box<dyn ASTNode> = multiply(derivative, du.node());
+                return Ok(Dual::new(value.node(), Some(tangent)).after(bindings));
+            }
+        }
+
+        let non_differentiable = || {
+            CompilerError::NonDifferentiableError(self.to_s_expression())
+                .with_location(&self.location)
+        };
+        let (mut own_bindings, callee, tangent) =
+            self.callee.differentiate(differentiator)?.into_parts();
+        if tangent.is_some() {
+            return Err(non_differentiable());
+        }
+        bindings.append(&mut own_bindings);
+
+        let mut args: Vec<Argument> = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            let (mut own_bindings, value, tangent) =
+                arg.value.differentiate(differentiator)?.into_parts();
+            if tangent.is_some() {
+                return Err(non_differentiable());
+            }
+            bindings.append(&mut own_bindings);
+            args.push(Argument::new(arg.name(), value, arg.location.clone()));
+        }
+        Ok(Dual::constant(Box::new(FunctionCallExpression::new(
+            callee,
+            args,
+            self.location.clone(),
+        )))
+        .after(bindings))
+    }
+
+    /// A call to a function of the module gives what its body works out to with its
+    /// parameters in the units of the arguments. An argument its body cannot be used with is
+    /// reported here, at the call
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        if self.callee.variable_name().is_none() {
+            self.callee.check_units(context)?;
+        }
+        let mut measures: Vec<Measure> = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            measures.push(arg.value.check_units(context)?);
+        }
+
+        let function: Option<&Function> = match self.callee.variable_name() {
+            Some(name) if self.callee.is_top_level_name() => context.top_level_function(name),
+            Some(name) => context.function(name),
+            None => None,
+        };
+        let function: &Function = match function {
+            Some(function) => function,
+            None => return Ok(Measure::Unknown),
+        };
+        // the arguments of a call that cannot be bound were reported along with its types
+        let bound_arguments: Vec<Option<usize>> =
+            match self.bind_arguments(&function.prototype().signature()) {
+                Ok(bound_arguments) => bound_arguments,
+                Err(_) => return Ok(Measure::Unknown),
+            };
+
+        let parameters: Vec<Measure> = bound_arguments
+            .iter()
+            .map(|index| index.map_or(Measure::Unknown, |index| measures[index].clone()))
+            .collect();
+        context.call(function, &parameters).map_err(|e| match e {
+            CompilerError::LocatedError(_, cause) => cause.with_location(&self.location),
+            e => e.with_location(&self.location),
+        })
     }
 }
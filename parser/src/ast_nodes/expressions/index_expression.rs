@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `array[index]`. The location is that of the index, which is where an out of bounds access is
+/// reported
+pub struct IndexExpression {
+    array: Box<dyn ASTNode>,
+    index: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl IndexExpression {
+    pub fn new(array: Box<dyn ASTNode>, index: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self {
+            array,
+            index,
+            location,
+        }
+    }
+
+    /// Reject an index that is known at compile time to fall outside an array whose length is
+    /// also known. Any other index is checked when it is evaluated
+    fn check_bounds(&self) -> Result<(), CompilerError> {
+        let (index, length): (i64, usize) =
+            match (self.index.constant_integer(), self.array.constant_length()) {
+                (Some(index), Some(length)) => (index, length),
+                _ => return Ok(()),
+            };
+
+        if index < 0 || index as usize >= length {
+            return Err(
+                CompilerError::IndexOutOfBoundsError(index, length).with_location(&self.location)
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Display for IndexExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Array: {}\nIndex: {}", self.array, self.index)
+    }
+}
+
+impl ASTNode for IndexExpression {
+    fn print(&self) {
+        println!("Node: Index Expression");
+        println!("Index Expression Array: {}", self.array);
+        println!("Index Expression Index: {}", self.index);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(index {} {})",
+            self.array.to_s_expression(),
+            self.index.to_s_expression()
+        )
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.array.check_scope(scope)?;
+        self.index.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.array.check_calls(functions)?;
+        self.index.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let array_type: Type = self.array.check_types(context)?;
+        let index_type: Type = self.index.check_types(context)?;
+        context.unify(&Type::I64, &index_type, &self.location)?;
+
+        let element_type: Type = match context.resolve_shape(&array_type) {
+            Type::Array(element_type) => *element_type,
+            Type::Variable(_) => {
+                let element_type: Type = context.fresh_variable();
+                let expected: Type = Type::Array(Box::new(element_type.clone()));
+                context.unify(&expected, &array_type, &self.location)?;
+                element_type
+            }
+            other => {
+                return Err(CompilerError::NotAnArrayError(context.resolve_deep(&other))
+                    .with_location(&self.location))
+            }
+        };
+
+        self.check_bounds()?;
+        Ok(element_type)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.array.check_units(context)?;
+        self.index.check_units(context)?;
+        Ok(Measure::Unknown)
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type, units::Unit};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+pub struct IntegerExpression {
+    value: i64,
+    /// The unit of measure given as `<m/s^2>` after the number, if any
+    unit: Option<Unit>,
+    location: SourceLocation,
+}
+
+impl IntegerExpression {
+    pub fn new(value: i64, location: SourceLocation) -> Self {
+        Self {
+            value,
+            unit: None,
+            location,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+}
+
+impl Display for IntegerExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IntegerExpression: {}", self.value)
+    }
+}
+
+impl ASTNode for IntegerExpression {
+    fn print(&self) {
+        println!("Node: IntegerExpression");
+        println!("IntegerExpression value: {}", self.value);
+    }
+
+    fn to_s_expression(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{}<{}>", self.value, unit),
+            None => self.value.to_string(),
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match &self.unit {
+            Some(unit) => Some(format!("{}<{}>", self.value, unit)),
+            None => Some(format!("{}", self.value)),
+        }
+    }
+
+    fn check_scope(&self, _scope: &mut Scope) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    /// An integer literal can stand for a number of either type, so `x + 1` works whatever
+    /// number `x` is. It is an i64 unless what it is used with makes it an f64
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        Ok(context.integer_literal(&self.location))
+    }
+
+    fn check_units(&self, _context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        Ok(Measure::Unit(self.unit.clone().unwrap_or_default()))
+    }
+
+    fn constant_integer(&self) -> Option<i64> {
+        Some(self.value)
+    }
+
+    fn evaluate_constant(
+        &self,
+        _evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        Ok(ConstantValue::I64(self.value))
+    }
+
+    fn differentiate(&self, _differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        Ok(Dual::constant(Box::new(Self {
+            value: self.value,
+            unit: self.unit.clone(),
+            location: self.location.clone(),
+        })))
+    }
+}
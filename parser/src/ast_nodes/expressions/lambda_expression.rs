@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::functions::function_prototype::FunctionPrototype,
+    function_signature::FunctionSignature,
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// An anonymous function such as `fn(x) x * 2`. Unlike a definition it can read the variables
+/// around it, which it captures when it is created
+pub struct LambdaExpression {
+    prototype: Box<FunctionPrototype>,
+    body: Box<dyn ASTNode>,
+    body_location: SourceLocation,
+}
+
+impl LambdaExpression {
+    pub fn new(
+        prototype: Box<FunctionPrototype>,
+        body: Box<dyn ASTNode>,
+        body_location: SourceLocation,
+    ) -> Self {
+        Self {
+            prototype,
+            body,
+            body_location,
+        }
+    }
+
+    fn check_body_type(
+        &self,
+        signature: &FunctionSignature,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        self.prototype.check_parameter_types(signature, context)?;
+        let body_type: Type = self.body.check_types(context)?;
+        context.coerce(signature.return_type(), &body_type, &self.body_location)?;
+        context.check_returned_errors(signature.return_type(), &self.body_location)
+    }
+}
+
+impl Display for LambdaExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Lambda Parameters: {}\nLambda Body: {}",
+            self.prototype.signature_s_expression(),
+            self.body
+        )
+    }
+}
+
+impl ASTNode for LambdaExpression {
+    fn print(&self) {
+        println!("Node: Lambda Expression");
+        println!(
+            "Lambda Expression Parameters: {}",
+            self.prototype.signature_s_expression()
+        );
+        println!("Lambda Expression Body: {}", self.body);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(fn {} {})",
+            self.prototype.signature_s_expression(),
+            self.body.to_s_expression()
+        )
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        scope.push_frame();
+        for arg in self.prototype.args() {
+            scope.declare(arg.name(), Mutability::Immutable);
+        }
+        let result = self.body.check_scope(scope);
+        scope.pop_frame();
+        result
+    }
+
+    /// Parameters hide any function with the same name from the body
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        let mut visible_functions: FunctionTable = functions.clone();
+        for arg in self.prototype.args() {
+            visible_functions.remove(arg.name());
+        }
+        self.body.check_calls(&visible_functions)
+    }
+
+    /// Lambdas are not generalized, every use of one shares the same parameter types
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let signature: FunctionSignature = self.prototype.fresh_signature(context);
+
+        context.push_frame();
+        context.push_handler();
+        let result = self.check_body_type(&signature, context);
+        context.pop_handler();
+        context.pop_frame();
+
+        result.map(|_| signature.function_type())
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        context.push_frame();
+        for arg in self.prototype.args() {
+            context.declare(arg.name(), Measure::Unknown);
+        }
+        let result = self.body.check_units(context);
+        context.pop_frame();
+        result.map(|_| Measure::Unknown)
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::Display;
+
+use common::{
+    error::CompilerError, source_location::SourceLocation, token::SimpleBinaryOperater, types::Type,
+};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `left && right` or `left || right`. Both operands are bools, and they short-circuit: the
+/// left operand is evaluated first, and the right one only when the left one does not decide
+/// the result by itself, i.e. when it is true for `&&` and false for `||`
+pub struct LogicalExpression {
+    operator: SimpleBinaryOperater,
+    left_hand_side: Box<dyn ASTNode>,
+    right_hand_side: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl LogicalExpression {
+    pub fn new(
+        operator: SimpleBinaryOperater,
+        left_hand_side: Box<dyn ASTNode>,
+        right_hand_side: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            operator,
+            left_hand_side,
+            right_hand_side,
+            location,
+        }
+    }
+
+    /// The value of the left operand that decides the result without the right one
+    fn short_circuit_value(&self) -> bool {
+        self.operator == SimpleBinaryOperater::Or
+    }
+}
+
+impl Display for LogicalExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Operator: {}\nLHS: {}\nRHS: {}",
+            self.operator.symbol(),
+            self.left_hand_side,
+            self.right_hand_side
+        )
+    }
+}
+
+impl ASTNode for LogicalExpression {
+    fn print(&self) {
+        println!("Node: Logical Expression");
+        println!("Logical Expression Operator: {}", self.operator.symbol());
+        println!("Logical Expression LHS: {}", self.left_hand_side);
+        println!("Logical Expression RHS: {}", self.right_hand_side);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "({} {} {})",
+            self.operator.symbol(),
+            self.left_hand_side.to_s_expression(),
+            self.right_hand_side.to_s_expression()
+        )
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(format!(
+            "({} {} {})",
+            self.left_hand_side.to_source()?,
+            self.operator.symbol(),
+            self.right_hand_side.to_source()?
+        ))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.left_hand_side.check_scope(scope)?;
+        self.right_hand_side.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.left_hand_side.check_calls(functions)?;
+        self.right_hand_side.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let left_type: Type = self.left_hand_side.check_types(context)?;
+        let right_type: Type = self.right_hand_side.check_types(context)?;
+
+        for operand_type in [&left_type, &right_type] {
+            if context
+                .unify(&Type::Bool, operand_type, &self.location)
+                .is_err()
+            {
+                return Err(CompilerError::InvalidOperandTypesError(
+                    self.operator.symbol(),
+                    context.resolve_shape(&left_type),
+                    context.resolve_shape(&right_type),
+                )
+                .with_location(&self.location));
+            }
+        }
+        Ok(Type::Bool)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.left_hand_side.check_units(context)?;
+        self.right_hand_side.check_units(context)?;
+        Ok(Measure::Unknown)
+    }
+
+    /// Known when either operand decides the result by itself, or when both are known
+    fn constant_boolean(&self) -> Option<bool> {
+        let deciding: bool = self.short_circuit_value();
+        match (
+            self.left_hand_side.constant_boolean(),
+            self.right_hand_side.constant_boolean(),
+        ) {
+            (Some(value), _) | (_, Some(value)) if value == deciding => Some(deciding),
+            (Some(_), Some(right)) => Some(right),
+            _ => None,
+        }
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        let invalid = |operand: &ConstantValue| {
+            CompilerError::InvalidOperandTypesError(
+                self.operator.symbol(),
+                operand.value_type(),
+                Type::Bool,
+            )
+            .with_location(&self.location)
+        };
+
+        // the right operand is only evaluated when the left one does not decide the result
+        let left: ConstantValue = self.left_hand_side.evaluate_constant(evaluator)?;
+        match left.as_bool() {
+            Some(value) if value == self.short_circuit_value() => Ok(left),
+            Some(_) => {
+                let right: ConstantValue = self.right_hand_side.evaluate_constant(evaluator)?;
+                match right.as_bool() {
+                    Some(_) => Ok(right),
+                    None => Err(invalid(&right)),
+                }
+            }
+            None => Err(invalid(&left)),
+        }
+    }
+
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        let (bindings, left, _) = self
+            .left_hand_side
+            .differentiate(differentiator)?
+            .into_parts();
+        // the right operand keeps its bindings to itself, as it is not always evaluated
+        let right: Box<dyn ASTNode> = self
+            .right_hand_side
+            .differentiate(differentiator)?
+            .into_value();
+        Ok(Dual::constant(Box::new(LogicalExpression::new(
+            self.operator.clone(),
+            left,
+            right,
+            self.location.clone(),
+        )))
+        .after(bindings))
+    }
+}
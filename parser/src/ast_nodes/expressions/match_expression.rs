@@ -0,0 +1,595 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::expressions::{
+        tuple_index_expression::TupleIndexExpression, var_expression::VarBinding,
+    },
+    builtins::result_variant_field,
+    differentiator::{Atom, Differentiator, Dual},
+    enum_signature::VariantSignature,
+    exhaustiveness::{Constructor, Exhaustiveness, Space},
+    module::FunctionTable,
+    scope::{Binding, Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// A constant a pattern compares the value against
+#[derive(Clone)]
+pub enum LiteralPattern {
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl LiteralPattern {
+    fn literal_type(&self) -> Type {
+        match self {
+            LiteralPattern::I64(_) => Type::I64,
+            LiteralPattern::F64(_) => Type::F64,
+            LiteralPattern::Bool(_) => Type::Bool,
+            LiteralPattern::Str(_) => Type::Str,
+        }
+    }
+
+    fn space(&self) -> Space {
+        match self {
+            LiteralPattern::Bool(value) => {
+                Space::Constructed(Constructor::Bool(*value), Vec::new())
+            }
+            literal => Space::Constructed(Constructor::Literal(literal.to_string()), Vec::new()),
+        }
+    }
+}
+
+impl Display for LiteralPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteralPattern::I64(value) => write!(f, "{}", value),
+            LiteralPattern::F64(value) => write!(f, "{:?}", value),
+            LiteralPattern::Bool(value) => write!(f, "{}", value),
+            LiteralPattern::Str(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// The left hand side of a match arm. A bare name binds the value, unless it names a variant
+/// of an enum that carries nothing, in which case it only matches that variant. A capitalized
+/// name is always taken to be a variant
+#[derive(Clone)]
+pub enum Pattern {
+    Wildcard,
+    Literal(LiteralPattern),
+    Name(String),
+    Constructor(String, Vec<Pattern>, SourceLocation),
+    Tuple(Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Every name the pattern may bind, from left to right
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => Vec::new(),
+            Pattern::Name(name) => vec![name.as_str()],
+            Pattern::Constructor(_, fields, _) | Pattern::Tuple(fields) => {
+                fields.iter().flat_map(|field| field.names()).collect()
+            }
+        }
+    }
+
+    /// Bring every name the pattern binds into the innermost frame. Like parameters, they are
+    /// immutable
+    fn declare(&self, scope: &mut Scope) {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => {}
+            Pattern::Name(name) => {
+                if scope.lookup(name) != Some(Binding::Constructor) {
+                    scope.declare(name, Mutability::Immutable);
+                }
+            }
+            Pattern::Constructor(_, fields, _) | Pattern::Tuple(fields) => {
+                for field in fields {
+                    field.declare(scope);
+                }
+            }
+        }
+    }
+
+    /// Check the pattern can match a value of `expected`, declaring the names it binds, and
+    /// work out the values it matches
+    fn check_types(
+        &self,
+        expected: &Type,
+        context: &mut TypeContext,
+        location: &SourceLocation,
+    ) -> Result<Space, CompilerError> {
+        match self {
+            Pattern::Wildcard => Ok(Space::Anything),
+            Pattern::Literal(literal) => {
+                context.coerce(expected, &literal.literal_type(), location)?;
+                Ok(literal.space())
+            }
+            Pattern::Name(name) => {
+                if context.variant(name).is_some() || Self::is_variant_name(name) {
+                    return Self::check_variant(name, &[], expected, context, location);
+                }
+                context.declare_variable(name, expected.clone());
+                Ok(Space::Anything)
+            }
+            Pattern::Constructor(name, fields, location) => {
+                Self::check_variant(name, fields, expected, context, location)
+            }
+            Pattern::Tuple(elements) => {
+                let element_types: Vec<Type> =
+                    elements.iter().map(|_| context.fresh_variable()).collect();
+                context.unify(&Type::Tuple(element_types.clone()), expected, location)?;
+
+                let mut spaces: Vec<Space> = Vec::with_capacity(elements.len());
+                for (element, element_type) in elements.iter().zip(&element_types) {
+                    spaces.push(element.check_types(element_type, context, location)?);
+                }
+                Ok(Space::Constructed(Constructor::Tuple, spaces))
+            }
+        }
+    }
+
+    /// Whether `name` is written like a variant. Such a name never binds the value, so a
+    /// misspelled variant is reported instead of silently matching anything
+    fn is_variant_name(name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+    }
+
+    /// Check a pattern for the variant `name` along with the patterns for what it carries
+    fn check_variant(
+        name: &str,
+        fields: &[Pattern],
+        expected: &Type,
+        context: &mut TypeContext,
+        location: &SourceLocation,
+    ) -> Result<Space, CompilerError> {
+        let variant: VariantSignature = match context.variant(name) {
+            Some(variant) => variant.clone(),
+            None => return Self::check_result_variant(name, fields, expected, context, location),
+        };
+        if variant.fields().len() != fields.len() {
+            return Err(CompilerError::PatternArityError(
+                name.to_string(),
+                variant.fields().len(),
+                fields.len(),
+            )
+            .with_location(location));
+        }
+
+        let enum_type: Type = Type::Named(variant.enum_name().to_string());
+        context.unify(&enum_type, expected, location)?;
+
+        let mut spaces: Vec<Space> = Vec::with_capacity(fields.len());
+        for (field, declared) in fields.iter().zip(variant.fields()) {
+            let field_type: Type =
+                context.fixed_variable(declared.field_type().clone(), declared.location());
+            spaces.push(field.check_types(&field_type, context, location)?);
+        }
+        Ok(Space::Constructed(
+            Constructor::Variant(name.to_string()),
+            spaces,
+        ))
+    }
+
+    /// Check a pattern for `Ok` or `Err` of the builtin result type, which an enum declaring a
+    /// variant of the same name hides. Any other name is not a variant at all
+    fn check_result_variant(
+        name: &str,
+        fields: &[Pattern],
+        expected: &Type,
+        context: &mut TypeContext,
+        location: &SourceLocation,
+    ) -> Result<Space, CompilerError> {
+        let value_type: Type = context.fresh_variable();
+        let error_type: Type = context.fresh_variable();
+        let field_type: Type = match result_variant_field(name, &value_type, &error_type) {
+            Some(field_type) => field_type,
+            None => {
+                return Err(
+                    CompilerError::UnknownVariantError(name.to_string()).with_location(location)
+                )
+            }
+        };
+        let field: &Pattern = match fields {
+            [field] => field,
+            _ => {
+                return Err(
+                    CompilerError::PatternArityError(name.to_string(), 1, fields.len())
+                        .with_location(location),
+                )
+            }
+        };
+
+        let result_type: Type = Type::Result(Box::new(value_type), Box::new(error_type));
+        context.unify(&result_type, expected, location)?;
+
+        let space: Space = field.check_types(&field_type, context, location)?;
+        Ok(Space::Constructed(
+            Constructor::Variant(name.to_string()),
+            vec![space],
+        ))
+    }
+
+    /// Declare every name the pattern binds with what is known of its unit, taken apart from
+    /// `measure` along with the value. Enum values carry nothing known, like parameters
+    fn declare_units(&self, measure: &Measure, context: &mut UnitContext) {
+        match (self, measure) {
+            (Pattern::Wildcard | Pattern::Literal(_), _) => {}
+            (Pattern::Name(name), measure) => context.declare(name, measure.clone()),
+            (Pattern::Tuple(elements), Measure::Tuple(measures))
+                if elements.len() == measures.len() =>
+            {
+                for (element, measure) in elements.iter().zip(measures) {
+                    element.declare_units(measure, context);
+                }
+            }
+            (Pattern::Constructor(_, fields, _) | Pattern::Tuple(fields), _) => {
+                for field in fields {
+                    field.declare_units(&Measure::Unknown, context);
+                }
+            }
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        match self {
+            Pattern::Wildcard => String::from("_"),
+            Pattern::Literal(literal) => literal.to_string(),
+            Pattern::Name(name) => name.clone(),
+            Pattern::Constructor(name, fields, _) => {
+                let mut parts: Vec<String> = vec![name.clone()];
+                parts.extend(fields.iter().map(|field| field.to_s_expression()));
+                format!("({})", parts.join(" "))
+            }
+            Pattern::Tuple(elements) => {
+                let mut parts: Vec<String> = vec![String::from("tuple")];
+                parts.extend(elements.iter().map(|element| element.to_s_expression()));
+                format!("({})", parts.join(" "))
+            }
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Literal(literal) => write!(f, "{}", literal),
+            Pattern::Name(name) => write!(f, "{}", name),
+            Pattern::Constructor(name, fields, _) => {
+                let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+                write!(f, "{}({})", name, fields.join(", "))
+            }
+            Pattern::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// `pattern if guard => body`. The arm is taken when the pattern matches and the guard, if
+/// any, holds
+pub struct MatchArm {
+    pattern: Pattern,
+    guard: Option<Box<dyn ASTNode>>,
+    body: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl MatchArm {
+    pub fn new(
+        pattern: Pattern,
+        guard: Option<Box<dyn ASTNode>>,
+        body: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            pattern,
+            guard,
+            body,
+            location,
+        }
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.pattern.declare(scope);
+        if let Some(guard) = &self.guard {
+            guard.check_scope(scope)?;
+        }
+        self.body.check_scope(scope)
+    }
+
+    /// The names the pattern binds hide any function with the same name from the guard and body
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        let mut visible_functions: FunctionTable = functions.clone();
+        for name in self.pattern.names() {
+            visible_functions.remove(name);
+        }
+        if let Some(guard) = &self.guard {
+            guard.check_calls(&visible_functions)?;
+        }
+        self.body.check_calls(&visible_functions)
+    }
+
+    /// Check the arm against the value being matched and the type every arm gives, returning
+    /// the values its pattern matches
+    fn check_types(
+        &self,
+        scrutinee_type: &Type,
+        result_type: &Type,
+        context: &mut TypeContext,
+    ) -> Result<Space, CompilerError> {
+        let space: Space = self
+            .pattern
+            .check_types(scrutinee_type, context, &self.location)?;
+        if let Some(guard) = &self.guard {
+            let guard_type: Type = guard.check_types(context)?;
+            context.coerce(&Type::Bool, &guard_type, &self.location)?;
+        }
+        let body_type: Type = self.body.check_types(context)?;
+        context.coerce(result_type, &body_type, &self.location)?;
+        Ok(space)
+    }
+
+    /// The unit of the arm's value, given that of the value being matched
+    fn check_units(
+        &self,
+        scrutinee: &Measure,
+        context: &mut UnitContext,
+    ) -> Result<Measure, CompilerError> {
+        self.pattern.declare_units(scrutinee, context);
+        if let Some(guard) = &self.guard {
+            guard.check_units(context)?;
+        }
+        self.body.check_units(context)
+    }
+
+    /// The arm's guard and the dual of its body. A name bound to the whole value being matched
+    /// shares its tangent, anything taken apart from it does not depend on the variable
+    fn differentiate(
+        &self,
+        scrutinee: &Option<Atom>,
+        differentiator: &mut Differentiator,
+    ) -> Result<(Option<Box<dyn ASTNode>>, Dual), CompilerError> {
+        match &self.pattern {
+            Pattern::Name(name) => differentiator.declare_pattern_name(name, scrutinee.clone()),
+            pattern => {
+                for name in pattern.names() {
+                    differentiator.declare_pattern_name(name, None);
+                }
+            }
+        }
+        let guard: Option<Box<dyn ASTNode>> = match &self.guard {
+            Some(guard) => Some(guard.differentiate(differentiator)?.into_value()),
+            None => None,
+        };
+        Ok((guard, self.body.differentiate(differentiator)?))
+    }
+
+    fn to_s_expression(&self) -> String {
+        match &self.guard {
+            Some(guard) => format!(
+                "({} (if {}) {})",
+                self.pattern.to_s_expression(),
+                guard.to_s_expression(),
+                self.body.to_s_expression()
+            ),
+            None => format!(
+                "({} {})",
+                self.pattern.to_s_expression(),
+                self.body.to_s_expression()
+            ),
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match &self.guard {
+            Some(guard) => Some(format!(
+                "{} if {} => {}",
+                self.pattern,
+                guard.to_source()?,
+                self.body.to_source()?
+            )),
+            None => Some(format!("{} => {}", self.pattern, self.body.to_source()?)),
+        }
+    }
+}
+
+impl Display for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.guard {
+            Some(guard) => write!(f, "{} if {} => {}", self.pattern, guard, self.body),
+            None => write!(f, "{} => {}", self.pattern, self.body),
+        }
+    }
+}
+
+/// `match value { pattern => body, ... }`, giving the body of the first arm whose pattern
+/// matches. Every value must be matched by some arm, and every arm must match some value the
+/// arms before it do not
+pub struct MatchExpression {
+    scrutinee: Box<dyn ASTNode>,
+    arms: Vec<MatchArm>,
+    location: SourceLocation,
+}
+
+impl MatchExpression {
+    pub fn new(scrutinee: Box<dyn ASTNode>, arms: Vec<MatchArm>, location: SourceLocation) -> Self {
+        Self {
+            scrutinee,
+            arms,
+            location,
+        }
+    }
+
+    /// Check the arms' patterns against each other once their types are known. Arms with a
+    /// guard can always fail, so they never make a later arm unreachable
+    fn check_coverage(
+        &self,
+        spaces: &[Space],
+        scrutinee_type: &Type,
+        context: &TypeContext,
+    ) -> Result<(), CompilerError> {
+        let scrutinee_type: Type = context.resolve_deep(scrutinee_type);
+        let exhaustiveness: Exhaustiveness = Exhaustiveness::new(context);
+
+        let mut covered: Vec<Space> = Vec::new();
+        for (arm, space) in self.arms.iter().zip(spaces) {
+            if !exhaustiveness.is_useful(&covered, space, &scrutinee_type) {
+                return Err(CompilerError::UnreachableArmError.with_location(&arm.location));
+            }
+            if arm.guard.is_none() {
+                covered.push(space.clone());
+            }
+        }
+
+        match exhaustiveness.missing(&covered, &scrutinee_type) {
+            Some(missing) => Err(CompilerError::NonExhaustiveMatchError(missing.to_string())
+                .with_location(&self.location)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for MatchExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arms: Vec<String> = self.arms.iter().map(|arm| arm.to_string()).collect();
+        write!(
+            f,
+            "Match Value: {}\nMatch Arms: [{}]",
+            self.scrutinee,
+            arms.join(", ")
+        )
+    }
+}
+
+impl ASTNode for MatchExpression {
+    fn print(&self) {
+        println!("Node: Match Expression");
+        println!("Match Expression Value: {}", self.scrutinee);
+        for arm in &self.arms {
+            println!("Match Expression Arm: {}", arm);
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut parts: Vec<String> = vec![self.scrutinee.to_s_expression()];
+        parts.extend(self.arms.iter().map(|arm| arm.to_s_expression()));
+        format!("(match {})", parts.join(" "))
+    }
+
+    fn to_source(&self) -> Option<String> {
+        let arms: Option<Vec<String>> = self.arms.iter().map(|arm| arm.to_source()).collect();
+        Some(format!(
+            "match {} {{ {} }}",
+            self.scrutinee.to_source()?,
+            arms?.join(", ")
+        ))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.scrutinee.check_scope(scope)?;
+        for arm in &self.arms {
+            scope.push_frame();
+            let result = arm.check_scope(scope);
+            scope.pop_frame();
+            result?;
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.scrutinee.check_calls(functions)?;
+        for arm in &self.arms {
+            arm.check_calls(functions)?;
+        }
+        Ok(())
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let scrutinee_type: Type = self.scrutinee.check_types(context)?;
+        let result_type: Type = context.fresh_variable();
+
+        let mut spaces: Vec<Space> = Vec::with_capacity(self.arms.len());
+        for arm in &self.arms {
+            context.push_frame();
+            let result = arm.check_types(&scrutinee_type, &result_type, context);
+            context.pop_frame();
+            spaces.push(result?);
+        }
+
+        self.check_coverage(&spaces, &scrutinee_type, context)?;
+        Ok(result_type)
+    }
+
+    /// When any arm's value has a tangent, every arm gives a `(value, tangent)` pair and the
+    /// match is bound to a fresh variable so the two can be read back out of it
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        let scrutinee: Dual = self.scrutinee.differentiate(differentiator)?;
+        let (scrutinee, tangent) = differentiator.atoms(scrutinee, &mut bindings);
+
+        let mut arms: Vec<(Option<Box<dyn ASTNode>>, Dual)> = Vec::with_capacity(self.arms.len());
+        for arm in &self.arms {
+            differentiator.push_frame();
+            let result = arm.differentiate(&tangent, differentiator);
+            differentiator.pop_frame();
+            arms.push(result?);
+        }
+
+        let paired: bool = arms.iter().any(|(_, body)| body.has_tangent());
+        let arms: Vec<MatchArm> = self
+            .arms
+            .iter()
+            .zip(arms)
+            .map(|(arm, (guard, body))| {
+                let body: Box<dyn ASTNode> = match paired {
+                    true => body.into_pair(),
+                    false => body.into_value(),
+                };
+                MatchArm::new(arm.pattern.clone(), guard, body, arm.location.clone())
+            })
+            .collect();
+        let value: Box<dyn ASTNode> = Box::new(MatchExpression::new(
+            scrutinee.node(),
+            arms,
+            self.location.clone(),
+        ));
+        if !paired {
+            return Ok(Dual::constant(value).after(bindings));
+        }
+
+        let pair: Atom = differentiator.bind("m", value, &mut bindings);
+        let element = |index: usize| -> Box<dyn ASTNode> {
+            Box::new(TupleIndexExpression::new(
+                pair.node(),
+                index,
+                self.location.clone(),
+            ))
+        };
+        Ok(Dual::new(element(0), Some(element(1))).after(bindings))
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let scrutinee: Measure = self.scrutinee.check_units(context)?;
+        let mut measure: Measure = Measure::Unknown;
+        for arm in &self.arms {
+            context.push_frame();
+            let result = arm.check_units(&scrutinee, context);
+            context.pop_frame();
+            measure = measure.agree(&result?).map_err(|(expected, found)| {
+                CompilerError::InconsistentUnitError(expected, found).with_location(&arm.location)
+            })?;
+        }
+        Ok(measure)
+    }
+}
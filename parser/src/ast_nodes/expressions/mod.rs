@@ -1,4 +1,27 @@
+pub mod array_expression;
+pub mod assignment_expression;
 pub mod binary_expression;
+pub mod block_expression;
+pub mod boolean_expression;
+pub mod break_expression;
+pub mod continue_expression;
+pub mod field_expression;
+pub mod for_expression;
 pub mod function_call_expression;
+pub mod index_expression;
+pub mod integer_expression;
+pub mod lambda_expression;
+pub mod logical_expression;
+pub mod match_expression;
 pub mod numeric_expression;
+pub mod propagate_expression;
+pub mod string_expression;
+pub mod struct_expression;
+pub mod try_expression;
+pub mod tuple_expression;
+pub mod tuple_index_expression;
+pub mod unary_expression;
+pub mod var_expression;
 pub mod variable_expression;
+pub mod while_expression;
+pub mod yield_expression;
@@ -1,14 +1,31 @@
 use std::fmt::Display;
 
-use crate::ast_node::ASTNode;
+use common::{error::CompilerError, types::Type, units::Unit};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
 
 pub struct NumericExpression {
     value: f64,
+    /// The unit of measure given as `<m/s^2>` after the number, if any
+    unit: Option<Unit>,
 }
 
 impl NumericExpression {
     pub fn new(value: f64) -> Self {
-        Self { value }
+        Self { value, unit: None }
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = Some(unit);
+        self
     }
 }
 
@@ -23,4 +40,48 @@ impl ASTNode for NumericExpression {
         println!("Node: NumericExpression");
         println!("NumericExpression value: {}", self.value);
     }
+
+    fn to_s_expression(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{}<{}>", self.value, unit),
+            None => self.value.to_string(),
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match &self.unit {
+            Some(unit) => Some(format!("{:?}<{}>", self.value, unit)),
+            None => Some(format!("{:?}", self.value)),
+        }
+    }
+
+    fn check_scope(&self, _scope: &mut Scope) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_types(&self, _context: &mut TypeContext) -> Result<Type, CompilerError> {
+        Ok(Type::F64)
+    }
+
+    fn check_units(&self, _context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        Ok(Measure::Unit(self.unit.clone().unwrap_or_default()))
+    }
+
+    fn evaluate_constant(
+        &self,
+        _evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        Ok(ConstantValue::F64(self.value))
+    }
+
+    fn differentiate(&self, _differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        Ok(Dual::constant(Box::new(Self {
+            value: self.value,
+            unit: self.unit.clone(),
+        })))
+    }
 }
@@ -0,0 +1,75 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `result?`, giving the value a result holds. When it holds an error instead, the error is
+/// handed to the innermost try around it, or else returned from the function
+pub struct PropagateExpression {
+    operand: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl PropagateExpression {
+    pub fn new(operand: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self { operand, location }
+    }
+}
+
+impl Display for PropagateExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Propagate: {}", self.operand)
+    }
+}
+
+impl ASTNode for PropagateExpression {
+    fn print(&self) {
+        println!("Node: Propagate Expression");
+        println!("Propagate Expression Operand: {}", self.operand);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("(? {})", self.operand.to_s_expression())
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.operand.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.operand.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let operand_type: Type = self.operand.check_types(context)?;
+        let (value_type, error_type) = match context.resolve_shape(&operand_type) {
+            Type::Result(value_type, error_type) => (*value_type, *error_type),
+            Type::Variable(_) => {
+                let value_type: Type = context.fresh_variable();
+                let error_type: Type = context.fresh_variable();
+                let expected: Type =
+                    Type::Result(Box::new(value_type.clone()), Box::new(error_type.clone()));
+                context.unify(&expected, &operand_type, &self.location)?;
+                (value_type, error_type)
+            }
+            other => {
+                return Err(CompilerError::NotAResultError(context.resolve_deep(&other))
+                    .with_location(&self.location))
+            }
+        };
+
+        context.record_propagation(&error_type, &self.location)?;
+        Ok(value_type)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.operand.check_units(context)
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+pub struct StringExpression {
+    value: String,
+}
+
+impl StringExpression {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Display for StringExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StringExpression: {:?}", self.value)
+    }
+}
+
+impl ASTNode for StringExpression {
+    fn print(&self) {
+        println!("Node: StringExpression");
+        println!("StringExpression value: {:?}", self.value);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("{:?}", self.value)
+    }
+
+    fn check_scope(&self, _scope: &mut Scope) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    fn check_types(&self, _context: &mut TypeContext) -> Result<Type, CompilerError> {
+        Ok(Type::Str)
+    }
+
+    fn check_units(&self, _context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        Ok(Measure::Unknown)
+    }
+
+    fn evaluate_constant(
+        &self,
+        _evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        Ok(ConstantValue::Str(self.value.clone()))
+    }
+}
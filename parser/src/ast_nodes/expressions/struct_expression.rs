@@ -0,0 +1,194 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    struct_signature::{FieldSignature, StructSignature},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// A `name: value` pair in a struct construction
+pub struct FieldValue {
+    name: String,
+    value: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl FieldValue {
+    pub fn new(name: &str, value: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            location,
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+/// `Point { x: 1, y: 2 }`, or `Point { x: 1, ..p }` to copy every field not given from `p`
+pub struct StructExpression {
+    name: String,
+    fields: Vec<FieldValue>,
+    base: Option<Box<dyn ASTNode>>,
+    location: SourceLocation,
+}
+
+impl StructExpression {
+    pub fn new(
+        name: &str,
+        fields: Vec<FieldValue>,
+        base: Option<Box<dyn ASTNode>>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            fields,
+            base,
+            location,
+        }
+    }
+
+    fn fields_string(&self) -> String {
+        let mut fields: Vec<String> = self.fields.iter().map(|field| field.to_string()).collect();
+        if let Some(base) = &self.base {
+            fields.push(format!("..{}", base));
+        }
+        format!("[{}]", fields.join(", "))
+    }
+
+    /// Every field must be given at most once, and all of them must be given unless there is
+    /// a base to copy the rest from
+    fn check_fields(&self, signature: &StructSignature) -> Result<(), CompilerError> {
+        for (i, field) in self.fields.iter().enumerate() {
+            if signature.field(&field.name).is_none() {
+                return Err(CompilerError::UnknownFieldError(
+                    self.name.clone(),
+                    field.name.clone(),
+                )
+                .with_location(&field.location));
+            }
+            if self.fields[..i]
+                .iter()
+                .any(|other| other.name == field.name)
+            {
+                return Err(CompilerError::DuplicateFieldError(
+                    self.name.clone(),
+                    field.name.clone(),
+                )
+                .with_location(&field.location));
+            }
+        }
+
+        if self.base.is_some() {
+            return Ok(());
+        }
+        match signature.fields().iter().find(|declared| {
+            !self
+                .fields
+                .iter()
+                .any(|field| field.name == declared.name())
+        }) {
+            Some(missing) => Err(CompilerError::MissingFieldError(
+                self.name.clone(),
+                missing.name().to_string(),
+            )
+            .with_location(&self.location)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for StructExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Struct: {}\nFields: {}", self.name, self.fields_string())
+    }
+}
+
+impl ASTNode for StructExpression {
+    fn print(&self) {
+        println!("Node: Struct Expression");
+        println!("Struct Expression Name: {}", self.name);
+        println!("Struct Expression Fields: {}", self.fields_string());
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut parts: Vec<String> = vec![self.name.clone()];
+        parts.extend(
+            self.fields
+                .iter()
+                .map(|field| format!("{}:{}", field.name, field.value.to_s_expression())),
+        );
+        if let Some(base) = &self.base {
+            parts.push(format!("..{}", base.to_s_expression()));
+        }
+        format!("(struct {})", parts.join(" "))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for field in &self.fields {
+            field.value.check_scope(scope)?;
+        }
+        match &self.base {
+            Some(base) => base.check_scope(scope),
+            None => Ok(()),
+        }
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        for field in &self.fields {
+            field.value.check_calls(functions)?;
+        }
+        match &self.base {
+            Some(base) => base.check_calls(functions),
+            None => Ok(()),
+        }
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let signature: StructSignature = match context.struct_signature(&self.name) {
+            Some(signature) => signature.clone(),
+            None => {
+                return Err(CompilerError::UnknownTypeError(self.name.clone())
+                    .with_location(&self.location))
+            }
+        };
+        self.check_fields(&signature)?;
+
+        let struct_type: Type = Type::Named(self.name.clone());
+        if let Some(base) = &self.base {
+            let base_type: Type = base.check_types(context)?;
+            context.unify(&struct_type, &base_type, &self.location)?;
+        }
+
+        for field in &self.fields {
+            let declared: &FieldSignature = signature
+                .field(&field.name)
+                .expect("fields were checked against the signature");
+            let value_type: Type = field.value.check_types(context)?;
+            let field_type: Type =
+                context.fixed_variable(declared.field_type().clone(), declared.location());
+            context.coerce(&field_type, &value_type, &field.location)?;
+        }
+
+        Ok(struct_type)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        for field in &self.fields {
+            field.value.check_units(context)?;
+        }
+        if let Some(base) = &self.base {
+            base.check_units(context)?;
+        }
+        Ok(Measure::Unknown)
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Display;
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    types::Type,
+    warning::{CompilerWarning, WarningKind},
+};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `try body catch name => handler`, giving the value of the body unless a `?` in it hands on
+/// an error. The handler then runs with the error bound to `name`, and gives the value instead
+pub struct TryExpression {
+    body: Box<dyn ASTNode>,
+    name: String,
+    handler: Box<dyn ASTNode>,
+    location: SourceLocation,
+    handler_location: SourceLocation,
+}
+
+impl TryExpression {
+    pub fn new(
+        body: Box<dyn ASTNode>,
+        name: &str,
+        handler: Box<dyn ASTNode>,
+        location: SourceLocation,
+        handler_location: SourceLocation,
+    ) -> Self {
+        Self {
+            body,
+            name: name.to_string(),
+            handler,
+            location,
+            handler_location,
+        }
+    }
+
+    /// The type of the body, and that of the errors handed from it to the handler, if any are
+    fn check_body_types(
+        &self,
+        context: &mut TypeContext,
+    ) -> Result<(Type, Option<Type>), CompilerError> {
+        let body_type: Type = self.body.check_types(context)?;
+        Ok((body_type, context.error_type().cloned()))
+    }
+}
+
+impl Display for TryExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Try: {}\nCatch: {} => {}",
+            self.body, self.name, self.handler
+        )
+    }
+}
+
+impl ASTNode for TryExpression {
+    fn print(&self) {
+        println!("Node: Try Expression");
+        println!("Try Expression Body: {}", self.body);
+        println!("Try Expression Error Name: {}", self.name);
+        println!("Try Expression Handler: {}", self.handler);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(try {} (catch {} {}))",
+            self.body.to_s_expression(),
+            self.name,
+            self.handler.to_s_expression()
+        )
+    }
+
+    /// Like a match pattern, the name of the error is immutable and only visible in the handler
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.body.check_scope(scope)?;
+        scope.push_frame();
+        scope.declare(&self.name, Mutability::Immutable);
+        let result = self.handler.check_scope(scope);
+        scope.pop_frame();
+        result
+    }
+
+    /// The name of the error hides any function with the same name from the handler
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.body.check_calls(functions)?;
+
+        let mut visible_functions: FunctionTable = functions.clone();
+        visible_functions.remove(&self.name);
+        self.handler.check_calls(&visible_functions)
+    }
+
+    /// A body that never hands an error on leaves nothing for the handler to do, which is
+    /// pointed out without stopping the check
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        context.push_handler();
+        let result = self.check_body_types(context);
+        context.pop_handler();
+        let (body_type, error_type) = result?;
+
+        let error_type: Type = match error_type {
+            Some(error_type) => error_type,
+            None => {
+                context.record_warning(CompilerWarning::new(
+                    WarningKind::UnusedCatch,
+                    &self.location,
+                ));
+                context.fresh_variable()
+            }
+        };
+
+        context.push_frame();
+        context.declare_variable(&self.name, error_type);
+        let handler_type: Result<Type, CompilerError> = self.handler.check_types(context);
+        context.pop_frame();
+
+        let result_type: Type = context.fresh_variable();
+        context.coerce(&result_type, &body_type, &self.location)?;
+        context.coerce(&result_type, &handler_type?, &self.handler_location)?;
+        Ok(result_type)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let body: Measure = self.body.check_units(context)?;
+
+        context.push_frame();
+        context.declare(&self.name, Measure::Unknown);
+        let handler: Result<Measure, CompilerError> = self.handler.check_units(context);
+        context.pop_frame();
+
+        body.agree(&handler?).map_err(|(expected, found)| {
+            CompilerError::InconsistentUnitError(expected, found)
+                .with_location(&self.handler_location)
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `(a, b, c)`. A single element tuple is written with a trailing comma, `(a,)`, to tell it
+/// apart from a parenthesized expression
+pub struct TupleExpression {
+    elements: Vec<Box<dyn ASTNode>>,
+}
+
+impl TupleExpression {
+    pub fn new(elements: Vec<Box<dyn ASTNode>>) -> Self {
+        Self { elements }
+    }
+}
+
+impl Display for TupleExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TupleExpression: (")?;
+        for (i, element) in self.elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl ASTNode for TupleExpression {
+    fn print(&self) {
+        println!("Node: Tuple Expression");
+        for element in &self.elements {
+            println!("Tuple Expression Element: {}", element);
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        let mut parts: Vec<String> = vec![String::from("tuple")];
+        parts.extend(
+            self.elements
+                .iter()
+                .map(|element| element.to_s_expression()),
+        );
+        format!("({})", parts.join(" "))
+    }
+
+    fn to_source(&self) -> Option<String> {
+        let elements: Option<Vec<String>> = self
+            .elements
+            .iter()
+            .map(|element| element.to_source())
+            .collect();
+        Some(format!("({})", elements?.join(", ")))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for element in &self.elements {
+            element.check_scope(scope)?;
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        for element in &self.elements {
+            element.check_calls(functions)?;
+        }
+        Ok(())
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let mut element_types: Vec<Type> = Vec::with_capacity(self.elements.len());
+        for element in &self.elements {
+            element_types.push(element.check_types(context)?);
+        }
+        Ok(Type::Tuple(element_types))
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let elements: Vec<Measure> = self
+            .elements
+            .iter()
+            .map(|element| element.check_units(context))
+            .collect::<Result<Vec<Measure>, CompilerError>>()?;
+        Ok(Measure::Tuple(elements))
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        let elements: Vec<ConstantValue> = self
+            .elements
+            .iter()
+            .map(|element| element.evaluate_constant(evaluator))
+            .collect::<Result<_, _>>()?;
+        Ok(ConstantValue::Tuple(elements))
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `tuple.0`, reading one element of a tuple by its position
+pub struct TupleIndexExpression {
+    tuple: Box<dyn ASTNode>,
+    index: usize,
+    location: SourceLocation,
+}
+
+impl TupleIndexExpression {
+    pub fn new(tuple: Box<dyn ASTNode>, index: usize, location: SourceLocation) -> Self {
+        Self {
+            tuple,
+            index,
+            location,
+        }
+    }
+}
+
+impl Display for TupleIndexExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tuple: {}\nElement: {}", self.tuple, self.index)
+    }
+}
+
+impl ASTNode for TupleIndexExpression {
+    fn print(&self) {
+        println!("Node: Tuple Index Expression");
+        println!("Tuple Index Expression Tuple: {}", self.tuple);
+        println!("Tuple Index Expression Element: {}", self.index);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("(. {} {})", self.tuple.to_s_expression(), self.index)
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(format!("{}.{}", self.tuple.to_source()?, self.index))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.tuple.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.tuple.check_calls(functions)
+    }
+
+    /// The tuple's type has to be known by now, since nothing here says how many elements it
+    /// has
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let tuple_type: Type = self.tuple.check_types(context)?;
+        match context.resolve_shape(&tuple_type) {
+            Type::Tuple(elements) => match elements.get(self.index) {
+                Some(element_type) => Ok(element_type.clone()),
+                None => Err(
+                    CompilerError::TupleIndexOutOfBoundsError(self.index, elements.len())
+                        .with_location(&self.location),
+                ),
+            },
+            Type::Variable(_) => {
+                Err(CompilerError::UnknownTupleTypeError(self.index).with_location(&self.location))
+            }
+            other => Err(
+                CompilerError::NotATupleError(context.resolve_deep(&other), self.index)
+                    .with_location(&self.location),
+            ),
+        }
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        match self.tuple.check_units(context)? {
+            Measure::Tuple(mut elements) if self.index < elements.len() => {
+                Ok(elements.swap_remove(self.index))
+            }
+            _ => Ok(Measure::Unknown),
+        }
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        match self.tuple.evaluate_constant(evaluator)? {
+            ConstantValue::Tuple(mut elements) if self.index < elements.len() => {
+                Ok(elements.swap_remove(self.index))
+            }
+            ConstantValue::Tuple(elements) => Err(CompilerError::TupleIndexOutOfBoundsError(
+                self.index,
+                elements.len(),
+            )
+            .with_location(&self.location)),
+            other => Err(
+                CompilerError::NotATupleError(other.value_type(), self.index)
+                    .with_location(&self.location),
+            ),
+        }
+    }
+}
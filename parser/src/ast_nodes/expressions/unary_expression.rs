@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    token::UnaryOperator,
+    types::{Constraint, Type},
+};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{negate, Differentiator, Dual},
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+pub struct UnaryExpression {
+    operator: UnaryOperator,
+    operand: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl UnaryExpression {
+    pub fn new(
+        operator: UnaryOperator,
+        operand: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            operator,
+            operand,
+            location,
+        }
+    }
+}
+
+impl Display for UnaryExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Operator: {}\nOperand: {}",
+            self.operator.symbol(),
+            self.operand
+        )
+    }
+}
+
+impl ASTNode for UnaryExpression {
+    fn print(&self) {
+        println!("Node: Unary Expression");
+        println!("Unary Expression Operator: {}", self.operator.symbol());
+        println!("Unary Expression Operand: {}", self.operand);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "({} {})",
+            self.operator.symbol(),
+            self.operand.to_s_expression()
+        )
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(format!(
+            "({}{})",
+            self.operator.symbol(),
+            self.operand.to_source()?
+        ))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.operand.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.operand.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let operand_type: Type = self.operand.check_types(context)?;
+        match self.operator {
+            UnaryOperator::Negation
+                if context.require(Constraint::Numeric, &operand_type, &self.location)? =>
+            {
+                Ok(operand_type)
+            }
+            UnaryOperator::Not
+                if context
+                    .coerce(&Type::Bool, &operand_type, &self.location)
+                    .is_ok() =>
+            {
+                Ok(Type::Bool)
+            }
+            _ => Err(CompilerError::InvalidUnaryOperandTypeError(
+                self.operator.symbol(),
+                context.resolve_shape(&operand_type),
+            )
+            .with_location(&self.location)),
+        }
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        let operand: Measure = self.operand.check_units(context)?;
+        match self.operator {
+            UnaryOperator::Negation => Ok(operand),
+            UnaryOperator::Not => Ok(Measure::Unknown),
+        }
+    }
+
+    fn constant_integer(&self) -> Option<i64> {
+        match self.operator {
+            UnaryOperator::Negation => self.operand.constant_integer().map(|value| -value),
+            UnaryOperator::Not => None,
+        }
+    }
+
+    fn constant_boolean(&self) -> Option<bool> {
+        match self.operator {
+            UnaryOperator::Not => self.operand.constant_boolean().map(|value| !value),
+            UnaryOperator::Negation => None,
+        }
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        let operand: ConstantValue = self.operand.evaluate_constant(evaluator)?;
+        ConstantValue::unary(&self.operator, operand).map_err(|e| e.with_location(&self.location))
+    }
+
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        let (bindings, operand, tangent) = self.operand.differentiate(differentiator)?.into_parts();
+        let value: Box<dyn ASTNode> = Box::new(UnaryExpression::new(
+            self.operator.clone(),
+            operand,
+            self.location.clone(),
+        ));
+        let tangent: Option<Box<dyn ASTNode>> = match self.operator {
+            UnaryOperator::Negation => tangent.map(negate),
+            UnaryOperator::Not => None,
+        };
+        Ok(Dual::new(value, tangent).after(bindings))
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    differentiator::{Differentiator, Dual},
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// What a var binding introduces: a single name, or a tuple pattern such as `(q, r)` taking
+/// its initializer apart element by element
+pub enum BindingPattern {
+    Name(String),
+    Tuple(Vec<BindingPattern>),
+}
+
+impl BindingPattern {
+    /// Every name the pattern binds, from left to right
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            BindingPattern::Name(name) => vec![name.as_str()],
+            BindingPattern::Tuple(elements) => elements
+                .iter()
+                .flat_map(|element| element.names())
+                .collect(),
+        }
+    }
+
+    pub fn declare(&self, scope: &mut Scope, mutability: Mutability) {
+        for name in self.names() {
+            scope.declare(name, mutability);
+        }
+    }
+
+    /// Match `binding_type` against the shape of the pattern and declare every name it binds
+    pub fn check_types(
+        &self,
+        binding_type: Type,
+        context: &mut TypeContext,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        match self {
+            BindingPattern::Name(name) => {
+                context.declare_variable(name, binding_type);
+                Ok(())
+            }
+            BindingPattern::Tuple(elements) => {
+                let element_types: Vec<Type> =
+                    elements.iter().map(|_| context.fresh_variable()).collect();
+                context.unify(&Type::Tuple(element_types.clone()), &binding_type, location)?;
+                for (element, element_type) in elements.iter().zip(element_types) {
+                    element.check_types(element_type, context, location)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl BindingPattern {
+    /// Declare every name the pattern binds with what is known of its unit, taken apart from
+    /// `measure` along with the value
+    pub fn declare_units(&self, measure: Measure, context: &mut UnitContext) {
+        match (self, measure) {
+            (BindingPattern::Name(name), measure) => context.declare(name, measure),
+            (BindingPattern::Tuple(elements), Measure::Tuple(measures))
+                if elements.len() == measures.len() =>
+            {
+                for (element, measure) in elements.iter().zip(measures) {
+                    element.declare_units(measure, context);
+                }
+            }
+            (BindingPattern::Tuple(elements), _) => {
+                for element in elements {
+                    element.declare_units(Measure::Unknown, context);
+                }
+            }
+        }
+    }
+}
+
+impl Display for BindingPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingPattern::Name(name) => write!(f, "{}", name),
+            BindingPattern::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// A single `name: type = initializer` binding of a var expression. Without an annotation the
+/// binding takes the type of its initializer, and bindings without either are an f64 starting
+/// out as 0.0
+pub struct VarBinding {
+    pattern: BindingPattern,
+    annotation: Option<Type>,
+    initializer: Option<Box<dyn ASTNode>>,
+    location: SourceLocation,
+}
+
+impl VarBinding {
+    pub fn new(
+        pattern: BindingPattern,
+        annotation: Option<Type>,
+        initializer: Option<Box<dyn ASTNode>>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            pattern,
+            annotation,
+            initializer,
+            location,
+        }
+    }
+
+    fn annotated_name(&self) -> String {
+        match &self.annotation {
+            Some(annotation) => format!("{}: {}", self.pattern, annotation),
+            None => self.pattern.to_string(),
+        }
+    }
+
+    /// Check the initializer against the bindings visible so far, then bring this binding into
+    /// the innermost frame
+    pub fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        if let Some(initializer) = &self.initializer {
+            initializer.check_scope(scope)?;
+        }
+        self.pattern.declare(scope, Mutability::Mutable);
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.pattern.names()
+    }
+
+    pub fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        match &self.initializer {
+            Some(initializer) => initializer.check_calls(functions),
+            None => Ok(()),
+        }
+    }
+
+    /// Work out the binding's type, then declare it in the innermost frame
+    pub fn check_types(&self, context: &mut TypeContext) -> Result<(), CompilerError> {
+        let initializer_type: Option<Type> = match &self.initializer {
+            Some(initializer) => Some(initializer.check_types(context)?),
+            None => None,
+        };
+
+        let binding_type: Type = match &self.annotation {
+            Some(annotation) => context.fixed_variable(annotation.clone(), &self.location),
+            None => context.fresh_variable(),
+        };
+        if let Some(found) = initializer_type {
+            context.coerce(&binding_type, &found, &self.location)?;
+        }
+
+        self.pattern
+            .check_types(binding_type, context, &self.location)
+    }
+
+    /// Work out the unit of the initializer, then declare the binding in the innermost frame.
+    /// A binding without an initializer can hold a value in any unit
+    pub fn check_units(&self, context: &mut UnitContext) -> Result<(), CompilerError> {
+        let measure: Measure = match &self.initializer {
+            Some(initializer) => initializer.check_units(context)?,
+            None => Measure::Unknown,
+        };
+        self.pattern.declare_units(measure, context);
+        Ok(())
+    }
+
+    /// Declare the binding to `differentiator`, appending what its value and tangent need to
+    /// `bindings`. Only bindings of a single name can be differentiated
+    pub fn differentiate(
+        &self,
+        differentiator: &mut Differentiator,
+        bindings: &mut Vec<VarBinding>,
+    ) -> Result<(), CompilerError> {
+        let name: &str = match &self.pattern {
+            BindingPattern::Name(name) => name,
+            BindingPattern::Tuple(_) => {
+                return Err(
+                    CompilerError::NonDifferentiableError(self.to_s_expression())
+                        .with_location(&self.location),
+                )
+            }
+        };
+        let dual: Option<Dual> = match &self.initializer {
+            Some(initializer) => Some(initializer.differentiate(differentiator)?),
+            None => None,
+        };
+        differentiator.declare(name, self.annotation.clone(), dual, bindings);
+        Ok(())
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        let name: String = self.annotated_name().replace(' ', "");
+        match &self.initializer {
+            Some(initializer) => format!("({} {})", name, initializer.to_s_expression()),
+            None => format!("({})", name),
+        }
+    }
+
+    pub fn to_source(&self) -> Option<String> {
+        match &self.initializer {
+            Some(initializer) => Some(format!(
+                "{} = {}",
+                self.annotated_name(),
+                initializer.to_source()?
+            )),
+            None => Some(self.annotated_name()),
+        }
+    }
+}
+
+impl Display for VarBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.initializer {
+            Some(initializer) => write!(f, "{} = {}", self.annotated_name(), initializer),
+            None => write!(f, "{}", self.annotated_name()),
+        }
+    }
+}
+
+/// `var x = init, y in body`. The bindings are mutable and only visible inside the body. Each
+/// initializer can see the bindings declared before it
+pub struct VarExpression {
+    bindings: Vec<VarBinding>,
+    body: Box<dyn ASTNode>,
+}
+
+impl VarExpression {
+    pub fn new(bindings: Vec<VarBinding>, body: Box<dyn ASTNode>) -> Self {
+        Self { bindings, body }
+    }
+
+    fn check_bindings_and_body(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for binding in &self.bindings {
+            binding.check_scope(scope)?;
+        }
+        self.body.check_scope(scope)
+    }
+
+    fn check_binding_and_body_types(
+        &self,
+        context: &mut TypeContext,
+    ) -> Result<Type, CompilerError> {
+        for binding in &self.bindings {
+            binding.check_types(context)?;
+        }
+        self.body.check_types(context)
+    }
+
+    fn differentiate_bindings_and_body(
+        &self,
+        differentiator: &mut Differentiator,
+    ) -> Result<Dual, CompilerError> {
+        let mut bindings: Vec<VarBinding> = Vec::new();
+        for binding in &self.bindings {
+            binding.differentiate(differentiator, &mut bindings)?;
+        }
+        Ok(self.body.differentiate(differentiator)?.after(bindings))
+    }
+
+    fn check_binding_and_body_units(
+        &self,
+        context: &mut UnitContext,
+    ) -> Result<Measure, CompilerError> {
+        for binding in &self.bindings {
+            binding.check_units(context)?;
+        }
+        self.body.check_units(context)
+    }
+}
+
+impl Display for VarExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bindings_string: String = String::new();
+        for binding in &self.bindings {
+            bindings_string = format!("{} {}", bindings_string, binding);
+        }
+
+        write!(
+            f,
+            "VarExpression bindings: {}\nVarExpression body: {}",
+            bindings_string, self.body
+        )
+    }
+}
+
+impl ASTNode for VarExpression {
+    fn print(&self) {
+        println!("Node: VarExpression");
+        for binding in &self.bindings {
+            println!("VarExpression binding: {}", binding);
+        }
+        println!("VarExpression body: {}", self.body);
+    }
+
+    fn to_s_expression(&self) -> String {
+        let bindings: Vec<String> = self
+            .bindings
+            .iter()
+            .map(|binding| binding.to_s_expression())
+            .collect();
+
+        format!(
+            "(var ({}) {})",
+            bindings.join(" "),
+            self.body.to_s_expression()
+        )
+    }
+
+    /// Parenthesized, as the body of a var expression reaches as far right as it can
+    fn to_source(&self) -> Option<String> {
+        let bindings: Option<Vec<String>> = self
+            .bindings
+            .iter()
+            .map(|binding| binding.to_source())
+            .collect();
+        Some(format!(
+            "(var {} in {})",
+            bindings?.join(", "),
+            self.body.to_source()?
+        ))
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        scope.push_frame();
+        let result = self.check_bindings_and_body(scope);
+        scope.pop_frame();
+        result
+    }
+
+    /// Each binding hides any function with the same name from the bindings after it and the
+    /// body
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        let mut visible_functions: FunctionTable = functions.clone();
+        for binding in &self.bindings {
+            binding.check_calls(&visible_functions)?;
+            for name in binding.names() {
+                visible_functions.remove(name);
+            }
+        }
+        self.body.check_calls(&visible_functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        context.push_frame();
+        let result = self.check_binding_and_body_types(context);
+        context.pop_frame();
+        result
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        context.push_frame();
+        let result = self.check_binding_and_body_units(context);
+        context.pop_frame();
+        result
+    }
+
+    /// The bindings are renamed apart, so they can be hoisted out along with the body's
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        differentiator.push_frame();
+        let result = self.differentiate_bindings_and_body(differentiator);
+        differentiator.pop_frame();
+        result
+    }
+}
@@ -1,15 +1,41 @@
 use std::fmt::Display;
 
-use crate::ast_node::ASTNode;
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, Dual},
+    function_signature::FunctionSignature,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
 
 pub struct VariableExpression {
     name: String,
+    location: SourceLocation,
+    /// Whether the name refers to what it means at the top level of the module, which no
+    /// local variable can hide, as the names a macro template does not bind do
+    top_level: bool,
 }
 
 impl VariableExpression {
-    pub fn new(name: &str) -> Self {
+    pub fn new(name: &str, location: SourceLocation) -> Self {
         Self {
             name: name.to_string(),
+            location,
+            top_level: false,
+        }
+    }
+
+    /// A reference to the function, constant or variant `name` of the module, even where a
+    /// local variable of the same name is in scope
+    pub fn top_level(name: &str, location: SourceLocation) -> Self {
+        Self {
+            top_level: true,
+            ..Self::new(name, location)
         }
     }
 }
@@ -25,4 +51,89 @@ impl ASTNode for VariableExpression {
         println!("Node: VariableExpression");
         println!("VariableExpression name: {}", self.name);
     }
+
+    fn to_s_expression(&self) -> String {
+        self.name.clone()
+    }
+
+    fn to_source(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        let result: Result<(), CompilerError> = match self.top_level {
+            true => scope.check_top_level_read(&self.name),
+            false => scope.check_read(&self.name),
+        };
+        if let Err(e) = result {
+            scope.record_error(e.with_location(&self.location));
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, _functions: &FunctionTable) -> Result<(), CompilerError> {
+        Ok(())
+    }
+
+    /// A name that is not a variable refers to a function, used as a value
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        if self.top_level {
+            if let Some(constant_type) = context.constant_type(&self.name) {
+                return Ok(constant_type);
+            }
+        } else if context.is_variable(&self.name) {
+            return context.variable_type(&self.name);
+        }
+        // a variant carrying no values is a value of its enum rather than a constructor
+        if let Some(variant) = context.declared_variant(&self.name) {
+            if variant.fields().is_empty() {
+                return Ok(Type::Named(variant.enum_name().to_string()));
+            }
+        }
+        let signature: FunctionSignature = context.function(&self.name)?;
+        context.record_reference(&signature);
+        Ok(signature.function_type())
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        match self.top_level {
+            true => Ok(context.lookup_top_level(&self.name)),
+            false => Ok(context.lookup(&self.name)),
+        }
+    }
+
+    fn variable_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn is_top_level_name(&self) -> bool {
+        self.top_level
+    }
+
+    fn evaluate_constant(
+        &self,
+        evaluator: &mut ConstantEvaluator,
+    ) -> Result<ConstantValue, CompilerError> {
+        if self.top_level && evaluator.is_module_constant(&self.name) {
+            return evaluator.module_constant(&self.name);
+        }
+        if !self.top_level && evaluator.is_constant(&self.name) {
+            return evaluator.constant(&self.name);
+        }
+        Err(CompilerError::NonConstantExpressionError(
+            evaluator.current().to_string(),
+            self.name.clone(),
+        ))
+    }
+
+    /// A top level name never refers to the variable derivatives are taken with respect to
+    fn differentiate(&self, differentiator: &mut Differentiator) -> Result<Dual, CompilerError> {
+        if self.top_level {
+            return Ok(Dual::constant(Box::new(Self::top_level(
+                &self.name,
+                self.location.clone(),
+            ))));
+        }
+        Ok(differentiator.variable(&self.name))
+    }
 }
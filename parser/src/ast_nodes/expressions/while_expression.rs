@@ -0,0 +1,88 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `while condition do body`, running the body for as long as the condition holds. The loop
+/// gives unit, except when its condition is always true, e.g. `while true`. It can then only
+/// end through a `break` and gives the value it breaks with
+pub struct WhileExpression {
+    condition: Box<dyn ASTNode>,
+    body: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl WhileExpression {
+    pub fn new(
+        condition: Box<dyn ASTNode>,
+        body: Box<dyn ASTNode>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            condition,
+            body,
+            location,
+        }
+    }
+}
+
+impl Display for WhileExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "While: {}\nDo: {}", self.condition, self.body)
+    }
+}
+
+impl ASTNode for WhileExpression {
+    fn print(&self) {
+        println!("Node: While Expression");
+        println!("While Expression Condition: {}", self.condition);
+        println!("While Expression Body: {}", self.body);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(while {} {})",
+            self.condition.to_s_expression(),
+            self.body.to_s_expression()
+        )
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.condition.check_scope(scope)?;
+        self.body.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.condition.check_calls(functions)?;
+        self.body.check_calls(functions)
+    }
+
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let condition_type: Type = self.condition.check_types(context)?;
+        context.unify(&Type::Bool, &condition_type, &self.location)?;
+
+        let value_type: Type = context.fresh_variable();
+        context.push_loop(value_type.clone());
+        let result: Result<Type, CompilerError> = self.body.check_types(context);
+        context.pop_loop();
+        result?;
+
+        if self.condition.constant_boolean() != Some(true) {
+            context.unify(&Type::Unit, &value_type, &self.location)?;
+        }
+        Ok(context.resolve(&value_type))
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.condition.check_units(context)?;
+        self.body.check_units(context)?;
+        Ok(Measure::Unknown)
+    }
+}
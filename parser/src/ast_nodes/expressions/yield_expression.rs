@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// `yield value`, handing a value to the loop going over the generator and suspending the body
+/// until the loop asks for the next one. A function whose body yields is a generator
+pub struct YieldExpression {
+    value: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl YieldExpression {
+    pub fn new(value: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self { value, location }
+    }
+}
+
+impl Display for YieldExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Yield: {}", self.value)
+    }
+}
+
+impl ASTNode for YieldExpression {
+    fn print(&self) {
+        println!("Node: Yield Expression");
+        println!("Yield Expression Value: {}", self.value);
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!("(yield {})", self.value.to_s_expression())
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        self.value.check_scope(scope)
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.value.check_calls(functions)
+    }
+
+    /// The body carries on once the next value is asked for, with nothing handed back to it
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let value_type: Type = self.value.check_types(context)?;
+        context.record_yield(&value_type, &self.location)?;
+        Ok(Type::Unit)
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        self.value.check_units(context)?;
+        Ok(Measure::Unknown)
+    }
+}
@@ -0,0 +1,150 @@
+use common::{error::CompilerError, source_location::SourceLocation};
+
+/// Every attribute the compiler knows of. An attribute with any other name is an error
+pub const ATTRIBUTES: [AttributeKind; 5] = [
+    AttributeKind::Inline,
+    AttributeKind::Memo,
+    AttributeKind::Pure,
+    AttributeKind::Deprecated,
+    AttributeKind::Checked,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeKind {
+    /// Asks for the body to be inlined at every call. Only a hint, recursive functions are
+    /// still called
+    Inline,
+    /// Asks for the result of every call to be cached by its arguments, which only gives the
+    /// same program for functions without side effects
+    Memo,
+    /// States the function has no side effects. Trusted for externs, checked for definitions
+    Pure,
+    /// Warns at every call, with an optional message such as what to call instead
+    Deprecated,
+    /// Turns the NaN an extern returns for an input it cannot handle into an error, so the
+    /// extern returns a `result[f64, str]` rather than an f64
+    Checked,
+}
+
+impl AttributeKind {
+    /// The attribute called `name`, if there is one
+    pub fn from_name(name: &str) -> Option<Self> {
+        ATTRIBUTES.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeKind::Inline => "inline",
+            AttributeKind::Memo => "memo",
+            AttributeKind::Pure => "pure",
+            AttributeKind::Deprecated => "deprecated",
+            AttributeKind::Checked => "checked",
+        }
+    }
+
+    /// Whether the attribute can be given a message, as in `@deprecated("use g")`
+    fn takes_message(&self) -> bool {
+        *self == AttributeKind::Deprecated
+    }
+
+    /// Whether the attribute means anything without a body, as on an extern
+    fn applies_to_externs(&self) -> bool {
+        matches!(
+            self,
+            AttributeKind::Pure | AttributeKind::Deprecated | AttributeKind::Checked
+        )
+    }
+
+    /// Whether the attribute means anything on a definition, whose body can return a result
+    /// itself
+    fn applies_to_definitions(&self) -> bool {
+        *self != AttributeKind::Checked
+    }
+
+    /// Whether the function it is given to must not have side effects
+    pub fn requires_purity(&self) -> bool {
+        matches!(self, AttributeKind::Memo | AttributeKind::Pure)
+    }
+
+    /// The attribute this one cannot be combined with. A memoized call looks its result up
+    /// instead of running the body, so there is no body left to inline
+    fn conflicts_with(&self) -> Option<AttributeKind> {
+        match self {
+            AttributeKind::Inline => Some(AttributeKind::Memo),
+            AttributeKind::Memo => Some(AttributeKind::Inline),
+            _ => None,
+        }
+    }
+}
+
+/// `@name` or `@name("message")` before a definition or an extern
+#[derive(Clone)]
+pub struct Attribute {
+    kind: AttributeKind,
+    message: Option<String>,
+    location: SourceLocation,
+}
+
+impl Attribute {
+    pub fn new(kind: AttributeKind, message: Option<String>, location: SourceLocation) -> Self {
+        Self {
+            kind,
+            message,
+            location,
+        }
+    }
+
+    pub fn kind(&self) -> AttributeKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// Check the attributes given to one function against each other and against what they
+    /// are given to, returning every error found
+    pub fn validate(attributes: &[Attribute], is_extern: bool) -> Vec<CompilerError> {
+        let mut errors: Vec<CompilerError> = Vec::new();
+        for (index, attribute) in attributes.iter().enumerate() {
+            let name: String = attribute.kind.name().to_string();
+            let error: Option<CompilerError> = if attribute.message.is_some()
+                && !attribute.kind.takes_message()
+            {
+                Some(CompilerError::AttributeArgumentError(name))
+            } else if is_extern && !attribute.kind.applies_to_externs() {
+                Some(CompilerError::AttributeTargetError(name, "an extern"))
+            } else if !is_extern && !attribute.kind.applies_to_definitions() {
+                Some(CompilerError::AttributeTargetError(name, "a definition"))
+            } else if attributes[..index]
+                .iter()
+                .any(|earlier| earlier.kind == attribute.kind)
+            {
+                Some(CompilerError::DuplicateAttributeError(name))
+            } else {
+                attribute
+                    .kind
+                    .conflicts_with()
+                    .filter(|other| attributes[..index].iter().any(|a| a.kind == *other))
+                    .map(|other| {
+                        CompilerError::ConflictingAttributesError(name, other.name().to_string())
+                    })
+            };
+            if let Some(error) = error {
+                errors.push(error.with_location(&attribute.location));
+            }
+        }
+        errors
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        match &self.message {
+            Some(message) => format!("@{}({:?})", self.kind.name(), message),
+            None => format!("@{}", self.kind.name()),
+        }
+    }
+}
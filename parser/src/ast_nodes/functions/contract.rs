@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// The name the return value goes by in a postcondition
+pub const RESULT_NAME: &str = "result";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractKind {
+    /// Has to hold of the arguments of every call
+    Requires,
+    /// Has to hold of the value every call returns, which the condition calls `result`
+    Ensures,
+}
+
+impl ContractKind {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ContractKind::Requires => "requires",
+            ContractKind::Ensures => "ensures",
+        }
+    }
+}
+
+/// `requires condition` or `ensures condition` after the parameters of a function, stating
+/// what it expects of its callers or promises them in return
+pub struct Contract {
+    kind: ContractKind,
+    condition: Box<dyn ASTNode>,
+    location: SourceLocation,
+}
+
+impl Contract {
+    pub fn new(kind: ContractKind, condition: Box<dyn ASTNode>, location: SourceLocation) -> Self {
+        Self {
+            kind,
+            condition,
+            location,
+        }
+    }
+
+    pub fn kind(&self) -> ContractKind {
+        self.kind
+    }
+
+    pub fn condition(&self) -> &dyn ASTNode {
+        self.condition.as_ref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        format!(
+            "({} {})",
+            self.kind.keyword(),
+            self.condition.to_s_expression()
+        )
+    }
+
+    /// The parameters have to be declared already. A postcondition also sees `result`
+    pub fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        scope.push_frame();
+        if self.kind == ContractKind::Ensures {
+            scope.declare(RESULT_NAME, Mutability::Immutable);
+        }
+        let result = self.condition.check_scope(scope);
+        scope.pop_frame();
+        result.map_err(|e| e.with_location(&self.location))
+    }
+
+    pub fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.condition.check_calls(functions)
+    }
+
+    /// The condition has to be a bool. The parameters have to be declared already, and a
+    /// postcondition sees `result` with the type `return_type`
+    pub fn check_types(
+        &self,
+        return_type: &Type,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        context.push_frame();
+        if self.kind == ContractKind::Ensures {
+            context.declare_variable(RESULT_NAME, return_type.clone());
+        }
+        let result = self
+            .condition
+            .check_types(context)
+            .and_then(|condition_type| {
+                context.coerce(&Type::Bool, &condition_type, &self.location)
+            });
+        context.pop_frame();
+        result.map_err(|e| e.with_location(&self.location))
+    }
+
+    /// The condition has to use units consistently. Nothing is known of the unit of `result`
+    pub fn check_units(&self, context: &mut UnitContext) -> Result<(), CompilerError> {
+        context.push_frame();
+        if self.kind == ContractKind::Ensures {
+            context.declare(RESULT_NAME, Measure::Unknown);
+        }
+        let result = self.condition.check_units(context);
+        context.pop_frame();
+        result
+            .map(|_| ())
+            .map_err(|e| e.with_location(&self.location))
+    }
+}
+
+impl Display for Contract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.kind.keyword(), self.condition)
+    }
+}
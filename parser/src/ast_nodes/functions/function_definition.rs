@@ -1,17 +1,126 @@
-use std::fmt::Display;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
-use crate::ast_node::ASTNode;
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
 
-use super::function_prototype::FunctionPrototype;
+use crate::{
+    ast_node::ASTNode,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    function_signature::FunctionSignature,
+    module::FunctionTable,
+    scope::Scope,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+use super::{
+    contract::{ContractKind, RESULT_NAME},
+    function_prototype::FunctionPrototype,
+};
 
 pub struct Function {
     prototype: Box<FunctionPrototype>,
     body: Box<dyn ASTNode>,
+    body_location: SourceLocation,
+    /// Whether modules importing this one can call the function
+    public: bool,
 }
 
 impl Function {
-    pub fn new(prototype: Box<FunctionPrototype>, body: Box<dyn ASTNode>) -> Self {
-        Self { prototype, body }
+    pub fn new(
+        prototype: Box<FunctionPrototype>,
+        body: Box<dyn ASTNode>,
+        body_location: SourceLocation,
+    ) -> Self {
+        Self {
+            prototype,
+            body,
+            body_location,
+            public: false,
+        }
+    }
+
+    /// The same function, made visible to the modules importing the one it is defined in
+    pub fn into_public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.public
+    }
+
+    pub fn prototype(&self) -> &FunctionPrototype {
+        self.prototype.as_ref()
+    }
+
+    pub fn body(&self) -> &dyn ASTNode {
+        self.body.as_ref()
+    }
+
+    /// The definition as Fragment source, if its body can be written out. Names made up by
+    /// macro expansion and the differentiator carry a `#`, which the lexer does not accept, so
+    /// they are written with a `_` instead
+    pub fn to_source(&self) -> Option<String> {
+        let source: String = format!("{} {}", self.prototype.to_source()?, self.body.to_source()?);
+        Some(spell_generated_names(&source))
+    }
+
+    /// A body that yields makes the function a generator. It then returns a generator of the
+    /// values it yields, and its body is only run for what it yields, not for its value. A body
+    /// handing errors on with `?` makes it return a result
+    fn check_body_type(
+        &self,
+        signature: &FunctionSignature,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        self.prototype.check_parameter_types(signature, context)?;
+        self.prototype.check_contract_types(signature, context)?;
+        let body_type: Type = self.body.check_types(context)?;
+        match context.yield_type().cloned() {
+            Some(yield_type) => {
+                context.unify(&Type::Unit, &body_type, &self.body_location)?;
+                let generator: Type = Type::Generator(Box::new(yield_type));
+                context.unify(signature.return_type(), &generator, &self.body_location)?
+            }
+            None => context.coerce(signature.return_type(), &body_type, &self.body_location)?,
+        }
+        self.check_postconditions(context);
+        context.check_returned_errors(signature.return_type(), &self.body_location)
+    }
+
+    /// Report a postcondition broken by a body that always gives the same value, known at
+    /// compile time. Any other body is left to be checked when the program runs
+    fn check_postconditions(&self, context: &mut TypeContext) {
+        let mut evaluator: ConstantEvaluator =
+            ConstantEvaluator::new(&[]).with_locals(context.constant_values());
+        let Ok(value) = self.body.evaluate_constant(&mut evaluator) else {
+            return;
+        };
+
+        let mut evaluator: ConstantEvaluator = ConstantEvaluator::new(&[])
+            .with_locals(HashMap::from([(RESULT_NAME.to_string(), value)]));
+        let postconditions = self
+            .prototype
+            .contracts()
+            .iter()
+            .filter(|contract| contract.kind() == ContractKind::Ensures);
+        for contract in postconditions {
+            if let Ok(ConstantValue::Bool(false)) =
+                contract.condition().evaluate_constant(&mut evaluator)
+            {
+                context.record_error(
+                    CompilerError::PostconditionViolationError(
+                        self.prototype.name().to_string(),
+                        contract.to_s_expression(),
+                        contract.location().clone(),
+                    )
+                    .with_location(&self.body_location),
+                );
+            }
+        }
     }
 }
 
@@ -31,4 +140,104 @@ impl ASTNode for Function {
         println!("Prototype: {}", self.prototype);
         println!("body: {}", self.body);
     }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(def {} {} {})",
+            self.prototype.generic_name(),
+            self.prototype.signature_s_expression(),
+            self.body.to_s_expression()
+        )
+    }
+
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        scope.push_frame();
+        let result = self
+            .prototype
+            .check_scope(scope)
+            .and_then(|_| self.body.check_scope(scope));
+        scope.pop_frame();
+        result
+    }
+
+    /// Parameters hide any function with the same name from the body
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        self.prototype.check_calls(functions)?;
+
+        let mut visible_functions: FunctionTable = functions.clone();
+        for arg in self.prototype.args() {
+            visible_functions.remove(arg.name());
+        }
+        self.body.check_calls(&visible_functions)
+    }
+
+    /// Infers the signature of the function and declares it in the innermost frame, generalized
+    /// over whatever the body leaves undetermined. The type of the node is its return type
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let signature: FunctionSignature = self.prototype.fresh_signature(context);
+        context.begin_definition(signature.clone());
+
+        context.push_frame();
+        context.push_handler();
+        let result = self.check_body_type(&signature, context);
+        context.pop_handler();
+        context.pop_frame();
+
+        context.end_definition();
+        result.map(|_| context.resolve(signature.return_type()))
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        context.push_frame();
+        let result = self
+            .prototype
+            .check_units(context)
+            .and_then(|_| self.body.check_units(context));
+        context.pop_frame();
+        result
+    }
+}
+
+/// `source` with every `name#n` replaced by `name_n`, or by that followed by as many `_` as it
+/// takes not to clash with a name already in `source`
+fn spell_generated_names(source: &str) -> String {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '#';
+    let words: Vec<&str> = source
+        .split(|c: char| !is_name_char(c))
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut taken: HashSet<String> = words
+        .iter()
+        .filter(|word| !word.contains('#'))
+        .map(|word| word.to_string())
+        .collect();
+    let mut spellings: HashMap<&str, String> = HashMap::new();
+    for word in words.into_iter().filter(|word| word.contains('#')) {
+        if spellings.contains_key(word) {
+            continue;
+        }
+        let mut spelling: String = word.replace('#', "_");
+        while taken.contains(&spelling) {
+            spelling.push('_');
+        }
+        taken.insert(spelling.clone());
+        spellings.insert(word, spelling);
+    }
+
+    let mut spelled: String = String::new();
+    let mut word: String = String::new();
+    for c in source.chars().chain(std::iter::once(' ')) {
+        if is_name_char(c) {
+            word.push(c);
+            continue;
+        }
+        match spellings.get(word.as_str()) {
+            Some(spelling) => spelled.push_str(spelling),
+            None => spelled.push_str(&word),
+        }
+        word.clear();
+        spelled.push(c);
+    }
+    spelled.pop();
+    spelled
 }
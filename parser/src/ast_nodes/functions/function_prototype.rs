@@ -1,26 +1,358 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
-use crate::ast_node::ASTNode;
+use common::{error::CompilerError, source_location::SourceLocation, types::Type};
+
+use crate::{
+    ast_node::ASTNode,
+    function_signature::{FunctionSignature, ParameterSignature, TypeParameter},
+    module::FunctionTable,
+    scope::{Mutability, Scope},
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+use super::{
+    attribute::{Attribute, AttributeKind},
+    contract::{Contract, ContractKind},
+};
+
+/// A named parameter, optionally with a type annotation and the value it takes when a call
+/// leaves it out
+pub struct Parameter {
+    name: String,
+    annotation: Option<Type>,
+    default: Option<Box<dyn ASTNode>>,
+    location: SourceLocation,
+}
+
+impl Parameter {
+    pub fn new(
+        name: &str,
+        annotation: Option<Type>,
+        default: Option<Box<dyn ASTNode>>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            annotation,
+            default,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn annotation(&self) -> Option<&Type> {
+        self.annotation.as_ref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    pub fn default(&self) -> Option<&dyn ASTNode> {
+        self.default.as_deref()
+    }
+
+    fn annotated_name(&self) -> String {
+        match &self.annotation {
+            Some(annotation) => format!("{}: {}", self.name, annotation),
+            None => self.name.clone(),
+        }
+    }
+
+    fn to_s_expression(&self) -> String {
+        let name: String = self.annotated_name().replace(' ', "");
+        match &self.default {
+            Some(default) => format!("({} {})", name, default.to_s_expression()),
+            None => name,
+        }
+    }
+
+    fn to_source(&self) -> Option<String> {
+        match &self.default {
+            Some(default) => Some(format!(
+                "{} = {}",
+                self.annotated_name(),
+                default.to_source()?
+            )),
+            None => Some(self.annotated_name()),
+        }
+    }
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.default {
+            Some(default) => write!(f, "{} = {}", self.annotated_name(), default),
+            None => write!(f, "{}", self.annotated_name()),
+        }
+    }
+}
 
 pub struct FunctionPrototype {
     name: String,
-    args: Vec<String>,
+    /// The names declared in `<T, U>` after the function name
+    type_parameters: Vec<String>,
+    args: Vec<Parameter>,
+    return_type: Option<Type>,
+    /// The `requires` and `ensures` clauses after the parameters, in the order they were written
+    contracts: Vec<Rc<Contract>>,
+    attributes: Vec<Attribute>,
+    location: SourceLocation,
 }
 
 impl FunctionPrototype {
-    pub fn new(name: &str, args: Vec<String>) -> Self {
+    pub fn new(
+        name: &str,
+        args: Vec<Parameter>,
+        return_type: Option<Type>,
+        location: SourceLocation,
+    ) -> Self {
         Self {
             name: name.to_string(),
+            type_parameters: Vec::new(),
             args,
+            return_type,
+            contracts: Vec::new(),
+            attributes: Vec::new(),
+            location,
+        }
+    }
+
+    /// The same prototype, generic over the type parameters named in `type_parameters`
+    pub fn with_type_parameters(mut self, type_parameters: Vec<String>) -> Self {
+        self.type_parameters = type_parameters;
+        self
+    }
+
+    /// The same prototype, with `requires` and `ensures` clauses
+    pub fn with_contracts(mut self, contracts: Vec<Contract>) -> Self {
+        self.contracts = contracts.into_iter().map(Rc::new).collect();
+        self
+    }
+
+    /// The same prototype, with attributes such as `@memo`
+    pub fn with_attributes(mut self, attributes: Vec<Attribute>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn type_parameters(&self) -> &[String] {
+        &self.type_parameters
+    }
+
+    pub fn args(&self) -> &[Parameter] {
+        &self.args
+    }
+
+    pub fn contracts(&self) -> &[Rc<Contract>] {
+        &self.contracts
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The declared return type, f64 when there is no annotation
+    pub fn return_type(&self) -> Type {
+        self.return_type.clone().unwrap_or(Type::F64)
+    }
+
+    /// What a call gives. A @checked extern gives the error of a NaN result instead, which says
+    /// which function could not handle its arguments
+    fn called_return_type(&self) -> Type {
+        match self.attribute(AttributeKind::Checked) {
+            Some(_) => Type::Result(Box::new(self.return_type()), Box::new(Type::Str)),
+            None => self.return_type(),
+        }
+    }
+
+    pub fn attribute(&self, kind: AttributeKind) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.kind() == kind)
+    }
+
+    /// The signature with unannotated types taken as f64, as they are for externs. Declared type
+    /// parameters become parameters of the signature, instantiated afresh at every call
+    pub fn signature(&self) -> FunctionSignature {
+        let parameter_types: Vec<Type> = self
+            .args
+            .iter()
+            .map(|arg| arg.annotation.clone().unwrap_or(Type::F64))
+            .collect();
+        self.signature_with_types(parameter_types, self.called_return_type())
+            .map_types(|t, _| {
+                t.map_variables(&mut |t| match t {
+                    Type::Generic(name) => self
+                        .type_parameters
+                        .iter()
+                        .position(|declared| declared == name)
+                        .map(Type::Parameter),
+                    _ => None,
+                })
+            })
+            .with_type_parameters(self.declared_type_parameters())
+            .with_preconditions(self.preconditions())
+            .with_attributes(self.attributes.clone())
+    }
+
+    fn declared_type_parameters(&self) -> Vec<TypeParameter> {
+        self.type_parameters
+            .iter()
+            .map(|name| TypeParameter::named(name))
+            .collect()
+    }
+
+    /// The signature a function body is inferred against. Annotated types are fixed up front,
+    /// everything else starts out as a fresh type variable. Declared type parameters stay
+    /// `Generic` until the signature is generalized, so the body cannot assume anything of them
+    pub fn fresh_signature(&self, context: &mut TypeContext) -> FunctionSignature {
+        let parameter_types: Vec<Type> = self
+            .args
+            .iter()
+            .map(|arg| match &arg.annotation {
+                Some(annotation) => context.fixed_variable(annotation.clone(), &arg.location),
+                None => context.fresh_variable(),
+            })
+            .collect();
+        let return_type: Type = match &self.return_type {
+            Some(return_type) => context.fixed_variable(return_type.clone(), &self.location),
+            None => context.fresh_variable(),
+        };
+        self.signature_with_types(parameter_types, return_type)
+            .with_type_parameters(self.declared_type_parameters())
+            .with_preconditions(self.preconditions())
+            .with_attributes(self.attributes.clone())
+    }
+
+    fn preconditions(&self) -> Vec<Rc<Contract>> {
+        self.contracts
+            .iter()
+            .filter(|contract| contract.kind() == ContractKind::Requires)
+            .cloned()
+            .collect()
+    }
+
+    fn signature_with_types(
+        &self,
+        parameter_types: Vec<Type>,
+        return_type: Type,
+    ) -> FunctionSignature {
+        let parameters: Vec<ParameterSignature> = self
+            .args
+            .iter()
+            .zip(parameter_types)
+            .map(|(arg, parameter_type)| {
+                ParameterSignature::new(
+                    &arg.name,
+                    parameter_type,
+                    arg.default.is_some(),
+                    arg.location.clone(),
+                )
+            })
+            .collect();
+        FunctionSignature::new(&self.name, parameters, return_type, self.location.clone())
+    }
+
+    /// Declares every parameter in the innermost frame with its type from `signature`, checking
+    /// default values against those types on the way
+    pub fn check_parameter_types(
+        &self,
+        signature: &FunctionSignature,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        for (arg, parameter) in self.args.iter().zip(signature.parameters()) {
+            if let Some(default) = &arg.default {
+                let default_type: Type = default.check_types(context)?;
+                context.coerce(parameter.parameter_type(), &default_type, &arg.location)?;
+            }
+            context.declare_variable(&arg.name, parameter.parameter_type().clone());
+        }
+        Ok(())
+    }
+
+    /// Checks every contract is a condition on the parameters, which have to be declared already
+    pub fn check_contract_types(
+        &self,
+        signature: &FunctionSignature,
+        context: &mut TypeContext,
+    ) -> Result<(), CompilerError> {
+        for contract in &self.contracts {
+            contract.check_types(signature.return_type(), context)?;
+        }
+        Ok(())
+    }
+
+    /// The name followed by the declared type parameters, e.g. `id<T>`
+    pub fn generic_name(&self) -> String {
+        if self.type_parameters.is_empty() {
+            return self.name.clone();
+        }
+        format!("{}<{}>", self.name, self.type_parameters.join(", "))
+    }
+
+    fn args_string(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_string()).collect();
+        format!("[{}]", args.join(", "))
+    }
+
+    /// The parameter list followed by the return type annotation, the contracts and the
+    /// attributes, if there are any
+    pub fn signature_s_expression(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.to_s_expression()).collect();
+        let mut s_expression: String = match &self.return_type {
+            Some(return_type) => format!("({}) -> {}", args.join(" "), return_type),
+            None => format!("({})", args.join(" ")),
+        };
+        for contract in &self.contracts {
+            s_expression = format!("{} {}", s_expression, contract.to_s_expression());
+        }
+        for attribute in &self.attributes {
+            s_expression = format!("{} {}", s_expression, attribute.to_s_expression());
+        }
+        s_expression
+    }
+
+    /// `def name(x: f64, y) -> f64` as it is written in source. Contracts and attributes are
+    /// not written out, so prototypes with any have no source
+    pub fn to_source(&self) -> Option<String> {
+        if !self.contracts.is_empty() || !self.attributes.is_empty() {
+            return None;
+        }
+        let args: Option<Vec<String>> = self.args.iter().map(|arg| arg.to_source()).collect();
+        let source: String = format!("def {}({})", self.generic_name(), args?.join(", "));
+        match &self.return_type {
+            Some(return_type) => Some(format!("{} -> {}", source, return_type)),
+            None => Some(source),
         }
     }
 }
 
 impl Display for FunctionPrototype {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // a definition without an annotation returns whatever its body is inferred to give,
+        // which only the signatures `-t` prints know
+        let return_type: String = match &self.return_type {
+            Some(return_type) => return_type.to_string(),
+            None => String::from("inferred"),
+        };
         let display: String = format!(
-            "Function Prototype Name: {}\nFunction Prototype Args: {:?}",
-            self.name, self.args
+            "Function Prototype Name: {}\nFunction Prototype Args: {}\nFunction Prototype Return Type: {}",
+            self.generic_name(),
+            self.args_string(),
+            return_type
         );
         write!(f, "{}", display)
     }
@@ -29,7 +361,63 @@ impl Display for FunctionPrototype {
 impl ASTNode for FunctionPrototype {
     fn print(&self) {
         println!("Node: Function Prototype");
-        println!("Function Prototype: {}", self.name);
-        println!("Function args: {:?}", self.args);
+        println!("Function Prototype: {}", self.generic_name());
+        println!("Function args: {}", self.args_string());
+        println!("Function return type: {}", self.return_type());
+    }
+
+    fn to_s_expression(&self) -> String {
+        format!(
+            "(extern {} {})",
+            self.generic_name(),
+            self.signature_s_expression()
+        )
+    }
+
+    /// Default values can refer to the parameters declared before them
+    fn check_scope(&self, scope: &mut Scope) -> Result<(), CompilerError> {
+        for arg in &self.args {
+            if let Some(default) = &arg.default {
+                default.check_scope(scope)?;
+            }
+            scope.declare(&arg.name, Mutability::Immutable);
+        }
+        for contract in &self.contracts {
+            contract.check_scope(scope)?;
+        }
+        Ok(())
+    }
+
+    fn check_calls(&self, functions: &FunctionTable) -> Result<(), CompilerError> {
+        for arg in &self.args {
+            if let Some(default) = &arg.default {
+                default.check_calls(functions)?;
+            }
+        }
+        for contract in &self.contracts {
+            contract.check_calls(functions)?;
+        }
+        Ok(())
+    }
+
+    /// Prototypes on their own are externs, which have no body to infer types from
+    fn check_types(&self, context: &mut TypeContext) -> Result<Type, CompilerError> {
+        let signature: FunctionSignature = self.signature();
+        self.check_parameter_types(&signature, context)?;
+        self.check_contract_types(&signature, context)?;
+        Ok(signature.return_type().clone())
+    }
+
+    fn check_units(&self, context: &mut UnitContext) -> Result<Measure, CompilerError> {
+        for arg in &self.args {
+            if let Some(default) = &arg.default {
+                default.check_units(context)?;
+            }
+            context.declare(&arg.name, Measure::Unknown);
+        }
+        for contract in &self.contracts {
+            contract.check_units(context)?;
+        }
+        Ok(Measure::Unknown)
     }
 }
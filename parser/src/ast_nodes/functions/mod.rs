@@ -1,2 +1,4 @@
+pub mod attribute;
+pub mod contract;
 pub mod function_definition;
 pub mod function_prototype;
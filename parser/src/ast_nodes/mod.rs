@@ -1,2 +1,4 @@
+pub mod constants;
 pub mod expressions;
 pub mod functions;
+pub mod types;
@@ -0,0 +1,125 @@
+use std::fmt::Display;
+
+use common::source_location::SourceLocation;
+
+use crate::{
+    ast_nodes::types::struct_definition::Field,
+    enum_signature::{EnumSignature, VariantSignature},
+    struct_signature::FieldSignature,
+};
+
+/// One alternative of an enum declaration, `Circle(r)`, or just `Empty` when it carries nothing
+pub struct Variant {
+    name: String,
+    fields: Vec<Field>,
+    location: SourceLocation,
+}
+
+impl Variant {
+    pub fn new(name: &str, fields: Vec<Field>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            fields,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    fn declaration(&self) -> String {
+        if self.fields.is_empty() {
+            return self.name.clone();
+        }
+        let fields: Vec<String> = self.fields.iter().map(|f| f.annotated_name()).collect();
+        format!("{}({})", self.name, fields.join(", "))
+    }
+}
+
+/// `enum Shape { Circle(r), Rect(w, h), Empty }`. A value of the enum is exactly one of its
+/// variants. Like structs, enums are a top level item and can be used before they are declared
+pub struct EnumDefinition {
+    name: String,
+    variants: Vec<Variant>,
+    location: SourceLocation,
+}
+
+impl EnumDefinition {
+    pub fn new(name: &str, variants: Vec<Variant>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            variants,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The signature with unannotated fields taken as f64, as they are in structs
+    pub fn signature(&self) -> EnumSignature {
+        let variants: Vec<VariantSignature> = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let fields: Vec<FieldSignature> = variant
+                    .fields
+                    .iter()
+                    .map(|field| field.signature())
+                    .collect();
+                VariantSignature::new(&variant.name, &self.name, fields, variant.location.clone())
+            })
+            .collect();
+        EnumSignature::new(&self.name, variants, self.location.clone())
+    }
+
+    pub fn print(&self) {
+        println!("Node: Enum Definition");
+        println!("{}", self);
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        let variants: Vec<String> = self
+            .variants
+            .iter()
+            .map(|variant| {
+                if variant.fields.is_empty() {
+                    return variant.name.clone();
+                }
+                let fields: Vec<String> = variant
+                    .fields
+                    .iter()
+                    .map(|field| field.annotated_name().replace(' ', ""))
+                    .collect();
+                format!("({} {})", variant.name, fields.join(" "))
+            })
+            .collect();
+        format!("(enum {} {})", self.name, variants.join(" "))
+    }
+}
+
+impl Display for EnumDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let variants: Vec<String> = self.variants.iter().map(|v| v.declaration()).collect();
+        write!(
+            f,
+            "Enum Name: {}\nEnum Variants: [{}]",
+            self.name,
+            variants.join(", ")
+        )
+    }
+}
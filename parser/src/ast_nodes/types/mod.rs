@@ -0,0 +1,2 @@
+pub mod enum_definition;
+pub mod struct_definition;
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+
+use common::{source_location::SourceLocation, types::Type};
+
+use crate::struct_signature::{FieldSignature, StructSignature};
+
+/// A field of a struct declaration, optionally with a type annotation
+pub struct Field {
+    name: String,
+    annotation: Option<Type>,
+    location: SourceLocation,
+}
+
+impl Field {
+    pub fn new(name: &str, annotation: Option<Type>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            annotation,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn annotation(&self) -> Option<&Type> {
+        self.annotation.as_ref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    pub fn annotated_name(&self) -> String {
+        match &self.annotation {
+            Some(annotation) => format!("{}: {}", self.name, annotation),
+            None => self.name.clone(),
+        }
+    }
+
+    /// The field's signature, taking it as an f64 when it is not annotated
+    pub fn signature(&self) -> FieldSignature {
+        FieldSignature::new(
+            &self.name,
+            self.annotation.clone().unwrap_or(Type::F64),
+            self.location.clone(),
+        )
+    }
+}
+
+/// `struct Point { x, y }`. Structs are a top level item like functions, and can be used
+/// before they are declared
+pub struct StructDefinition {
+    name: String,
+    fields: Vec<Field>,
+    location: SourceLocation,
+}
+
+impl StructDefinition {
+    pub fn new(name: &str, fields: Vec<Field>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            fields,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The signature with unannotated fields taken as f64, as unannotated extern parameters are
+    pub fn signature(&self) -> StructSignature {
+        let fields: Vec<FieldSignature> =
+            self.fields.iter().map(|field| field.signature()).collect();
+        StructSignature::new(&self.name, fields, self.location.clone())
+    }
+
+    pub fn print(&self) {
+        println!("Node: Struct Definition");
+        println!("{}", self);
+    }
+
+    pub fn to_s_expression(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| field.annotated_name().replace(' ', ""))
+            .collect();
+        format!("(struct {} ({}))", self.name, fields.join(" "))
+    }
+}
+
+impl Display for StructDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self.fields.iter().map(|f| f.annotated_name()).collect();
+        write!(
+            f,
+            "Struct Name: {}\nStruct Fields: [{}]",
+            self.name,
+            fields.join(", ")
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use common::{
+    source_location::SourceLocation,
+    types::{Constraint, Type},
+};
+
+use crate::function_signature::{FunctionSignature, ParameterSignature, TypeParameter};
+
+/// The constructors of a result, `Ok` holding a value and `Err` an error. They can be used in
+/// patterns too, like the variants of an enum
+pub const RESULT_VARIANTS: [&str; 2] = ["Ok", "Err"];
+
+/// What the result variant `name` carries in a `result[value, error]`, if it is one
+pub fn result_variant_field(name: &str, value: &Type, error: &Type) -> Option<Type> {
+    match name {
+        "Ok" => Some(value.clone()),
+        "Err" => Some(error.clone()),
+        _ => None,
+    }
+}
+
+/// Functions every module can call without declaring them. They are provided by the runtime
+/// the same way externs are, and an extern with the same name replaces the builtin
+pub fn builtin_signatures() -> Vec<FunctionSignature> {
+    let location: SourceLocation = SourceLocation::builtin();
+    let parameter = |name: &str, parameter_type: Type| -> ParameterSignature {
+        ParameterSignature::new(name, parameter_type, false, location.clone())
+    };
+    // type parameters are instantiated afresh at each call
+    let any: Type = Type::Parameter(0);
+    let result =
+        |value: Type, error: Type| -> Type { Type::Result(Box::new(value), Box::new(error)) };
+
+    vec![
+        FunctionSignature::new(
+            "len",
+            vec![parameter("collection", any.clone())],
+            Type::I64,
+            location.clone(),
+        )
+        .with_type_parameters(vec![TypeParameter::new(Some(Constraint::Collection))]),
+        FunctionSignature::new(
+            "substr",
+            vec![
+                parameter("text", Type::Str),
+                parameter("start", Type::I64),
+                parameter("length", Type::I64),
+            ],
+            Type::Str,
+            location.clone(),
+        ),
+        FunctionSignature::new(
+            "to_string",
+            vec![parameter("value", any.clone())],
+            Type::Str,
+            location.clone(),
+        )
+        .with_type_parameters(vec![TypeParameter::new(None)]),
+        FunctionSignature::new(
+            "print",
+            vec![parameter("text", Type::Str)],
+            Type::Unit,
+            location.clone(),
+        )
+        .with_side_effect(Some(String::from("print"))),
+        FunctionSignature::new(
+            "println",
+            vec![parameter("text", Type::Str)],
+            Type::Unit,
+            location.clone(),
+        )
+        .with_side_effect(Some(String::from("println"))),
+        FunctionSignature::new(
+            RESULT_VARIANTS[0],
+            vec![parameter("value", any.clone())],
+            result(any.clone(), Type::Parameter(1)),
+            location.clone(),
+        )
+        .with_type_parameters(vec![TypeParameter::new(None), TypeParameter::new(None)]),
+        FunctionSignature::new(
+            RESULT_VARIANTS[1],
+            vec![parameter("error", Type::Parameter(1))],
+            result(any, Type::Parameter(1)),
+            location.clone(),
+        )
+        .with_type_parameters(vec![TypeParameter::new(None), TypeParameter::new(None)]),
+        // conversions give an error saying what could not be converted, rather than NaN
+        FunctionSignature::new(
+            "parse_f64",
+            vec![parameter("text", Type::Str)],
+            result(Type::F64, Type::Str),
+            location.clone(),
+        ),
+        FunctionSignature::new(
+            "parse_i64",
+            vec![parameter("text", Type::Str)],
+            result(Type::I64, Type::Str),
+            location.clone(),
+        ),
+    ]
+}
@@ -0,0 +1,291 @@
+use std::{collections::HashMap, fmt::Display};
+
+use common::{
+    error::CompilerError,
+    token::{SimpleBinaryOperater, UnaryOperator},
+    types::Type,
+};
+
+use crate::ast_nodes::constants::constant_definition::ConstantDefinition;
+
+/// A value worked out at compile time
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantValue {
+    F64(f64),
+    I64(i64),
+    Bool(bool),
+    Str(String),
+    Tuple(Vec<ConstantValue>),
+}
+
+impl ConstantValue {
+    pub fn value_type(&self) -> Type {
+        match self {
+            ConstantValue::F64(_) => Type::F64,
+            ConstantValue::I64(_) => Type::I64,
+            ConstantValue::Bool(_) => Type::Bool,
+            ConstantValue::Str(_) => Type::Str,
+            ConstantValue::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| element.value_type())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The same value as a `target`, following the implicit i64 to f64 widening
+    pub fn convert_to(self, target: &Type) -> Result<ConstantValue, CompilerError> {
+        match (self, target) {
+            (ConstantValue::I64(value), Type::F64) => Ok(ConstantValue::F64(value as f64)),
+            (value, target) if value.value_type() == *target => Ok(value),
+            (value, target) => Err(CompilerError::TypeMismatchError(
+                target.clone(),
+                value.value_type(),
+            )),
+        }
+    }
+
+    /// The value as an f64 if it is a number of either type
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            ConstantValue::F64(value) => Some(*value),
+            ConstantValue::I64(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConstantValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Apply a unary operator, following the same typing rules as the type checker
+    pub fn unary(operator: &UnaryOperator, operand: ConstantValue) -> Result<Self, CompilerError> {
+        match (operator, operand) {
+            (UnaryOperator::Negation, ConstantValue::I64(value)) => value
+                .checked_neg()
+                .map(ConstantValue::I64)
+                .ok_or(CompilerError::ConstantOverflowError(0, "-", value)),
+            (UnaryOperator::Negation, ConstantValue::F64(value)) => Ok(ConstantValue::F64(-value)),
+            (UnaryOperator::Not, ConstantValue::Bool(value)) => Ok(ConstantValue::Bool(!value)),
+            (operator, operand) => Err(CompilerError::InvalidUnaryOperandTypeError(
+                operator.symbol(),
+                operand.value_type(),
+            )),
+        }
+    }
+
+    /// Apply a binary arithmetic, comparison or equality operator, following the same typing
+    /// rules as the type checker. Integer arithmetic that overflows is an error rather than
+    /// wrapping around
+    pub fn binary(
+        operator: &SimpleBinaryOperater,
+        left: ConstantValue,
+        right: ConstantValue,
+    ) -> Result<Self, CompilerError> {
+        let invalid = |left: &ConstantValue, right: &ConstantValue| {
+            CompilerError::InvalidOperandTypesError(
+                operator.symbol(),
+                left.value_type(),
+                right.value_type(),
+            )
+        };
+
+        match (&left, &right) {
+            (ConstantValue::I64(l), ConstantValue::I64(r)) => {
+                let (l, r): (i64, i64) = (*l, *r);
+                let overflow = || CompilerError::ConstantOverflowError(l, operator.symbol(), r);
+                match operator {
+                    SimpleBinaryOperater::Addition => l
+                        .checked_add(r)
+                        .map(ConstantValue::I64)
+                        .ok_or_else(overflow),
+                    SimpleBinaryOperater::Subtraction => l
+                        .checked_sub(r)
+                        .map(ConstantValue::I64)
+                        .ok_or_else(overflow),
+                    SimpleBinaryOperater::Multiplication => l
+                        .checked_mul(r)
+                        .map(ConstantValue::I64)
+                        .ok_or_else(overflow),
+                    SimpleBinaryOperater::Division if r == 0 => {
+                        Err(CompilerError::DivisionByZeroError)
+                    }
+                    SimpleBinaryOperater::Division => l
+                        .checked_div(r)
+                        .map(ConstantValue::I64)
+                        .ok_or_else(overflow),
+                    SimpleBinaryOperater::LessThan => Ok(ConstantValue::Bool(l < r)),
+                    SimpleBinaryOperater::GreaterThan => Ok(ConstantValue::Bool(l > r)),
+                    SimpleBinaryOperater::LessEqual => Ok(ConstantValue::Bool(l <= r)),
+                    SimpleBinaryOperater::GreaterEqual => Ok(ConstantValue::Bool(l >= r)),
+                    SimpleBinaryOperater::Equality => Ok(ConstantValue::Bool(l == r)),
+                    _ => Err(invalid(&left, &right)),
+                }
+            }
+            (ConstantValue::Str(l), ConstantValue::Str(r)) => match operator {
+                SimpleBinaryOperater::Addition => Ok(ConstantValue::Str(format!("{}{}", l, r))),
+                SimpleBinaryOperater::LessThan => Ok(ConstantValue::Bool(l < r)),
+                SimpleBinaryOperater::GreaterThan => Ok(ConstantValue::Bool(l > r)),
+                SimpleBinaryOperater::LessEqual => Ok(ConstantValue::Bool(l <= r)),
+                SimpleBinaryOperater::GreaterEqual => Ok(ConstantValue::Bool(l >= r)),
+                SimpleBinaryOperater::Equality => Ok(ConstantValue::Bool(l == r)),
+                _ => Err(invalid(&left, &right)),
+            },
+            _ => match (left.as_f64(), right.as_f64()) {
+                (Some(l), Some(r)) => match operator {
+                    SimpleBinaryOperater::Addition => Ok(ConstantValue::F64(l + r)),
+                    SimpleBinaryOperater::Subtraction => Ok(ConstantValue::F64(l - r)),
+                    SimpleBinaryOperater::Multiplication => Ok(ConstantValue::F64(l * r)),
+                    SimpleBinaryOperater::Division => Ok(ConstantValue::F64(l / r)),
+                    SimpleBinaryOperater::LessThan => Ok(ConstantValue::Bool(l < r)),
+                    SimpleBinaryOperater::GreaterThan => Ok(ConstantValue::Bool(l > r)),
+                    SimpleBinaryOperater::LessEqual => Ok(ConstantValue::Bool(l <= r)),
+                    SimpleBinaryOperater::GreaterEqual => Ok(ConstantValue::Bool(l >= r)),
+                    SimpleBinaryOperater::Equality => Ok(ConstantValue::Bool(l == r)),
+                    _ => Err(invalid(&left, &right)),
+                },
+                _ if *operator == SimpleBinaryOperater::Equality
+                    && left.value_type() == right.value_type() =>
+                {
+                    Ok(ConstantValue::Bool(left == right))
+                }
+                _ => Err(invalid(&left, &right)),
+            },
+        }
+    }
+}
+
+impl Display for ConstantValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstantValue::F64(value) => write!(f, "{:?}", value),
+            ConstantValue::I64(value) => write!(f, "{}", value),
+            ConstantValue::Bool(value) => write!(f, "{}", value),
+            ConstantValue::Str(value) => write!(f, "{:?}", value),
+            ConstantValue::Tuple(elements) if elements.len() == 1 => {
+                write!(f, "({},)", elements[0])
+            }
+            ConstantValue::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+/// Works out the value of every constant in a module. Constants can refer to each other in
+/// any order, so each one is evaluated the first time it is needed, and a constant needed
+/// while it is still being evaluated depends on itself
+pub struct ConstantEvaluator<'a> {
+    definitions: HashMap<String, &'a ConstantDefinition>,
+    /// The value of every constant evaluated so far, `None` for the ones that failed
+    values: HashMap<String, Option<ConstantValue>>,
+    /// The constants being evaluated, each one needed by the one before it
+    evaluating: Vec<String>,
+    /// Values given to names that are not constants, such as the parameters of a function
+    /// whose contract is being checked
+    locals: HashMap<String, ConstantValue>,
+    errors: Vec<CompilerError>,
+}
+
+impl<'a> ConstantEvaluator<'a> {
+    pub fn new(definitions: &'a [ConstantDefinition]) -> Self {
+        Self {
+            definitions: definitions
+                .iter()
+                .map(|definition| (definition.name().to_string(), definition))
+                .collect(),
+            values: HashMap::new(),
+            evaluating: Vec::new(),
+            locals: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The same evaluator, with `locals` giving the value of names that are not constants
+    pub fn with_locals(mut self, locals: HashMap<String, ConstantValue>) -> Self {
+        self.locals = locals;
+        self
+    }
+
+    /// The value of every constant in `definitions` that could be evaluated, in order, along
+    /// with the errors found in the others. Each error is only reported for the constant it
+    /// was found in, not for every constant depending on it
+    pub fn evaluate_all(
+        mut self,
+        definitions: &[ConstantDefinition],
+    ) -> (Vec<(String, ConstantValue)>, Vec<CompilerError>) {
+        let values: Vec<(String, ConstantValue)> = definitions
+            .iter()
+            .filter_map(|definition| {
+                let value: ConstantValue = self.constant(definition.name()).ok()?;
+                Some((definition.name().to_string(), value))
+            })
+            .collect();
+        (values, self.errors)
+    }
+
+    /// The name of the constant whose initializer is being evaluated
+    pub fn current(&self) -> &str {
+        self.evaluating.last().map_or("", |name| name.as_str())
+    }
+
+    /// Whether the value of `name` is known, as a constant or one of the locals
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.locals.contains_key(name) || self.definitions.contains_key(name)
+    }
+
+    /// Whether `name` is a constant of the module, whatever local has the same name
+    pub fn is_module_constant(&self, name: &str) -> bool {
+        self.definitions.contains_key(name)
+    }
+
+    /// The value of the constant `name`, evaluating it first if needed
+    pub fn constant(&mut self, name: &str) -> Result<ConstantValue, CompilerError> {
+        if let Some(value) = self.locals.get(name) {
+            return Ok(value.clone());
+        }
+        self.module_constant(name)
+    }
+
+    /// The value of the module constant `name`, leaving out the locals
+    pub fn module_constant(&mut self, name: &str) -> Result<ConstantValue, CompilerError> {
+        if let Some(value) = self.values.get(name) {
+            return value
+                .clone()
+                .ok_or_else(|| CompilerError::InvalidConstantError(name.to_string()));
+        }
+        if let Some(start) = self.evaluating.iter().position(|n| n == name) {
+            let mut cycle: Vec<String> = self.evaluating[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(CompilerError::CircularConstantError(cycle));
+        }
+        let definition: &ConstantDefinition = match self.definitions.get(name) {
+            Some(definition) => definition,
+            None => return Err(CompilerError::UndefinedVariableError(name.to_string())),
+        };
+
+        self.evaluating.push(name.to_string());
+        let result: Result<ConstantValue, CompilerError> = definition.evaluate(self);
+        self.evaluating.pop();
+
+        match result {
+            Ok(value) => {
+                self.values.insert(name.to_string(), Some(value.clone()));
+                Ok(value)
+            }
+            Err(e) => {
+                // a constant this one depends on already reported why it failed
+                if !matches!(e.root_cause(), CompilerError::InvalidConstantError(_)) {
+                    self.errors.push(e.with_location(definition.location()));
+                }
+                self.values.insert(name.to_string(), None);
+                Err(CompilerError::InvalidConstantError(name.to_string()))
+            }
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    token::{SimpleBinaryOperater, UnaryOperator},
+    types::Type,
+};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::expressions::{
+        binary_expression::BinaryExpression,
+        function_call_expression::{Argument, FunctionCallExpression},
+        integer_expression::IntegerExpression,
+        numeric_expression::NumericExpression,
+        tuple_expression::TupleExpression,
+        unary_expression::UnaryExpression,
+        var_expression::{BindingPattern, VarBinding, VarExpression},
+        variable_expression::VariableExpression,
+    },
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+};
+
+/// The externs whose derivative is known, so calls to them can be differentiated with the
+/// chain rule. The derivatives of `sin` and `cos` call each other, so both must be declared
+pub const MATH_FUNCTIONS: [&str; 6] = ["sin", "cos", "tan", "exp", "log", "sqrt"];
+
+/// The derivative of a value, missing when it is known to be 0
+pub type Tangent = Option<Box<dyn ASTNode>>;
+
+/// An expression taken apart by forward mode differentiation: the bindings it needs evaluated
+/// first, then its value and the derivative of that value. A missing tangent is known to be 0
+pub struct Dual {
+    bindings: Vec<VarBinding>,
+    value: Box<dyn ASTNode>,
+    tangent: Tangent,
+}
+
+impl Dual {
+    pub fn new(value: Box<dyn ASTNode>, tangent: Tangent) -> Self {
+        Self {
+            bindings: Vec::new(),
+            value,
+            tangent,
+        }
+    }
+
+    /// A value that does not depend on the variable being differentiated with respect to
+    pub fn constant(value: Box<dyn ASTNode>) -> Self {
+        Self::new(value, None)
+    }
+
+    /// The same dual, needing `bindings` evaluated before its own
+    pub fn after(mut self, mut bindings: Vec<VarBinding>) -> Self {
+        bindings.append(&mut self.bindings);
+        self.bindings = bindings;
+        self
+    }
+
+    pub fn has_tangent(&self) -> bool {
+        self.tangent.is_some()
+    }
+
+    pub fn into_parts(self) -> (Vec<VarBinding>, Box<dyn ASTNode>, Tangent) {
+        (self.bindings, self.value, self.tangent)
+    }
+
+    /// The value alone, as a single expression evaluating the bindings first
+    pub fn into_value(self) -> Box<dyn ASTNode> {
+        Self::wrap(self.bindings, self.value)
+    }
+
+    /// The tangent alone, as a single expression evaluating the bindings first
+    pub fn into_tangent(self) -> Box<dyn ASTNode> {
+        let tangent: Box<dyn ASTNode> = self.tangent.unwrap_or_else(|| number(0.0));
+        Self::wrap(self.bindings, tangent)
+    }
+
+    /// The value and tangent as a tuple, as a single expression evaluating the bindings first
+    pub fn into_pair(self) -> Box<dyn ASTNode> {
+        let tangent: Box<dyn ASTNode> = self.tangent.unwrap_or_else(|| number(0.0));
+        let pair: Box<dyn ASTNode> = Box::new(TupleExpression::new(vec![self.value, tangent]));
+        Self::wrap(self.bindings, pair)
+    }
+
+    fn wrap(bindings: Vec<VarBinding>, body: Box<dyn ASTNode>) -> Box<dyn ASTNode> {
+        match bindings.is_empty() {
+            true => body,
+            false => Box::new(VarExpression::new(bindings, body)),
+        }
+    }
+}
+
+/// A value that can be used any number of times in a derivative: a variable or a number
+#[derive(Clone)]
+pub enum Atom {
+    Variable(String),
+    F64(f64),
+    I64(i64),
+}
+
+impl Atom {
+    pub fn node(&self) -> Box<dyn ASTNode> {
+        match self {
+            Atom::Variable(name) => {
+                Box::new(VariableExpression::new(name, SourceLocation::builtin()))
+            }
+            Atom::F64(value) => number(*value),
+            Atom::I64(value) => Box::new(IntegerExpression::new(*value, SourceLocation::builtin())),
+        }
+    }
+}
+
+/// Works out the derivative of a function with respect to one of its parameters by source
+/// transformation. Every expression is rewritten into its value and its tangent, and values
+/// used more than once are bound to fresh variables so nothing is evaluated twice. The
+/// variables of the function are renamed along the way, so the bindings of nested expressions
+/// can all be hoisted into one list
+pub struct Differentiator {
+    /// For every variable in scope, the name its value goes by in the derivative and its
+    /// tangent, if it is not 0
+    frames: Vec<HashMap<String, (String, Option<Atom>)>>,
+    /// The math functions the module declares, whose derivatives are known
+    math_functions: HashSet<String>,
+    fresh: usize,
+    location: SourceLocation,
+}
+
+impl Differentiator {
+    /// A differentiator for a function with `parameters`, differentiating with respect to the
+    /// one named `with_respect_to`
+    pub fn new(
+        parameters: &[&str],
+        with_respect_to: &str,
+        math_functions: HashSet<String>,
+        location: SourceLocation,
+    ) -> Self {
+        let parameters: HashMap<String, (String, Option<Atom>)> = parameters
+            .iter()
+            .map(|parameter| {
+                let tangent: Option<Atom> =
+                    (*parameter == with_respect_to).then_some(Atom::F64(1.0));
+                (parameter.to_string(), (parameter.to_string(), tangent))
+            })
+            .collect();
+        Self {
+            frames: vec![parameters],
+            math_functions,
+            fresh: 0,
+            location,
+        }
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    pub fn push_frame(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    /// A name no variable of the function can have, based on `name`
+    fn fresh(&mut self, name: &str) -> String {
+        self.fresh += 1;
+        format!("{}#{}", name, self.fresh)
+    }
+
+    /// A `name = value` binding
+    pub fn binding(&self, name: &str, value: Box<dyn ASTNode>) -> VarBinding {
+        VarBinding::new(
+            BindingPattern::Name(name.to_string()),
+            None,
+            Some(value),
+            self.location.clone(),
+        )
+    }
+
+    /// Bind the variable `name` of the function to `dual`, appending the bindings its value
+    /// and tangent need to `bindings`. Without a dual the variable starts out as its type's
+    /// default, which does not depend on anything
+    pub fn declare(
+        &mut self,
+        name: &str,
+        annotation: Option<Type>,
+        dual: Option<Dual>,
+        bindings: &mut Vec<VarBinding>,
+    ) {
+        let value_name: String = self.fresh(name);
+        let (value, tangent) = match dual {
+            Some(dual) => {
+                let (mut own_bindings, value, tangent) = dual.into_parts();
+                bindings.append(&mut own_bindings);
+                (Some(value), tangent)
+            }
+            None => (None, None),
+        };
+        bindings.push(VarBinding::new(
+            BindingPattern::Name(value_name.clone()),
+            annotation,
+            value,
+            self.location.clone(),
+        ));
+        let tangent: Option<Atom> = tangent.map(|tangent| self.atom(tangent, "du", bindings));
+        self.declare_tangent(name, &value_name, tangent);
+    }
+
+    /// Bring a name bound by a pattern into scope, its tangent being `tangent`
+    pub fn declare_pattern_name(&mut self, name: &str, tangent: Option<Atom>) {
+        self.declare_tangent(name, name, tangent);
+    }
+
+    fn declare_tangent(&mut self, name: &str, value_name: &str, tangent: Option<Atom>) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), (value_name.to_string(), tangent));
+        }
+    }
+
+    /// A reference to `name`. Constants and functions do not depend on any parameter
+    pub fn variable(&self, name: &str) -> Dual {
+        match self.frames.iter().rev().find_map(|frame| frame.get(name)) {
+            Some((value_name, tangent)) => Dual::new(
+                Box::new(VariableExpression::new(value_name, self.location.clone())),
+                tangent.as_ref().map(|tangent| tangent.node()),
+            ),
+            None => Dual::constant(Box::new(VariableExpression::new(
+                name,
+                self.location.clone(),
+            ))),
+        }
+    }
+
+    /// `node` as something that can be used more than once, bound to a fresh variable based on
+    /// `name` unless it already is a variable or a number
+    fn atom(&mut self, node: Box<dyn ASTNode>, name: &str, bindings: &mut Vec<VarBinding>) -> Atom {
+        if let Some(variable) = node.variable_name() {
+            return Atom::Variable(variable.to_string());
+        }
+        match node.evaluate_constant(&mut ConstantEvaluator::new(&[])) {
+            Ok(ConstantValue::F64(value)) => Atom::F64(value),
+            Ok(ConstantValue::I64(value)) => Atom::I64(value),
+            _ => self.bind(name, node, bindings),
+        }
+    }
+
+    /// The value and tangent of `dual` as atoms, appending the bindings they need to `bindings`
+    pub fn atoms(&mut self, dual: Dual, bindings: &mut Vec<VarBinding>) -> (Atom, Option<Atom>) {
+        let (mut own_bindings, value, tangent) = dual.into_parts();
+        bindings.append(&mut own_bindings);
+        let value: Atom = self.atom(value, "u", bindings);
+        let tangent: Option<Atom> = tangent.map(|tangent| self.atom(tangent, "du", bindings));
+        (value, tangent)
+    }
+
+    /// `node` bound to a fresh variable based on `name`, which the returned atom refers to
+    pub fn bind(
+        &mut self,
+        name: &str,
+        node: Box<dyn ASTNode>,
+        bindings: &mut Vec<VarBinding>,
+    ) -> Atom {
+        let name: String = self.fresh(name);
+        bindings.push(self.binding(&name, node));
+        Atom::Variable(name)
+    }
+
+    /// Whether a call to `name` can be differentiated with the chain rule
+    pub fn is_math_function(&self, name: &str) -> bool {
+        self.math_functions.contains(name)
+    }
+
+    /// The derivative of the math function `name` at `argument`, a tangent of 1 for it, given
+    /// the `value` it takes there. Only `sin` and `cos` need another extern for it
+    pub fn math_derivative(
+        &self,
+        name: &str,
+        argument: &Atom,
+        value: &Atom,
+    ) -> Result<Box<dyn ASTNode>, CompilerError> {
+        let u = || argument.node();
+        let companion = |other: &str| match self.is_math_function(other) {
+            true => Ok(self.call(other, u())),
+            false => Err(CompilerError::MissingExternError(
+                name.to_string(),
+                other.to_string(),
+            )),
+        };
+        Ok(match name {
+            "sin" => companion("cos")?,
+            "cos" => negate(companion("sin")?),
+            "tan" => binary(
+                SimpleBinaryOperater::Addition,
+                number(1.0),
+                multiply(value.node(), value.node()),
+            ),
+            "exp" => value.node(),
+            "log" => divide(number(1.0), u()),
+            _ => divide(number(1.0), multiply(number(2.0), value.node())),
+        })
+    }
+
+    /// A call to the function `name` with a single argument
+    pub fn call(&self, name: &str, argument: Box<dyn ASTNode>) -> Box<dyn ASTNode> {
+        Box::new(FunctionCallExpression::new(
+            Box::new(VariableExpression::new(name, self.location.clone())),
+            vec![Argument::new(None, argument, self.location.clone())],
+            self.location.clone(),
+        ))
+    }
+}
+
+pub fn number(value: f64) -> Box<dyn ASTNode> {
+    Box::new(NumericExpression::new(value))
+}
+
+pub fn binary(
+    operator: SimpleBinaryOperater,
+    left: Box<dyn ASTNode>,
+    right: Box<dyn ASTNode>,
+) -> Box<dyn ASTNode> {
+    Box::new(BinaryExpression::new(
+        operator,
+        left,
+        right,
+        SourceLocation::builtin(),
+    ))
+}
+
+pub fn negate(operand: Box<dyn ASTNode>) -> Box<dyn ASTNode> {
+    Box::new(UnaryExpression::new(
+        UnaryOperator::Negation,
+        operand,
+        SourceLocation::builtin(),
+    ))
+}
+
+/// `left * right`, leaving out a factor of 1
+pub fn multiply(left: Box<dyn ASTNode>, right: Box<dyn ASTNode>) -> Box<dyn ASTNode> {
+    let is_one = |node: &dyn ASTNode| {
+        matches!(
+            node.evaluate_constant(&mut ConstantEvaluator::new(&[])),
+            Ok(ConstantValue::F64(value)) if value == 1.0
+        ) || node.constant_integer() == Some(1)
+    };
+    match (is_one(left.as_ref()), is_one(right.as_ref())) {
+        (true, _) => right,
+        (_, true) => left,
+        _ => binary(SimpleBinaryOperater::Multiplication, left, right),
+    }
+}
+
+pub fn divide(left: Box<dyn ASTNode>, right: Box<dyn ASTNode>) -> Box<dyn ASTNode> {
+    binary(SimpleBinaryOperater::Division, left, right)
+}
+
+/// `left - right`, either of which can be known to be 0
+pub fn difference(
+    left: Option<Box<dyn ASTNode>>,
+    right: Option<Box<dyn ASTNode>>,
+) -> Option<Box<dyn ASTNode>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(binary(SimpleBinaryOperater::Subtraction, left, right)),
+        (left, right) => left.or(right.map(negate)),
+    }
+}
+
+/// The sum of the terms that are not known to be 0, `None` when all of them are
+pub fn sum(terms: Vec<Option<Box<dyn ASTNode>>>) -> Option<Box<dyn ASTNode>> {
+    terms
+        .into_iter()
+        .flatten()
+        .reduce(|sum, term| binary(SimpleBinaryOperater::Addition, sum, term))
+}
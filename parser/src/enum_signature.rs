@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+use common::{source_location::SourceLocation, types::Type};
+
+use crate::{
+    function_signature::{FunctionSignature, ParameterSignature},
+    struct_signature::FieldSignature,
+};
+
+/// One alternative of an enum along with the values it carries, in declaration order
+#[derive(Clone)]
+pub struct VariantSignature {
+    name: String,
+    enum_name: String,
+    fields: Vec<FieldSignature>,
+    location: SourceLocation,
+}
+
+impl VariantSignature {
+    pub fn new(
+        name: &str,
+        enum_name: &str,
+        fields: Vec<FieldSignature>,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            enum_name: enum_name.to_string(),
+            fields,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn enum_name(&self) -> &str {
+        &self.enum_name
+    }
+
+    pub fn fields(&self) -> &[FieldSignature] {
+        &self.fields
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The function building a value of this variant, taking its fields as parameters
+    pub fn constructor(&self) -> FunctionSignature {
+        let parameters: Vec<ParameterSignature> = self
+            .fields
+            .iter()
+            .map(|field| {
+                ParameterSignature::new(
+                    field.name(),
+                    field.field_type().clone(),
+                    false,
+                    field.location().clone(),
+                )
+            })
+            .collect();
+        FunctionSignature::new(
+            &self.name,
+            parameters,
+            Type::Named(self.enum_name.clone()),
+            self.location.clone(),
+        )
+    }
+}
+
+/// Prints the variant as declared, e.g. `Rect(w: f64, h: f64)`
+impl Display for VariantSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "{}", self.name);
+        }
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name(), field.field_type()))
+            .collect();
+        write!(f, "{}({})", self.name, fields.join(", "))
+    }
+}
+
+/// The variants of an enum in declaration order
+#[derive(Clone)]
+pub struct EnumSignature {
+    name: String,
+    variants: Vec<VariantSignature>,
+    location: SourceLocation,
+}
+
+impl EnumSignature {
+    pub fn new(name: &str, variants: Vec<VariantSignature>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            variants,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn variants(&self) -> &[VariantSignature] {
+        &self.variants
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&VariantSignature> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
+/// Prints the declaration, e.g. `Shape { Circle(r: f64), Empty }`
+impl Display for EnumSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let variants: Vec<String> = self.variants.iter().map(|v| v.to_string()).collect();
+        write!(f, "{} {{ {} }}", self.name, variants.join(", "))
+    }
+}
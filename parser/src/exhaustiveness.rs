@@ -0,0 +1,251 @@
+use std::fmt::Display;
+
+use common::types::Type;
+
+use crate::{
+    builtins::{result_variant_field, RESULT_VARIANTS},
+    enum_signature::EnumSignature,
+    type_context::TypeContext,
+};
+
+/// What a pattern checks a value against, ignoring any names it binds
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constructor {
+    Variant(String),
+    Bool(bool),
+    Tuple,
+    /// A number or string, of which there are too many to ever list them all
+    Literal(String),
+}
+
+/// A constructor along with the types of the values it carries
+type Signature = (Constructor, Vec<Type>);
+
+/// A pattern reduced to the shape of the values it matches. Bindings and wildcards both match
+/// anything, and arms with a guard may not match at all so they are left out
+#[derive(Debug, Clone)]
+pub enum Space {
+    Anything,
+    Constructed(Constructor, Vec<Space>),
+}
+
+impl Display for Space {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (constructor, fields) = match self {
+            Space::Anything => return write!(f, "_"),
+            Space::Constructed(constructor, fields) => (constructor, fields),
+        };
+        let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+        match constructor {
+            Constructor::Variant(name) if fields.is_empty() => write!(f, "{}", name),
+            Constructor::Variant(name) => write!(f, "{}({})", name, fields.join(", ")),
+            Constructor::Bool(value) => write!(f, "{}", value),
+            Constructor::Tuple if fields.len() == 1 => write!(f, "({},)", fields[0]),
+            Constructor::Tuple => write!(f, "({})", fields.join(", ")),
+            Constructor::Literal(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Answers whether a pattern can match a value no earlier pattern does, following the usual
+/// usefulness algorithm over rows of patterns. A match is exhaustive when a wildcard would be
+/// useless after its last arm
+pub struct Exhaustiveness<'c, 'a> {
+    context: &'c TypeContext<'a>,
+}
+
+impl<'c, 'a> Exhaustiveness<'c, 'a> {
+    pub fn new(context: &'c TypeContext<'a>) -> Self {
+        Self { context }
+    }
+
+    /// Whether `pattern` matches some value of `scrutinee_type` that none of `previous` do
+    pub fn is_useful(&self, previous: &[Space], pattern: &Space, scrutinee_type: &Type) -> bool {
+        let rows: Vec<Vec<Space>> = previous.iter().map(|space| vec![space.clone()]).collect();
+        self.useful(
+            &rows,
+            std::slice::from_ref(pattern),
+            std::slice::from_ref(scrutinee_type),
+        )
+    }
+
+    /// A value of `scrutinee_type` none of `arms` match, if there is one
+    pub fn missing(&self, arms: &[Space], scrutinee_type: &Type) -> Option<Space> {
+        let rows: Vec<Vec<Space>> = arms.iter().map(|space| vec![space.clone()]).collect();
+        self.witness(&rows, std::slice::from_ref(scrutinee_type))
+            .map(|mut witness| witness.remove(0))
+    }
+
+    /// Every constructor of `t` along with the types of the values each one carries, `None`
+    /// when they cannot all be listed
+    fn constructors(&self, t: &Type) -> Option<Vec<Signature>> {
+        match t {
+            Type::Bool => Some(vec![
+                (Constructor::Bool(true), Vec::new()),
+                (Constructor::Bool(false), Vec::new()),
+            ]),
+            Type::Tuple(elements) => Some(vec![(Constructor::Tuple, elements.clone())]),
+            Type::Result(value, error) => Some(
+                RESULT_VARIANTS
+                    .iter()
+                    .map(|name| {
+                        let fields: Vec<Type> = result_variant_field(name, value, error)
+                            .into_iter()
+                            .collect();
+                        (Constructor::Variant(name.to_string()), fields)
+                    })
+                    .collect(),
+            ),
+            Type::Named(name) => {
+                let signature: &EnumSignature = self.context.enum_signature(name)?;
+                Some(
+                    signature
+                        .variants()
+                        .iter()
+                        .map(|variant| {
+                            let fields: Vec<Type> = variant
+                                .fields()
+                                .iter()
+                                .map(|field| field.field_type().clone())
+                                .collect();
+                            (Constructor::Variant(variant.name().to_string()), fields)
+                        })
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// The types of the values `constructor` carries when it builds a `t`
+    fn field_types(&self, constructor: &Constructor, t: &Type) -> Vec<Type> {
+        self.constructors(t)
+            .and_then(|constructors| {
+                constructors
+                    .into_iter()
+                    .find(|(candidate, _)| candidate == constructor)
+            })
+            .map_or_else(Vec::new, |(_, fields)| fields)
+    }
+
+    /// The constructors of `t` if the first column of `rows` uses every one of them
+    fn complete_constructors(
+        &self,
+        rows: &[Vec<Space>],
+        t: &Type,
+    ) -> Result<Vec<Signature>, Option<Signature>> {
+        let constructors: Vec<Signature> = match self.constructors(t) {
+            Some(constructors) => constructors,
+            None => return Err(None),
+        };
+        match constructors.iter().find(|(constructor, _)| {
+            !rows
+                .iter()
+                .any(|row| matches!(&row[0], Space::Constructed(c, _) if c == constructor))
+        }) {
+            Some(missing) => Err(Some(missing.clone())),
+            None => Ok(constructors),
+        }
+    }
+
+    /// The rows that match `constructor` in their first column, with that column replaced by
+    /// the values the constructor carries
+    fn specialize(rows: &[Vec<Space>], constructor: &Constructor, arity: usize) -> Vec<Vec<Space>> {
+        rows.iter()
+            .filter_map(|row| {
+                let mut specialized: Vec<Space> = match &row[0] {
+                    Space::Anything => vec![Space::Anything; arity],
+                    Space::Constructed(c, fields) if c == constructor => fields.clone(),
+                    Space::Constructed(..) => return None,
+                };
+                specialized.extend_from_slice(&row[1..]);
+                Some(specialized)
+            })
+            .collect()
+    }
+
+    /// The rows that match anything in their first column, without that column
+    fn default_rows(rows: &[Vec<Space>]) -> Vec<Vec<Space>> {
+        rows.iter()
+            .filter(|row| matches!(row[0], Space::Anything))
+            .map(|row| row[1..].to_vec())
+            .collect()
+    }
+
+    fn useful(&self, rows: &[Vec<Space>], vector: &[Space], types: &[Type]) -> bool {
+        let (head, head_type) = match (vector.first(), types.first()) {
+            (Some(head), Some(head_type)) => (head, head_type),
+            _ => return rows.is_empty(),
+        };
+
+        match head {
+            Space::Constructed(constructor, fields) => {
+                let mut specialized_vector: Vec<Space> = fields.clone();
+                specialized_vector.extend_from_slice(&vector[1..]);
+                let mut specialized_types: Vec<Type> = self.field_types(constructor, head_type);
+                specialized_types.resize(fields.len(), Type::Unit);
+                specialized_types.extend_from_slice(&types[1..]);
+                self.useful(
+                    &Self::specialize(rows, constructor, fields.len()),
+                    &specialized_vector,
+                    &specialized_types,
+                )
+            }
+            Space::Anything => match self.complete_constructors(rows, head_type) {
+                Ok(constructors) => constructors.into_iter().any(|(constructor, fields)| {
+                    let mut specialized_vector: Vec<Space> = vec![Space::Anything; fields.len()];
+                    specialized_vector.extend_from_slice(&vector[1..]);
+                    let arity: usize = fields.len();
+                    let mut specialized_types: Vec<Type> = fields;
+                    specialized_types.extend_from_slice(&types[1..]);
+                    self.useful(
+                        &Self::specialize(rows, &constructor, arity),
+                        &specialized_vector,
+                        &specialized_types,
+                    )
+                }),
+                Err(_) => self.useful(&Self::default_rows(rows), &vector[1..], &types[1..]),
+            },
+        }
+    }
+
+    /// A row of values, one per column, that no row of `rows` matches
+    fn witness(&self, rows: &[Vec<Space>], types: &[Type]) -> Option<Vec<Space>> {
+        let head_type: &Type = match types.first() {
+            Some(head_type) => head_type,
+            None if rows.is_empty() => return Some(Vec::new()),
+            None => return None,
+        };
+
+        match self.complete_constructors(rows, head_type) {
+            Ok(constructors) => constructors.into_iter().find_map(|(constructor, fields)| {
+                let arity: usize = fields.len();
+                let mut specialized_types: Vec<Type> = fields;
+                specialized_types.extend_from_slice(&types[1..]);
+                let mut witness: Vec<Space> = self.witness(
+                    &Self::specialize(rows, &constructor, arity),
+                    &specialized_types,
+                )?;
+                let rest: Vec<Space> = witness.split_off(arity);
+                let mut result: Vec<Space> = vec![Space::Constructed(constructor, witness)];
+                result.extend(rest);
+                Some(result)
+            }),
+            Err(missing) => {
+                let rest: Vec<Space> = self.witness(&Self::default_rows(rows), &types[1..])?;
+                let uses_constructors: bool = rows
+                    .iter()
+                    .any(|row| matches!(row[0], Space::Constructed(..)));
+                let head: Space = match missing {
+                    Some((constructor, fields)) if uses_constructors => {
+                        Space::Constructed(constructor, vec![Space::Anything; fields.len()])
+                    }
+                    _ => Space::Anything,
+                };
+                let mut result: Vec<Space> = vec![head];
+                result.extend(rest);
+                Some(result)
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::{fmt::Display, rc::Rc};
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    types::{Constraint, Type},
+};
+
+use crate::ast_nodes::functions::{
+    attribute::{Attribute, AttributeKind},
+    contract::Contract,
+};
+
+#[derive(Clone)]
+pub struct ParameterSignature {
+    name: String,
+    parameter_type: Type,
+    has_default: bool,
+    location: SourceLocation,
+}
+
+impl ParameterSignature {
+    pub fn new(
+        name: &str,
+        parameter_type: Type,
+        has_default: bool,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            parameter_type,
+            has_default,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parameter_type(&self) -> &Type {
+        &self.parameter_type
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
+/// A type parameter a signature is polymorphic over. Every call gets its own copy of it
+#[derive(Clone)]
+pub struct TypeParameter {
+    /// The name the parameter was declared with, `None` when inference introduced it
+    name: Option<String>,
+    constraint: Option<Constraint>,
+}
+
+impl TypeParameter {
+    pub fn new(constraint: Option<Constraint>) -> Self {
+        Self {
+            name: None,
+            constraint,
+        }
+    }
+
+    /// A parameter declared between `<` and `>` after the function name
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            constraint: None,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// What any type substituted for the parameter has to satisfy
+    pub fn constraint(&self) -> Option<&Constraint> {
+        self.constraint.as_ref()
+    }
+}
+
+/// What callers need to know about a function: its parameters in order and what it returns
+#[derive(Clone)]
+pub struct FunctionSignature {
+    name: String,
+    parameters: Vec<ParameterSignature>,
+    return_type: Type,
+    type_parameters: Vec<TypeParameter>,
+    /// What every call has to satisfy, checked at the call site when the arguments are known
+    /// at compile time
+    preconditions: Vec<Rc<Contract>>,
+    attributes: Vec<Attribute>,
+    /// The function called by this one that may have side effects, or its own name when it
+    /// has them itself. `None` when no call by name was found to have any
+    side_effect: Option<String>,
+    location: SourceLocation,
+}
+
+impl FunctionSignature {
+    pub fn new(
+        name: &str,
+        parameters: Vec<ParameterSignature>,
+        return_type: Type,
+        location: SourceLocation,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters,
+            return_type,
+            type_parameters: Vec::new(),
+            preconditions: Vec::new(),
+            attributes: Vec::new(),
+            side_effect: None,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parameters(&self) -> &[ParameterSignature] {
+        &self.parameters
+    }
+
+    pub fn return_type(&self) -> &Type {
+        &self.return_type
+    }
+
+    pub fn type_parameters(&self) -> &[TypeParameter] {
+        &self.type_parameters
+    }
+
+    pub fn preconditions(&self) -> &[Rc<Contract>] {
+        &self.preconditions
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// The attribute of the given kind, if the function was given one
+    pub fn attribute(&self, kind: AttributeKind) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.kind() == kind)
+    }
+
+    pub fn side_effect(&self) -> Option<&str> {
+        self.side_effect.as_deref()
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The type of the function when it is used as a value
+    pub fn function_type(&self) -> Type {
+        let parameter_types: Vec<Type> = self
+            .parameters
+            .iter()
+            .map(|parameter| parameter.parameter_type.clone())
+            .collect();
+        Type::Function(parameter_types, Box::new(self.return_type.clone()))
+    }
+
+    /// The parameter types followed by the return type
+    pub fn types(&self) -> Vec<Type> {
+        let mut types: Vec<Type> = self
+            .parameters
+            .iter()
+            .map(|parameter| parameter.parameter_type.clone())
+            .collect();
+        types.push(self.return_type.clone());
+        types
+    }
+
+    /// The same signature with every parameter and return type passed through `map`, along with
+    /// the location the type was declared at
+    pub fn map_types(
+        &self,
+        mut map: impl FnMut(&Type, &SourceLocation) -> Type,
+    ) -> FunctionSignature {
+        let mut signature: FunctionSignature = self.clone();
+        for parameter in &mut signature.parameters {
+            parameter.parameter_type = map(&parameter.parameter_type, &parameter.location);
+        }
+        signature.return_type = map(&signature.return_type, &signature.location);
+        signature
+    }
+
+    pub fn with_type_parameters(mut self, type_parameters: Vec<TypeParameter>) -> Self {
+        self.type_parameters = type_parameters;
+        self
+    }
+
+    pub fn with_preconditions(mut self, preconditions: Vec<Rc<Contract>>) -> Self {
+        self.preconditions = preconditions;
+        self
+    }
+
+    pub fn with_attributes(mut self, attributes: Vec<Attribute>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// The same signature, for a function that may have side effects through calling `callee`
+    pub fn with_side_effect(mut self, callee: Option<String>) -> Self {
+        self.side_effect = callee;
+        self
+    }
+
+    /// Match the arguments of a call against the parameters. `argument_names` holds the name of
+    /// every argument in call order, `None` for positional ones. For each parameter the result
+    /// holds the index of the argument supplying it, or `None` when its default value should be
+    /// used
+    pub fn bind_arguments(
+        &self,
+        argument_names: &[Option<&str>],
+    ) -> Result<Vec<Option<usize>>, CompilerError> {
+        let mut bound_arguments: Vec<Option<usize>> = vec![None; self.parameters.len()];
+
+        for (argument_index, argument_name) in argument_names.iter().enumerate() {
+            let parameter_index: usize = match argument_name {
+                None if argument_index < self.parameters.len() => argument_index,
+                None => {
+                    return Err(CompilerError::TooManyArgumentsError(
+                        self.name.clone(),
+                        self.parameters.len(),
+                        argument_names.len(),
+                    ))
+                }
+                Some(name) => match self.parameters.iter().position(|p| p.name == *name) {
+                    Some(position) => position,
+                    None => {
+                        return Err(CompilerError::UnknownNamedArgumentError(
+                            self.name.clone(),
+                            name.to_string(),
+                        ))
+                    }
+                },
+            };
+
+            if bound_arguments[parameter_index].is_some() {
+                return Err(CompilerError::DuplicateArgumentError(
+                    self.name.clone(),
+                    self.parameters[parameter_index].name.clone(),
+                ));
+            }
+            bound_arguments[parameter_index] = Some(argument_index);
+        }
+
+        for (parameter, bound_argument) in self.parameters.iter().zip(&bound_arguments) {
+            if bound_argument.is_none() && !parameter.has_default {
+                return Err(CompilerError::MissingArgumentError(
+                    self.name.clone(),
+                    parameter.name.clone(),
+                ));
+            }
+        }
+
+        Ok(bound_arguments)
+    }
+}
+
+/// Prints declared type parameters by name and inferred ones as `'a`, `'b`, ... e.g.
+/// `first<T>(items: [T], n: 'b) -> T` or `add(x: 'a, y: 'a) -> 'a where 'a: numeric`
+impl Display for FunctionSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let named = |t: &Type| -> Type {
+            t.map_variables(&mut |t| match t {
+                Type::Parameter(index) => self
+                    .type_parameters
+                    .get(*index)?
+                    .name()
+                    .map(|name| Type::Generic(name.to_string())),
+                _ => None,
+            })
+        };
+        let parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|parameter| format!("{}: {}", parameter.name, named(&parameter.parameter_type)))
+            .collect();
+        let constraints: Vec<String> = self
+            .type_parameters
+            .iter()
+            .enumerate()
+            .filter_map(|(index, parameter)| {
+                let constraint: &Constraint = parameter.constraint.as_ref()?;
+                Some(format!(
+                    "{}: {}",
+                    named(&Type::Parameter(index)),
+                    constraint
+                ))
+            })
+            .collect();
+        let names: Vec<&str> = self
+            .type_parameters
+            .iter()
+            .filter_map(|parameter| parameter.name())
+            .collect();
+
+        write!(f, "{}", self.name)?;
+        if !names.is_empty() {
+            write!(f, "<{}>", names.join(", "))?;
+        }
+        write!(
+            f,
+            "({}) -> {}",
+            parameters.join(", "),
+            named(&self.return_type)
+        )?;
+        if !constraints.is_empty() {
+            write!(f, " where {}", constraints.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,16 @@
 pub mod ast;
 mod ast_node;
 mod ast_nodes;
+mod builtins;
+pub mod constant_evaluator;
+mod differentiator;
+pub mod enum_signature;
+mod exhaustiveness;
+pub mod function_signature;
+mod macro_definition;
+pub mod module;
+pub mod module_loader;
+mod scope;
+pub mod struct_signature;
+mod type_context;
+mod unit_context;
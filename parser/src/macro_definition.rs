@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    token::{SimpleBinaryOperater, Token},
+};
+
+/// A token along with where it was read, or which expansion produced it
+pub type SourceToken = (Token, SourceLocation);
+
+/// Whether `token` opens a token tree
+pub fn opens_tree(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LeftParenthesis | Token::LeftBrace | Token::LeftBracket
+    )
+}
+
+/// Whether `token` closes a token tree
+pub fn closes_tree(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::RightParenthesis | Token::RightBrace | Token::RightBracket
+    )
+}
+
+/// The position just past the token tree starting at `start`: a single token, or a bracket
+/// along with everything up to the one closing it
+fn tree_end(tokens: &[SourceToken], start: usize) -> Option<usize> {
+    let mut depth: usize = 0;
+    for (offset, (token, _)) in tokens.get(start..)?.iter().enumerate() {
+        if opens_tree(token) {
+            depth += 1;
+        } else if closes_tree(token) {
+            depth = depth.checked_sub(1)?;
+        }
+        if depth == 0 {
+            return Some(start + offset + 1);
+        }
+    }
+    None
+}
+
+/// Whether the identifier `name` read at `location` was written in a macro template without
+/// the template binding it. Expansion renames every name a template binds, so any other name
+/// from a template refers to what it means where the macro is defined, at the top level of the
+/// module, whatever the call site binds
+pub fn is_free_template_name(name: &str, location: &SourceLocation) -> bool {
+    location.expansion().is_some() && !name.contains('#')
+}
+
+/// The position of `terminator` in `tokens` from `start` on, or the end of `tokens` without
+/// one, outside of any brackets. `None` when it is missing, or when a `,` or `;` that is not
+/// the terminator comes first, as the tokens before it would be more than one expression
+fn expression_end(
+    tokens: &[SourceToken],
+    start: usize,
+    terminator: Option<&Token>,
+) -> Option<usize> {
+    let mut depth: usize = 0;
+    for (offset, (token, _)) in tokens[start..].iter().enumerate() {
+        if depth == 0 && Some(token) == terminator {
+            return Some(start + offset);
+        }
+        if depth == 0 && matches!(token, Token::Comma | Token::SemiColon) {
+            return None;
+        }
+        if opens_tree(token) {
+            depth += 1;
+        } else if closes_tree(token) {
+            depth = depth.saturating_sub(1);
+        }
+    }
+    terminator.is_none().then_some(tokens.len())
+}
+
+/// `macro name(pattern) => template`. A use such as `name!(arguments)` is replaced by the
+/// template before it is parsed, with every `$variable` in the template replaced by the tokens
+/// the pattern matched for it. Names the template binds with `var`, `fn`, `def`, `for`, `catch`
+/// or a match arm are renamed in every expansion, so they can never capture or shadow the
+/// names used at the call site, and the names it does not bind cannot be captured by them
+pub struct MacroDefinition {
+    name: String,
+    pattern: Vec<SourceToken>,
+    template: Vec<SourceToken>,
+    binders: HashSet<String>,
+    location: SourceLocation,
+}
+
+impl MacroDefinition {
+    pub fn new(
+        name: &str,
+        pattern: Vec<SourceToken>,
+        template: Vec<SourceToken>,
+        location: SourceLocation,
+    ) -> Result<Self, CompilerError> {
+        let mut variables: Vec<&str> = Vec::new();
+        for (token, location) in &pattern {
+            if let Token::MacroVariable(variable) = token {
+                if variables.contains(&variable.as_str()) {
+                    return Err(CompilerError::DuplicateMacroVariableError(
+                        name.to_string(),
+                        variable.clone(),
+                    )
+                    .with_location(location));
+                }
+                variables.push(variable);
+            }
+        }
+        for (token, location) in &template {
+            if let Token::MacroVariable(variable) = token {
+                if !variables.contains(&variable.as_str()) {
+                    return Err(CompilerError::UnknownMacroVariableError(
+                        name.to_string(),
+                        variable.clone(),
+                    )
+                    .with_location(location));
+                }
+            }
+        }
+
+        let binders: HashSet<String> = Self::binders(&template);
+        Ok(Self {
+            name: name.to_string(),
+            pattern,
+            template,
+            binders,
+            location,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// The names the template binds, found from its tokens alone: the names declared by `var`
+    /// and `for`, the parameters of `fn` and `def` along with the name of the function `def`
+    /// defines, the error `catch` names and the names match arms bind
+    fn binders(template: &[SourceToken]) -> HashSet<String> {
+        let mut binders: HashSet<String> = HashSet::new();
+        for (index, (token, _)) in template.iter().enumerate() {
+            let rest: &[SourceToken] = &template[index + 1..];
+            match token {
+                Token::Var | Token::For => Self::var_binders(rest, &mut binders),
+                Token::Fn => Self::parameter_binders(rest, &mut binders),
+                Token::Match => Self::arm_binders(rest, &mut binders),
+                Token::Catch => {
+                    if let Some((Token::Identifier(name), _)) = rest.first() {
+                        binders.insert(name.clone());
+                    }
+                }
+                Token::Def => {
+                    if let Some((Token::Identifier(name), _)) = rest.first() {
+                        binders.insert(name.clone());
+                    }
+                    if let Some(start) = rest
+                        .iter()
+                        .position(|(token, _)| *token == Token::LeftParenthesis)
+                    {
+                        Self::parameter_binders(&rest[start..], &mut binders);
+                    }
+                }
+                _ => {}
+            }
+        }
+        binders
+    }
+
+    /// The names in the patterns of the bindings following `var`, up to its `in` or `;`
+    fn var_binders(tokens: &[SourceToken], binders: &mut HashSet<String>) {
+        let mut depth: usize = 0;
+        let mut in_pattern: bool = true;
+        for (token, _) in tokens {
+            match token {
+                Token::Identifier(name) if in_pattern => {
+                    binders.insert(name.clone());
+                }
+                Token::Comma if depth == 0 => in_pattern = true,
+                Token::In | Token::SemiColon if depth == 0 => return,
+                Token::Colon | Token::SimpleBinaryOperator(SimpleBinaryOperater::Assignment)
+                    if depth == 0 =>
+                {
+                    in_pattern = false
+                }
+                token if opens_tree(token) => depth += 1,
+                token if closes_tree(token) => match depth.checked_sub(1) {
+                    Some(outer) => depth = outer,
+                    None => return,
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// The names bound by the patterns of the arms in the braces following `match`. A
+    /// capitalized name in a pattern is a variant, and binds nothing
+    fn arm_binders(tokens: &[SourceToken], binders: &mut HashSet<String>) {
+        let start: usize = match tokens
+            .iter()
+            .position(|(token, _)| *token == Token::LeftBrace)
+        {
+            Some(start) => start,
+            None => return,
+        };
+        let mut depth: usize = 0;
+        let mut in_pattern: bool = true;
+        for (token, _) in &tokens[start..] {
+            match token {
+                Token::Comma if depth == 1 => in_pattern = true,
+                Token::FatArrow | Token::If if depth == 1 => in_pattern = false,
+                Token::Identifier(name)
+                    if in_pattern && name != "_" && !name.starts_with(char::is_uppercase) =>
+                {
+                    binders.insert(name.clone());
+                }
+                token if opens_tree(token) => depth += 1,
+                token if closes_tree(token) => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The parameter names in the parenthesized list `tokens` starts with
+    fn parameter_binders(tokens: &[SourceToken], binders: &mut HashSet<String>) {
+        let mut depth: usize = 0;
+        let mut expecting_name: bool = false;
+        for (token, _) in tokens {
+            match token {
+                token if opens_tree(token) => {
+                    depth += 1;
+                    expecting_name = depth == 1;
+                }
+                token if closes_tree(token) => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                Token::Comma if depth == 1 => expecting_name = true,
+                Token::Identifier(name) if expecting_name => {
+                    binders.insert(name.clone());
+                    expecting_name = false;
+                }
+                _ => expecting_name = false,
+            }
+        }
+    }
+
+    /// The tokens each variable of the pattern matched in `arguments`, the tokens between the
+    /// parentheses of a use of the macro. A variable matches a single expression: one followed
+    /// by another token of the pattern matches everything up to that token, and the last one
+    /// everything left, as long as no `,` or `;` outside of any brackets separates two
+    /// expressions there. One followed by another variable matches a single token tree
+    fn match_arguments(
+        &self,
+        arguments: &[SourceToken],
+    ) -> Option<HashMap<String, Vec<SourceToken>>> {
+        let mut bindings: HashMap<String, Vec<SourceToken>> = HashMap::new();
+        let mut position: usize = 0;
+        for (index, (token, _)) in self.pattern.iter().enumerate() {
+            let variable: &str = match token {
+                Token::MacroVariable(variable) => variable,
+                literal => {
+                    if arguments.get(position).map(|(token, _)| token) != Some(literal) {
+                        return None;
+                    }
+                    position += 1;
+                    continue;
+                }
+            };
+
+            let end: usize = match self.pattern.get(index + 1) {
+                Some((Token::MacroVariable(_), _)) => tree_end(arguments, position)?,
+                next => expression_end(arguments, position, next.map(|(token, _)| token))?,
+            };
+            if end <= position {
+                return None;
+            }
+            bindings.insert(variable.to_string(), arguments[position..end].to_vec());
+            position = end;
+        }
+        (position == arguments.len()).then_some(bindings)
+    }
+
+    /// The tokens a use of the macro at `call_site` expands to. Tokens from the template keep
+    /// their place in the definition, marked as coming from this expansion, while those given
+    /// as arguments keep their place at the call site. `expansion` numbers the expansion, giving
+    /// the names the template binds a name of their own in each one
+    pub fn expand(
+        &self,
+        arguments: &[SourceToken],
+        call_site: &SourceLocation,
+        expansion: usize,
+    ) -> Result<Vec<SourceToken>, CompilerError> {
+        let bindings: HashMap<String, Vec<SourceToken>> =
+            self.match_arguments(arguments).ok_or_else(|| {
+                CompilerError::MacroArgumentsError(self.name.clone(), self.location.clone())
+                    .with_location(call_site)
+            })?;
+
+        let mut tokens: Vec<SourceToken> = Vec::new();
+        let mut previous: Option<&Token> = None;
+        for (token, location) in &self.template {
+            let location: SourceLocation = location.in_expansion(&self.name, call_site);
+            match token {
+                Token::MacroVariable(variable) => {
+                    let argument: Vec<SourceToken> = bindings[variable].clone();
+                    tokens.extend(Self::grouped(argument, &location));
+                }
+                // a field is named after a `.`, and never refers to a binding
+                Token::Identifier(name)
+                    if self.binders.contains(name) && previous != Some(&Token::Dot) =>
+                {
+                    let renamed: String = format!("{}#{}", name, expansion);
+                    tokens.push((Token::Identifier(renamed), location));
+                }
+                token => tokens.push((token.clone(), location)),
+            }
+            previous = Some(token);
+        }
+        Ok(Self::grouped(tokens, call_site))
+    }
+
+    /// `tokens` as a single token tree, wrapped in parentheses unless they already are one, so
+    /// the operators around them cannot split them up
+    fn grouped(tokens: Vec<SourceToken>, location: &SourceLocation) -> Vec<SourceToken> {
+        if tree_end(&tokens, 0) == Some(tokens.len()) {
+            return tokens;
+        }
+        let mut grouped: Vec<SourceToken> = vec![(Token::LeftParenthesis, location.clone())];
+        grouped.extend(tokens);
+        grouped.push((Token::RightParenthesis, location.clone()));
+        grouped
+    }
+}
@@ -0,0 +1,633 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    error::CompilerError, source_location::SourceLocation, types::Type, warning::CompilerWarning,
+};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::{
+        constants::constant_definition::ConstantDefinition,
+        functions::{
+            attribute::AttributeKind,
+            function_definition::Function,
+            function_prototype::{FunctionPrototype, Parameter},
+        },
+        types::{enum_definition::EnumDefinition, struct_definition::StructDefinition},
+    },
+    builtins::builtin_signatures,
+    constant_evaluator::{ConstantEvaluator, ConstantValue},
+    differentiator::{Differentiator, MATH_FUNCTIONS},
+    enum_signature::{EnumSignature, VariantSignature},
+    function_signature::FunctionSignature,
+    module_loader::{Import, ModuleInterface},
+    scope::Scope,
+    struct_signature::StructSignature,
+    type_context::TypeContext,
+    unit_context::{Measure, UnitContext},
+};
+
+/// Every callable function in a module, keyed by function name
+pub type FunctionTable = HashMap<String, FunctionSignature>;
+
+/// Every struct declared in a module, keyed by struct name
+pub type StructTable = HashMap<String, StructSignature>;
+
+/// Every enum declared in a module, keyed by enum name
+pub type EnumTable = HashMap<String, EnumSignature>;
+
+/// Everything parsed out of a single source file
+pub struct Module {
+    /// The name given by a `module` declaration, if the file has one
+    name: Option<String>,
+    imports: Vec<Import>,
+    /// What each imported module provides, once the imports have been loaded
+    interfaces: Vec<ModuleInterface>,
+    functions: Vec<Function>,
+    externs: Vec<FunctionPrototype>,
+    structs: Vec<StructDefinition>,
+    enums: Vec<EnumDefinition>,
+    constants: Vec<ConstantDefinition>,
+    top_level_expressions: Vec<Function>,
+}
+
+impl Module {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            imports: Vec::new(),
+            interfaces: Vec::new(),
+            functions: Vec::new(),
+            externs: Vec::new(),
+            structs: Vec::new(),
+            enums: Vec::new(),
+            constants: Vec::new(),
+            top_level_expressions: Vec::new(),
+        }
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
+    pub fn add_import(&mut self, import: Import) {
+        self.imports.push(import);
+    }
+
+    pub fn add_interface(&mut self, interface: ModuleInterface) {
+        self.interfaces.push(interface);
+    }
+
+    pub fn add_function(&mut self, function: Function) {
+        self.functions.push(function);
+    }
+
+    pub fn add_extern(&mut self, prototype: FunctionPrototype) {
+        self.externs.push(prototype);
+    }
+
+    pub fn add_struct(&mut self, definition: StructDefinition) {
+        self.structs.push(definition);
+    }
+
+    pub fn add_enum(&mut self, definition: EnumDefinition) {
+        self.enums.push(definition);
+    }
+
+    pub fn add_constant(&mut self, definition: ConstantDefinition) {
+        self.constants.push(definition);
+    }
+
+    pub fn add_top_level_expression(&mut self, expression: Function) {
+        self.top_level_expressions.push(expression);
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn imports(&self) -> &[Import] {
+        &self.imports
+    }
+
+    /// Whether anything has been declared in the module yet
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty()
+            && self.functions.is_empty()
+            && self.externs.is_empty()
+            && self.structs.is_empty()
+            && self.enums.is_empty()
+            && self.constants.is_empty()
+            && self.top_level_expressions.is_empty()
+    }
+
+    pub fn functions(&self) -> &[Function] {
+        &self.functions
+    }
+
+    pub fn externs(&self) -> &[FunctionPrototype] {
+        &self.externs
+    }
+
+    pub fn structs(&self) -> &[StructDefinition] {
+        &self.structs
+    }
+
+    pub fn enums(&self) -> &[EnumDefinition] {
+        &self.enums
+    }
+
+    pub fn constants(&self) -> &[ConstantDefinition] {
+        &self.constants
+    }
+
+    pub fn top_level_expressions(&self) -> &[Function] {
+        &self.top_level_expressions
+    }
+
+    /// The builtins along with every extern declared in the module, every public function of
+    /// the modules it imports and the constructor of every enum variant
+    fn extern_table(&self) -> FunctionTable {
+        let mut table: FunctionTable = HashMap::new();
+        for signature in builtin_signatures() {
+            table.insert(signature.name().to_string(), signature);
+        }
+        for interface in &self.interfaces {
+            for signature in interface.functions() {
+                table.insert(signature.name().to_string(), signature.clone());
+            }
+        }
+        // nothing is known of what an extern does, unless it is declared @pure
+        for prototype in &self.externs {
+            let signature: FunctionSignature = prototype.signature();
+            let side_effect: Option<String> = match signature.attribute(AttributeKind::Pure) {
+                Some(_) => None,
+                None => Some(prototype.name().to_string()),
+            };
+            table.insert(
+                prototype.name().to_string(),
+                signature.with_side_effect(side_effect),
+            );
+        }
+        for variant in self.variants() {
+            table.insert(variant.name().to_string(), variant.constructor());
+        }
+        table
+    }
+
+    /// Every variant of every enum declared in or imported into the module
+    fn variants(&self) -> Vec<VariantSignature> {
+        self.enum_table()
+            .values()
+            .flat_map(|signature| signature.variants().to_vec())
+            .collect()
+    }
+
+    pub fn function_table(&self) -> FunctionTable {
+        let mut table: FunctionTable = self.extern_table();
+        for function in &self.functions {
+            let prototype: &FunctionPrototype = function.prototype();
+            table.insert(prototype.name().to_string(), prototype.signature());
+        }
+        table
+    }
+
+    /// Every struct declared in the module or any module it imports
+    pub fn struct_table(&self) -> StructTable {
+        let imported = self
+            .interfaces
+            .iter()
+            .flat_map(|interface| interface.structs().iter().cloned());
+        imported
+            .chain(self.structs.iter().map(|definition| definition.signature()))
+            .map(|signature| (signature.name().to_string(), signature))
+            .collect()
+    }
+
+    /// Every enum declared in the module or any module it imports
+    pub fn enum_table(&self) -> EnumTable {
+        let imported = self
+            .interfaces
+            .iter()
+            .flat_map(|interface| interface.enums().iter().cloned());
+        imported
+            .chain(self.enums.iter().map(|definition| definition.signature()))
+            .map(|signature| (signature.name().to_string(), signature))
+            .collect()
+    }
+
+    /// Report every function `interface` exports that an interface imported before it also
+    /// exports, at the import `location`, and every one the module declares itself, where it
+    /// declares it. Either way one of the two would silently hide the other
+    pub fn check_import(
+        &self,
+        interface: &ModuleInterface,
+        location: &SourceLocation,
+    ) -> Vec<CompilerError> {
+        let mut errors: Vec<CompilerError> = Vec::new();
+        for name in interface
+            .functions()
+            .iter()
+            .map(|signature| signature.name())
+        {
+            let earlier: Option<&ModuleInterface> = self.interfaces.iter().find(|earlier| {
+                earlier.name() != interface.name()
+                    && earlier.functions().iter().any(|f| f.name() == name)
+            });
+            if let Some(earlier) = earlier {
+                errors.push(
+                    CompilerError::AmbiguousImportError(
+                        name.to_string(),
+                        earlier.name().to_string(),
+                        interface.name().to_string(),
+                    )
+                    .with_location(location),
+                );
+            }
+
+            let declared: Option<&SourceLocation> = self
+                .functions
+                .iter()
+                .map(|function| function.prototype())
+                .chain(self.externs.iter())
+                .find(|prototype| prototype.name() == name)
+                .map(|prototype| prototype.location());
+            if let Some(declared) = declared {
+                errors.push(
+                    CompilerError::ImportConflictError(
+                        name.to_string(),
+                        interface.name().to_string(),
+                    )
+                    .with_location(declared),
+                );
+            }
+        }
+        errors
+    }
+
+    /// Whether a function or an extern named `name` is declared in the module
+    pub fn declares_function(&self, name: &str) -> bool {
+        self.functions
+            .iter()
+            .any(|function| function.prototype().name() == name)
+            || self
+                .externs
+                .iter()
+                .any(|prototype| prototype.name() == name)
+    }
+
+    /// Whether `name` is a builtin type, or a struct or enum declared in or imported into the
+    /// module
+    pub fn declares_type(&self, name: &str) -> bool {
+        Type::from_name(name).is_some()
+            || self.structs.iter().any(|s| s.name() == name)
+            || self.enums.iter().any(|e| e.name() == name)
+            || self.interfaces.iter().any(|interface| {
+                interface.structs().iter().any(|s| s.name() == name)
+                    || interface.enums().iter().any(|e| e.name() == name)
+            })
+    }
+
+    /// Point out functions an imported module defines but does not make public, rather than
+    /// reporting them as undefined
+    fn explain_private(&self, error: CompilerError) -> CompilerError {
+        let name: &str = match error.root_cause() {
+            CompilerError::UndefinedFunctionError(name)
+            | CompilerError::UndefinedVariableError(name) => name,
+            _ => return error,
+        };
+        match self
+            .interfaces
+            .iter()
+            .find(|interface| interface.hides(name))
+        {
+            Some(interface) => {
+                let private: CompilerError = CompilerError::PrivateFunctionError(
+                    name.to_string(),
+                    interface.name().to_string(),
+                );
+                match error.location() {
+                    Some(location) => private.with_location(location),
+                    None => private,
+                }
+            }
+            None => error,
+        }
+    }
+
+    /// Check that every name used in the module is bound. Functions can be referred to before
+    /// they are defined, so this runs once the whole file has been parsed
+    pub fn check_scope(&self) -> Vec<CompilerError> {
+        let mut scope: Scope = Scope::new();
+        for name in self.function_table().keys() {
+            scope.declare_function(name);
+        }
+        for variant in self.variants() {
+            scope.declare_constructor(variant.name());
+        }
+        for constant in &self.constants {
+            scope.declare_constant(constant.name());
+        }
+
+        // names are checked past the first one found unbound, so every error is reported at
+        // the name itself
+        let mut errors: Vec<CompilerError> = Vec::new();
+        for constant in &self.constants {
+            let result = constant.check_scope(&mut scope);
+            errors.append(&mut scope.take_errors());
+            if let Err(e) = result {
+                errors.push(e.with_location(constant.location()));
+            }
+        }
+        for function in self.functions.iter().chain(&self.top_level_expressions) {
+            let result = function.check_scope(&mut scope);
+            errors.append(&mut scope.take_errors());
+            if let Err(e) = result {
+                errors.push(e.with_location(function.prototype().location()));
+            }
+        }
+        errors
+            .into_iter()
+            .map(|error| self.explain_private(error))
+            .collect()
+    }
+
+    /// Report every field holding a value of the struct it belongs to, directly or through other
+    /// structs and tuples. Such a struct could only be built from one that already exists, so
+    /// only an array, which can be empty, can hold it
+    pub fn check_recursive_structs(&self) -> Vec<CompilerError> {
+        let mut errors: Vec<CompilerError> = Vec::new();
+        for definition in &self.structs {
+            for field in definition.fields() {
+                let mut visited: HashSet<String> = HashSet::new();
+                if let Some(annotation) = field.annotation() {
+                    if self.holds_struct(annotation, definition.name(), &mut visited) {
+                        errors.push(
+                            CompilerError::RecursiveFieldError(
+                                definition.name().to_string(),
+                                field.name().to_string(),
+                            )
+                            .with_location(field.location()),
+                        );
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    /// Whether a value of type `t` holds a value of the struct `name` in place. Arrays,
+    /// generators and functions only refer to their values, so they are not looked into
+    fn holds_struct(&self, t: &Type, name: &str, visited: &mut HashSet<String>) -> bool {
+        match t {
+            Type::Named(named) if named == name => true,
+            Type::Named(named) if visited.insert(named.clone()) => self
+                .structs
+                .iter()
+                .filter(|definition| definition.name() == named)
+                .flat_map(|definition| definition.fields())
+                .filter_map(|field| field.annotation())
+                .any(|annotation| self.holds_struct(annotation, name, visited)),
+            Type::Tuple(elements) => elements
+                .iter()
+                .any(|element| self.holds_struct(element, name, visited)),
+            Type::Result(value, error) => {
+                self.holds_struct(value, name, visited) || self.holds_struct(error, name, visited)
+            }
+            _ => false,
+        }
+    }
+
+    /// Check every call in the module against the prototype it names. Functions can be called
+    /// before they are defined, so this runs once the whole file has been parsed
+    pub fn check_calls(&self) -> Vec<CompilerError> {
+        let table: FunctionTable = self.function_table();
+        let constant_errors = self.constants.iter().filter_map(|constant| {
+            constant
+                .check_calls(&table)
+                .err()
+                .map(|e| e.with_location(constant.location()))
+        });
+        let function_errors = self
+            .functions
+            .iter()
+            .chain(&self.top_level_expressions)
+            .filter_map(|function| function.check_calls(&table).err());
+        constant_errors.chain(function_errors).collect()
+    }
+
+    /// The value of every constant that could be evaluated, in the order they were declared,
+    /// along with the errors found in the others
+    pub fn evaluate_constants(&self) -> (Vec<(String, ConstantValue)>, Vec<CompilerError>) {
+        ConstantEvaluator::new(&self.constants).evaluate_all(&self.constants)
+    }
+
+    /// Infer and check the types of every item in the module, returning the inferred signature
+    /// of each function in the order they were defined. Should only run once parsing and call
+    /// checking found no errors
+    pub fn check_types(&self) -> (Vec<FunctionSignature>, Vec<CompilerError>) {
+        let (signatures, errors, _) = self.check_types_with_warnings();
+        (signatures, errors)
+    }
+
+    /// Check types as `check_types` does, also returning the warnings found on the way, such
+    /// as calls to deprecated functions
+    pub fn check_types_with_warnings(
+        &self,
+    ) -> (
+        Vec<FunctionSignature>,
+        Vec<CompilerError>,
+        Vec<CompilerWarning>,
+    ) {
+        // functions can be called before they are defined, so each one is inferred the first
+        // time it is needed
+        let pending: HashMap<String, &Function> = self
+            .functions
+            .iter()
+            .map(|function| (function.prototype().name().to_string(), function))
+            .collect();
+        let mut context: TypeContext = TypeContext::new(
+            self.extern_table(),
+            self.struct_table(),
+            self.enum_table(),
+            pending,
+        );
+
+        // constants are evaluated before anything else, and have the type of their value
+        let (values, constant_errors) = self.evaluate_constants();
+        for constant in &self.constants {
+            match values.iter().find(|(name, _)| name == constant.name()) {
+                Some((_, value)) => context.declare_constant(constant.name(), value.clone()),
+                None => {
+                    let constant_type: Type = match constant.annotation() {
+                        Some(annotation) => annotation.clone(),
+                        None => context.fresh_variable(),
+                    };
+                    context.declare_variable(constant.name(), constant_type);
+                }
+            }
+        }
+
+        for prototype in &self.externs {
+            context.push_frame();
+            if let Err(e) = prototype.check_types(&mut context) {
+                context.record_error(e);
+            }
+            context.pop_frame();
+        }
+
+        for function in &self.functions {
+            context.infer_pending(function.prototype().name());
+        }
+
+        // top level expressions have no declared return type, any value is fine
+        for expression in &self.top_level_expressions {
+            context.push_frame();
+            if let Err(e) = expression.body().check_types(&mut context) {
+                context.record_error(e);
+            }
+            context.pop_frame();
+        }
+
+        let signatures: Vec<FunctionSignature> = self
+            .functions
+            .iter()
+            .filter_map(|function| context.signature(function.prototype().name()).cloned())
+            .collect();
+        let mut errors: Vec<CompilerError> = constant_errors;
+        errors.append(&mut context.take_errors());
+        (signatures, errors, context.take_warnings())
+    }
+
+    /// Check that every value is used consistently with its unit of measure. Units are only
+    /// known from numbers written with one and from what is worked out of them. A function is
+    /// checked with its parameters in any unit, and again for every call with the units of its
+    /// arguments, which gives the unit of what the call returns. Should only run once type
+    /// checking found no errors
+    pub fn check_units(&self) -> Vec<CompilerError> {
+        let mut context: UnitContext = UnitContext::new().with_functions(&self.functions);
+
+        // constants can be used before they are declared, so their units are worked out again
+        // until none of them changes
+        for _ in 0..=self.constants.len() {
+            let mut changed: bool = false;
+            for constant in &self.constants {
+                let measure: Measure = constant
+                    .check_units(&mut context)
+                    .unwrap_or(Measure::Unknown);
+                if context.lookup(constant.name()) != measure {
+                    context.declare(constant.name(), measure);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let constant_errors: Vec<CompilerError> = self
+            .constants
+            .iter()
+            .filter_map(|constant| {
+                constant
+                    .check_units(&mut context)
+                    .err()
+                    .map(|e| e.with_location(constant.location()))
+            })
+            .collect();
+        let function_errors = self
+            .functions
+            .iter()
+            .chain(&self.top_level_expressions)
+            .filter_map(|function| {
+                function
+                    .check_units(&mut context.definition_context())
+                    .err()
+                    .map(|e| e.with_location(function.prototype().location()))
+            });
+        constant_errors.into_iter().chain(function_errors).collect()
+    }
+
+    /// The derivative of the function `name` with respect to its parameter `parameter`, which
+    /// can be left out when the function takes a single one. The derivative takes the same
+    /// parameters, is named `d{name}_d{parameter}`, and can call any extern in
+    /// [`MATH_FUNCTIONS`] the module declares
+    pub fn derivative(
+        &self,
+        name: &str,
+        parameter: Option<&str>,
+    ) -> Result<Function, CompilerError> {
+        let function: &Function = self
+            .functions
+            .iter()
+            .find(|function| function.prototype().name() == name)
+            .ok_or_else(|| CompilerError::UndefinedFunctionError(name.to_string()))?;
+        let prototype: &FunctionPrototype = function.prototype();
+        let parameter: &str = match (parameter, prototype.args()) {
+            (Some(parameter), args) if args.iter().any(|arg| arg.name() == parameter) => parameter,
+            (Some(parameter), _) => {
+                return Err(CompilerError::UnknownNamedArgumentError(
+                    name.to_string(),
+                    parameter.to_string(),
+                ))
+            }
+            (None, [arg]) => arg.name(),
+            (None, _) => return Err(CompilerError::DerivativeParameterError(name.to_string())),
+        };
+
+        let parameters: Vec<&str> = prototype.args().iter().map(|arg| arg.name()).collect();
+        let math_functions: HashSet<String> = self
+            .externs
+            .iter()
+            .map(|prototype| prototype.name().to_string())
+            .filter(|name| MATH_FUNCTIONS.contains(&name.as_str()))
+            .collect();
+        let mut differentiator: Differentiator = Differentiator::new(
+            &parameters,
+            parameter,
+            math_functions,
+            prototype.location().clone(),
+        );
+        let body: Box<dyn ASTNode> = function
+            .body()
+            .differentiate(&mut differentiator)
+            .map_err(|e| e.with_location(prototype.location()))?
+            .into_tangent();
+
+        let args: Vec<Parameter> = prototype
+            .args()
+            .iter()
+            .map(|arg| {
+                Parameter::new(
+                    arg.name(),
+                    arg.annotation().cloned(),
+                    None,
+                    arg.location().clone(),
+                )
+            })
+            .collect();
+        let derivative_name: String = format!("d{}_d{}", name, parameter);
+        if self.declares_function(&derivative_name) {
+            return Err(CompilerError::DuplicateFunctionError(derivative_name)
+                .with_location(prototype.location()));
+        }
+        let derivative: FunctionPrototype = FunctionPrototype::new(
+            &derivative_name,
+            args,
+            Some(Type::F64),
+            prototype.location().clone(),
+        )
+        .with_type_parameters(prototype.type_parameters().to_vec());
+        Ok(Function::new(
+            Box::new(derivative),
+            body,
+            prototype.location().clone(),
+        ))
+    }
+}
+
+impl Default for Module {
+    fn default() -> Self {
+        Self::new()
+    }
+}
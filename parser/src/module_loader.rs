@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::{error::CompilerError, source_location::SourceLocation};
+use lexer::lexer::Lexer;
+
+use crate::{
+    ast::Ast, enum_signature::EnumSignature, function_signature::FunctionSignature, module::Module,
+    struct_signature::StructSignature,
+};
+
+/// `import math.helpers`, naming a module by its path with `.` between directories
+#[derive(Clone)]
+pub struct Import {
+    path: Vec<String>,
+    location: SourceLocation,
+}
+
+impl Import {
+    pub fn new(path: Vec<String>, location: SourceLocation) -> Self {
+        Self { path, location }
+    }
+
+    pub fn name(&self) -> String {
+        self.path.join(".")
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+
+    /// Whether a module declared as `declared` can be imported this way. A module can be found
+    /// from more than one directory, so the import only has to give the end of its name
+    fn matches(&self, declared: &str) -> bool {
+        let name: String = self.name();
+        declared == name || declared.ends_with(&format!(".{}", name))
+    }
+
+    /// Where the module's file sits relative to the directory it is looked up in
+    fn relative_path(&self) -> PathBuf {
+        let mut path: PathBuf = self.path.iter().collect();
+        path.set_extension("fr");
+        path
+    }
+}
+
+/// What a module makes available to the modules importing it: the signatures of its `pub`
+/// functions, and every type its signatures could mention
+#[derive(Clone)]
+pub struct ModuleInterface {
+    name: String,
+    functions: Vec<FunctionSignature>,
+    private_functions: Vec<String>,
+    structs: Vec<StructSignature>,
+    enums: Vec<EnumSignature>,
+}
+
+impl ModuleInterface {
+    /// The interface of `module` given the signatures inferred for its functions. Functions
+    /// whose signature could not be inferred are exported as declared
+    fn new(name: &str, module: &Module, signatures: &[FunctionSignature]) -> Self {
+        let (public, private): (Vec<_>, Vec<_>) = module
+            .functions()
+            .iter()
+            .partition(|function| function.is_public());
+        let functions: Vec<FunctionSignature> = public
+            .iter()
+            .map(|function| {
+                let prototype = function.prototype();
+                signatures
+                    .iter()
+                    .find(|signature| signature.name() == prototype.name())
+                    .cloned()
+                    .unwrap_or_else(|| prototype.signature())
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            functions,
+            private_functions: private
+                .iter()
+                .map(|function| function.prototype().name().to_string())
+                .collect(),
+            structs: module.struct_table().into_values().collect(),
+            enums: module.enum_table().into_values().collect(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn functions(&self) -> &[FunctionSignature] {
+        &self.functions
+    }
+
+    /// Whether the module defines `name` without making it public
+    pub fn hides(&self, name: &str) -> bool {
+        self.private_functions.iter().any(|private| private == name)
+    }
+
+    pub fn structs(&self) -> &[StructSignature] {
+        &self.structs
+    }
+
+    pub fn enums(&self) -> &[EnumSignature] {
+        &self.enums
+    }
+}
+
+/// Finds, parses and checks the modules a file imports. An import is looked up relative to
+/// the importing file first, then under each search root in order. Every module is only
+/// loaded once, however many files import it
+pub struct ModuleLoader {
+    search_roots: Vec<PathBuf>,
+    verbose: bool,
+    /// Whether the contracts of functions are kept, or left out as in a release build
+    contracts: bool,
+    interfaces: HashMap<PathBuf, ModuleInterface>,
+    /// The files being loaded, each importing the next, used to report import cycles
+    loading: Vec<(PathBuf, String)>,
+}
+
+impl ModuleLoader {
+    pub fn new(search_roots: Vec<PathBuf>, verbose: bool) -> Self {
+        Self {
+            search_roots,
+            verbose,
+            contracts: true,
+            interfaces: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    /// The same loader, keeping the contracts of functions in every module it loads or leaving
+    /// them out
+    pub fn with_contracts(mut self, enabled: bool) -> Self {
+        self.contracts = enabled;
+        self
+    }
+
+    /// Parse `file` along with everything it imports
+    pub fn load(&mut self, file: PathBuf) -> Result<(Module, Vec<CompilerError>), CompilerError> {
+        let mut lexer: Lexer = Lexer::init();
+        lexer.new_file(file)?;
+        let mut ast: Ast = Ast::new(&mut lexer, self.verbose).with_contracts(self.contracts);
+        Ok(ast.parse_tokens_with(self))
+    }
+
+    /// Mark `file`, known as `name`, as being loaded until the matching call to `leave`
+    pub fn enter(&mut self, file: &Path, name: &str) {
+        self.loading.push((Self::identity(file), name.to_string()));
+    }
+
+    pub fn leave(&mut self) {
+        self.loading.pop();
+    }
+
+    /// The interface of the module `import` names, along with any errors found in it the first
+    /// time it was loaded
+    pub fn import(
+        &mut self,
+        import: &Import,
+        importing_file: &Path,
+    ) -> Result<(ModuleInterface, Vec<CompilerError>), CompilerError> {
+        let file: PathBuf = Self::identity(&self.resolve(import, importing_file)?);
+        if let Some(start) = self
+            .loading
+            .iter()
+            .position(|(loading, _)| *loading == file)
+        {
+            let mut cycle: Vec<String> = self.loading[start..]
+                .iter()
+                .map(|(_, name)| name.clone())
+                .collect();
+            cycle.push(import.name());
+            return Err(CompilerError::CircularImportError(cycle));
+        }
+        if let Some(interface) = self.interfaces.get(&file) {
+            return Ok((interface.clone(), Vec::new()));
+        }
+
+        let (module, mut errors) = self.load(file.clone())?;
+        if let Some(declared) = module.name() {
+            if !import.matches(declared) {
+                return Err(CompilerError::ModuleNameMismatchError(
+                    declared.to_string(),
+                    import.name(),
+                ));
+            }
+        }
+
+        let mut signatures: Vec<FunctionSignature> = Vec::new();
+        if errors.is_empty() {
+            let (inferred, mut type_errors) = module.check_types();
+            signatures = inferred;
+            if type_errors.is_empty() {
+                type_errors = module.check_units();
+            }
+            errors.append(&mut type_errors);
+        }
+        let interface: ModuleInterface = ModuleInterface::new(&import.name(), &module, &signatures);
+        self.interfaces.insert(file, interface.clone());
+        Ok((interface, errors))
+    }
+
+    /// The file `import` refers to: the first one found next to the importing file or under
+    /// one of the search roots
+    fn resolve(&self, import: &Import, importing_file: &Path) -> Result<PathBuf, CompilerError> {
+        let relative: PathBuf = import.relative_path();
+        let importing_directory: &Path = importing_file.parent().unwrap_or(Path::new(""));
+        let candidates: Vec<PathBuf> = std::iter::once(importing_directory)
+            .chain(self.search_roots.iter().map(|root| root.as_path()))
+            .map(|directory| directory.join(&relative))
+            .collect();
+
+        match candidates.iter().find(|candidate| candidate.is_file()) {
+            Some(found) => Ok(found.clone()),
+            None => Err(CompilerError::ModuleNotFoundError(
+                import.name(),
+                candidates,
+            )),
+        }
+    }
+
+    /// The same path for every way of spelling a file's location
+    fn identity(file: &Path) -> PathBuf {
+        fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf())
+    }
+}
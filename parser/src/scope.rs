@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use common::error::CompilerError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mutability {
+    Immutable,
+    Mutable,
+}
+
+/// What a name in scope refers to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    Variable(Mutability),
+    Function,
+    /// The constructor of an enum variant, which can also be used as a pattern
+    Constructor,
+    /// A module level constant, visible everywhere in the module
+    Constant,
+}
+
+/// A stack of lexical frames mapping names to how they were bound. Function parameters are
+/// immutable, names introduced with `var` are mutable, and inner frames shadow outer ones.
+/// Functions and constants are bound too so they can be used as values, but can never be
+/// assigned to
+pub struct Scope {
+    frames: Vec<HashMap<String, Binding>>,
+    /// Names found unbound or assigned to when they cannot be, kept so that checking carries
+    /// on past them
+    errors: Vec<CompilerError>,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Self {
+            frames: vec![HashMap::new()],
+            errors: Vec::new(),
+        }
+    }
+
+    /// The scope the body of a function defined here is checked in. Definitions cannot capture
+    /// variables, so only the functions, constructors and constants visible here carry over
+    pub fn definition_scope(&self) -> Scope {
+        let functions: HashMap<String, Binding> = self
+            .frames
+            .iter()
+            .flatten()
+            .filter(|(_, binding)| !matches!(binding, Binding::Variable(_)))
+            .map(|(name, binding)| (name.clone(), *binding))
+            .collect();
+        Self {
+            frames: vec![functions],
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub fn declare(&mut self, name: &str, mutability: Mutability) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), Binding::Variable(mutability));
+        }
+    }
+
+    pub fn declare_function(&mut self, name: &str) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), Binding::Function);
+        }
+    }
+
+    pub fn declare_constructor(&mut self, name: &str) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), Binding::Constructor);
+        }
+    }
+
+    pub fn declare_constant(&mut self, name: &str) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), Binding::Constant);
+        }
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Binding> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(name).copied())
+    }
+
+    /// What `name` refers to once local variables are left out: a function, constructor or
+    /// constant of the module
+    pub fn lookup_top_level(&self, name: &str) -> Option<Binding> {
+        self.frames.iter().rev().find_map(|frame| {
+            frame
+                .get(name)
+                .copied()
+                .filter(|binding| !matches!(binding, Binding::Variable(_)))
+        })
+    }
+
+    pub fn record_error(&mut self, error: CompilerError) {
+        self.errors.push(error);
+    }
+
+    pub fn take_errors(&mut self) -> Vec<CompilerError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn check_read(&self, name: &str) -> Result<(), CompilerError> {
+        match self.lookup(name) {
+            Some(_) => Ok(()),
+            None => Err(CompilerError::UndefinedVariableError(name.to_string())),
+        }
+    }
+
+    pub fn check_top_level_read(&self, name: &str) -> Result<(), CompilerError> {
+        match self.lookup_top_level(name) {
+            Some(_) => Ok(()),
+            None => Err(CompilerError::UndefinedVariableError(name.to_string())),
+        }
+    }
+
+    pub fn check_write(&self, name: &str) -> Result<(), CompilerError> {
+        match self.lookup(name) {
+            Some(Binding::Variable(Mutability::Mutable)) => Ok(()),
+            Some(
+                Binding::Variable(Mutability::Immutable)
+                | Binding::Function
+                | Binding::Constructor
+                | Binding::Constant,
+            ) => Err(CompilerError::AssignmentToImmutableError(name.to_string())),
+            None => Err(CompilerError::UndefinedVariableError(name.to_string())),
+        }
+    }
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
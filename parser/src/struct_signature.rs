@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+use common::{source_location::SourceLocation, types::Type};
+
+#[derive(Clone)]
+pub struct FieldSignature {
+    name: String,
+    field_type: Type,
+    location: SourceLocation,
+}
+
+impl FieldSignature {
+    pub fn new(name: &str, field_type: Type, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            field_type,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn field_type(&self) -> &Type {
+        &self.field_type
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
+/// The fields of a struct in declaration order, along with their types
+#[derive(Clone)]
+pub struct StructSignature {
+    name: String,
+    fields: Vec<FieldSignature>,
+    location: SourceLocation,
+}
+
+impl StructSignature {
+    pub fn new(name: &str, fields: Vec<FieldSignature>, location: SourceLocation) -> Self {
+        Self {
+            name: name.to_string(),
+            fields,
+            location,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &[FieldSignature] {
+        &self.fields
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldSignature> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    pub fn location(&self) -> &SourceLocation {
+        &self.location
+    }
+}
+
+/// Prints the declaration, e.g. `Point { x: f64, y: f64 }`
+impl Display for StructSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name, field.field_type))
+            .collect();
+        write!(f, "{} {{ {} }}", self.name, fields.join(", "))
+    }
+}
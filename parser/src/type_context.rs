@@ -0,0 +1,967 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    error::CompilerError,
+    source_location::SourceLocation,
+    types::{Constraint, Type},
+    warning::{CompilerWarning, WarningKind},
+};
+
+use crate::{
+    ast_node::ASTNode,
+    ast_nodes::functions::{attribute::AttributeKind, function_definition::Function},
+    constant_evaluator::ConstantValue,
+    enum_signature::{EnumSignature, VariantSignature},
+    function_signature::{FunctionSignature, TypeParameter},
+    module::{EnumTable, FunctionTable, StructTable},
+    struct_signature::StructSignature,
+};
+
+/// What inference has learned about a single type variable
+#[derive(Default)]
+struct TypeVariable {
+    binding: Option<Type>,
+    /// Where the variable was bound, pointed at when a later use conflicts with it
+    origin: Option<SourceLocation>,
+    /// What the variable has to satisfy once bound, and where that was required
+    constraint: Option<(Constraint, SourceLocation)>,
+    /// The type the variable is given if nothing fixes it by the time its function is
+    /// generalized, e.g. i64 for the type of an integer literal
+    default: Option<Type>,
+}
+
+/// A function whose body is being inferred, along with the functions that could not be
+/// generalized before it is finished because they share its type variables
+struct Definition {
+    name: String,
+    group: Vec<String>,
+    /// The first function called or taken as a value by the body that may have side effects
+    side_effect: Option<String>,
+    calls_itself: bool,
+    /// The type of the values the body yields, once a `yield` has been seen. Only a generator
+    /// has one
+    yield_type: Option<Type>,
+}
+
+/// Types of the variables and functions visible at some point of the type inference pass.
+/// Frames are pushed for every construct that opens a new scope, and local definitions only
+/// live as long as the frame they were declared in.
+///
+/// Types are inferred by unification: anything not annotated starts out as a type variable and
+/// is bound as soon as some use pins it down. Function signatures are generalized once their
+/// body is inferred, so a definition like `def identity(x) x` can be called at any type
+pub struct TypeContext<'a> {
+    variables: Vec<HashMap<String, Type>>,
+    functions: Vec<FunctionTable>,
+    structs: StructTable,
+    enums: EnumTable,
+    type_variables: Vec<TypeVariable>,
+    /// Module level functions whose bodies have not been inferred yet
+    pending: HashMap<String, &'a Function>,
+    definitions: Vec<Definition>,
+    /// The type of the value each enclosing loop breaks with, innermost last
+    loops: Vec<Type>,
+    /// For every function and try around the expression being checked, innermost last, the
+    /// type of the errors a `?` hands to it, once one has
+    handlers: Vec<Option<Type>>,
+    /// Errors found while inferring a pending function on behalf of one of its callers
+    errors: Vec<CompilerError>,
+    /// Problems worth pointing out that do not stop the module from compiling
+    warnings: Vec<CompilerWarning>,
+    /// The value of every module level constant that could be evaluated
+    constants: HashMap<String, ConstantValue>,
+}
+
+impl<'a> TypeContext<'a> {
+    pub fn new(
+        functions: FunctionTable,
+        structs: StructTable,
+        enums: EnumTable,
+        pending: HashMap<String, &'a Function>,
+    ) -> Self {
+        Self {
+            variables: vec![HashMap::new()],
+            functions: vec![functions],
+            structs,
+            enums,
+            type_variables: Vec::new(),
+            pending,
+            definitions: Vec::new(),
+            loops: Vec::new(),
+            handlers: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            constants: HashMap::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self) {
+        self.variables.push(HashMap::new());
+        self.functions.push(HashMap::new());
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.variables.pop();
+        self.functions.pop();
+    }
+
+    pub fn declare_variable(&mut self, name: &str, variable_type: Type) {
+        if let Some(frame) = self.variables.last_mut() {
+            frame.insert(name.to_string(), variable_type);
+        }
+    }
+
+    pub fn declare_function(&mut self, signature: FunctionSignature) {
+        if let Some(frame) = self.functions.last_mut() {
+            frame.insert(signature.name().to_string(), signature);
+        }
+    }
+
+    /// Enter a loop whose `break`s give a value of type `value_type`
+    pub fn push_loop(&mut self, value_type: Type) {
+        self.loops.push(value_type);
+    }
+
+    pub fn pop_loop(&mut self) {
+        self.loops.pop();
+    }
+
+    /// The type of the value the innermost loop breaks with
+    pub fn loop_type(&self) -> Option<&Type> {
+        self.loops.last()
+    }
+
+    /// Check a value yielded by the body of the innermost function, which makes the function a
+    /// generator. Every `yield` of a function has to give a value of the same type
+    pub fn record_yield(
+        &mut self,
+        found: &Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        let yield_type: Type = match self.definitions.last().map(|d| d.yield_type.clone()) {
+            Some(Some(yield_type)) => yield_type,
+            Some(None) => {
+                let yield_type: Type = self.fresh_variable();
+                if let Some(definition) = self.definitions.last_mut() {
+                    definition.yield_type = Some(yield_type.clone());
+                }
+                yield_type
+            }
+            None => return Err(CompilerError::YieldOutsideFunctionError.with_location(location)),
+        };
+        self.coerce(&yield_type, found, location)
+    }
+
+    /// The type of the values the innermost function yields, if it is a generator
+    pub fn yield_type(&self) -> Option<&Type> {
+        self.definitions.last()?.yield_type.as_ref()
+    }
+
+    /// Enter a function or a try, which the errors of any `?` in it are handed to
+    pub fn push_handler(&mut self) {
+        self.handlers.push(None);
+    }
+
+    pub fn pop_handler(&mut self) {
+        self.handlers.pop();
+    }
+
+    /// Hand an error of type `error_type` to the innermost function or try. Every `?` handing
+    /// errors to the same one has to give them the same type
+    pub fn record_propagation(
+        &mut self,
+        error_type: &Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        match self.handlers.last_mut() {
+            Some(Some(handled)) => {
+                let handled: Type = handled.clone();
+                self.unify(&handled, error_type, location)
+            }
+            Some(handled) => {
+                *handled = Some(error_type.clone());
+                Ok(())
+            }
+            None => Err(CompilerError::PropagationOutsideHandlerError.with_location(location)),
+        }
+    }
+
+    /// The type of the errors handed to the innermost function or try, if any were
+    pub fn error_type(&self) -> Option<&Type> {
+        self.handlers.last()?.as_ref()
+    }
+
+    /// A function whose body hands errors on with `?` returns them, so it has to return a
+    /// result holding errors of their type
+    pub fn check_returned_errors(
+        &mut self,
+        return_type: &Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        let error_type: Type = match self.error_type() {
+            Some(error_type) => error_type.clone(),
+            None => return Ok(()),
+        };
+        let value_type: Type = self.fresh_variable();
+        let result_type: Type = Type::Result(Box::new(value_type), Box::new(error_type));
+        self.unify(return_type, &result_type, location)
+    }
+
+    /// Declare a module level constant of known value, which is declared in the outermost frame
+    pub fn declare_constant(&mut self, name: &str, value: ConstantValue) {
+        if let Some(frame) = self.variables.first_mut() {
+            frame.insert(name.to_string(), value.value_type());
+        }
+        self.constants.insert(name.to_string(), value);
+    }
+
+    /// The type of the module level constant `name`, whatever variable hides it
+    pub fn constant_type(&self, name: &str) -> Option<Type> {
+        self.constants.get(name).map(|value| value.value_type())
+    }
+
+    /// The value of every constant a local variable does not hide at this point
+    pub fn constant_values(&self) -> HashMap<String, ConstantValue> {
+        self.constants
+            .iter()
+            .filter(|(name, _)| {
+                !self.variables[1..]
+                    .iter()
+                    .any(|frame| frame.contains_key(*name))
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Whether `name` refers to a variable rather than a function. Variables shadow functions
+    /// with the same name
+    pub fn is_variable(&self, name: &str) -> bool {
+        self.variables.iter().any(|frame| frame.contains_key(name))
+    }
+
+    pub fn variable_type(&self, name: &str) -> Result<Type, CompilerError> {
+        self.variables
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(name).cloned())
+            .ok_or_else(|| CompilerError::UndefinedVariableError(name.to_string()))
+    }
+
+    /// The signature as declared, without instantiating its type parameters
+    pub fn signature(&self, name: &str) -> Option<&FunctionSignature> {
+        self.functions
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(name))
+    }
+
+    /// The signature of the function `name` with fresh type variables for each of its type
+    /// parameters, ready to be unified with the arguments of one call
+    pub fn function(&mut self, name: &str) -> Result<FunctionSignature, CompilerError> {
+        if self.signature(name).is_none() {
+            self.infer_pending(name);
+        }
+
+        let signature: FunctionSignature = self
+            .signature(name)
+            .cloned()
+            .ok_or_else(|| CompilerError::UndefinedFunctionError(name.to_string()))?;
+        Ok(self.instantiate(&signature))
+    }
+
+    /// Infer a module level function the first time it is needed. Its body is inferred outside
+    /// of whatever local frames are open, and its errors are kept to be reported with the rest
+    pub fn infer_pending(&mut self, name: &str) {
+        let function: &'a Function = match self.pending.remove(name) {
+            Some(function) => function,
+            None => return,
+        };
+
+        let local_variables: Vec<HashMap<String, Type>> = self.variables.split_off(1);
+        let local_functions: Vec<FunctionTable> = self.functions.split_off(1);
+        if let Err(e) = function.check_types(self) {
+            self.errors.push(e);
+        }
+        self.variables.extend(local_variables);
+        self.functions.extend(local_functions);
+    }
+
+    pub fn struct_signature(&self, name: &str) -> Option<&StructSignature> {
+        self.structs.get(name)
+    }
+
+    /// Every struct that declares a field called `field`
+    pub fn structs_with_field(&self, field: &str) -> Vec<&StructSignature> {
+        self.structs
+            .values()
+            .filter(|signature| signature.field(field).is_some())
+            .collect()
+    }
+
+    pub fn enum_signature(&self, name: &str) -> Option<&EnumSignature> {
+        self.enums.get(name)
+    }
+
+    /// The enum variant `name` refers to, unless a variable with the same name shadows it
+    pub fn variant(&self, name: &str) -> Option<&VariantSignature> {
+        if self.is_variable(name) {
+            return None;
+        }
+        self.declared_variant(name)
+    }
+
+    /// The enum variant named `name`, whatever variable shadows it
+    pub fn declared_variant(&self, name: &str) -> Option<&VariantSignature> {
+        self.enums
+            .values()
+            .find_map(|signature| signature.variant(name))
+    }
+
+    pub fn record_error(&mut self, error: CompilerError) {
+        self.errors.push(error);
+    }
+
+    pub fn record_warning(&mut self, warning: CompilerWarning) {
+        self.warnings.push(warning);
+    }
+
+    pub fn take_errors(&mut self) -> Vec<CompilerError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<CompilerWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Note a reference from the body being inferred to the function `signature` describes,
+    /// taken as a value. It can be called from anywhere the value goes, so its side effects
+    /// count as the body's own
+    pub fn record_reference(&mut self, signature: &FunctionSignature) {
+        if signature.side_effect().is_some() {
+            self.record_side_effect(signature.name());
+        }
+    }
+
+    /// Note a call through the function value `callee` from the body being inferred. Nothing
+    /// is known of what the value does, so the call may have side effects
+    pub fn record_value_call(&mut self, callee: &str) {
+        self.record_side_effect(callee);
+    }
+
+    fn record_side_effect(&mut self, callee: &str) {
+        if let Some(caller) = self.definitions.last_mut() {
+            if caller.side_effect.is_none() {
+                caller.side_effect = Some(callee.to_string());
+            }
+        }
+    }
+
+    /// Note a call by name to the function `signature` describes, made at `location` from the
+    /// body being inferred. Calls to a deprecated function are warned about, unless it is
+    /// calling itself
+    pub fn record_call(&mut self, signature: &FunctionSignature, location: &SourceLocation) {
+        let caller: Option<&mut Definition> = self.definitions.last_mut();
+        let calls_itself: bool = caller
+            .as_ref()
+            .is_some_and(|caller| caller.name == signature.name());
+
+        if let Some(caller) = caller {
+            if calls_itself {
+                caller.calls_itself = true;
+            }
+        }
+        if !calls_itself && signature.side_effect().is_some() {
+            self.record_side_effect(signature.name());
+        }
+        if let Some(deprecated) = signature.attribute(AttributeKind::Deprecated) {
+            if !calls_itself {
+                self.warnings.push(CompilerWarning::new(
+                    WarningKind::DeprecatedCall(
+                        signature.name().to_string(),
+                        deprecated.message().map(|message| message.to_string()),
+                    ),
+                    location,
+                ));
+            }
+        }
+    }
+
+    pub fn fresh_variable(&mut self) -> Type {
+        self.type_variables.push(TypeVariable::default());
+        Type::Variable(self.type_variables.len() - 1)
+    }
+
+    /// A variable already bound to `bound_type`, so conflicts with it point back at `location`
+    pub fn fixed_variable(&mut self, bound_type: Type, location: &SourceLocation) -> Type {
+        self.type_variables.push(TypeVariable {
+            binding: Some(bound_type),
+            origin: Some(location.clone()),
+            constraint: None,
+            default: None,
+        });
+        Type::Variable(self.type_variables.len() - 1)
+    }
+
+    /// The type of an integer literal written at `location`: any numeric type, i64 unless
+    /// something makes it an f64
+    pub fn integer_literal(&mut self, location: &SourceLocation) -> Type {
+        self.type_variables.push(TypeVariable {
+            binding: None,
+            origin: None,
+            constraint: Some((Constraint::Numeric, location.clone())),
+            default: Some(Type::I64),
+        });
+        Type::Variable(self.type_variables.len() - 1)
+    }
+
+    /// Follow variable bindings until reaching a concrete type or a variable that is still unbound
+    pub fn resolve(&self, t: &Type) -> Type {
+        let mut current: &Type = t;
+        while let Type::Variable(id) = current {
+            match &self.type_variables[*id].binding {
+                Some(bound) => current = bound,
+                None => break,
+            }
+        }
+        current.clone()
+    }
+
+    /// Like `resolve_deep`, but a variable with a default type, such as that of an integer literal,
+    /// is taken to be of that type. Only a number can take its place, so a check of what kind
+    /// of value it was given can already tell it is not a tuple, an array or a function
+    pub fn resolve_shape(&self, t: &Type) -> Type {
+        match self.resolve(t) {
+            Type::Variable(id) => self.type_variables[id]
+                .default
+                .clone()
+                .unwrap_or(Type::Variable(id)),
+            _ => self.resolve_deep(t),
+        }
+    }
+
+    /// Like `resolve`, but also resolves the variables nested inside the type
+    pub fn resolve_deep(&self, t: &Type) -> Type {
+        match self.resolve(t) {
+            Type::Array(element) => Type::Array(Box::new(self.resolve_deep(&element))),
+            Type::Generator(element) => Type::Generator(Box::new(self.resolve_deep(&element))),
+            Type::Result(value, error) => Type::Result(
+                Box::new(self.resolve_deep(&value)),
+                Box::new(self.resolve_deep(&error)),
+            ),
+            Type::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(|t| self.resolve_deep(t)).collect())
+            }
+            Type::Function(parameters, return_type) => Type::Function(
+                parameters.iter().map(|t| self.resolve_deep(t)).collect(),
+                Box::new(self.resolve_deep(&return_type)),
+            ),
+            resolved => resolved,
+        }
+    }
+
+    /// Where the concrete type `current` resolves to was fixed, if it came through a variable
+    fn origin(&self, t: &Type) -> Option<&SourceLocation> {
+        let mut current: &Type = t;
+        while let Type::Variable(id) = current {
+            let variable: &TypeVariable = &self.type_variables[*id];
+            match &variable.binding {
+                Some(bound @ Type::Variable(_)) => current = bound,
+                Some(_) => return variable.origin.as_ref(),
+                None => return None,
+            }
+        }
+        None
+    }
+
+    /// Bind the unbound variable `id`. Constraints on the variable carry over to whatever it is
+    /// bound to
+    fn bind(
+        &mut self,
+        id: usize,
+        bound_type: Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        if bound_type == Type::Variable(id) {
+            return Ok(());
+        }
+        if self.resolve_deep(&bound_type).variables().contains(&id) {
+            return Err(CompilerError::InfiniteTypeError(
+                Type::Variable(id),
+                self.resolve_deep(&bound_type),
+            )
+            .with_location(location));
+        }
+
+        if let Type::Variable(other) = bound_type {
+            if self.type_variables[other].default.is_none() {
+                self.type_variables[other].default = self.type_variables[id].default.clone();
+            }
+        }
+        let constraint: Option<(Constraint, SourceLocation)> =
+            self.type_variables[id].constraint.clone();
+        match (&bound_type, constraint) {
+            (_, None) => {}
+            (Type::Variable(other), Some((constraint, required))) => {
+                match self.type_variables[*other].constraint.clone() {
+                    None => self.type_variables[*other].constraint = Some((constraint, required)),
+                    Some(existing) => {
+                        // a variable with a default, like the type of an integer literal, is
+                        // reported as the type it would be
+                        let default: Option<Type> = self.type_variables[*other].default.clone();
+                        let rejecting: Option<(Constraint, SourceLocation)> =
+                            [(constraint.clone(), required), existing.clone()]
+                                .into_iter()
+                                .find(|(constraint, _)| {
+                                    default.as_ref().is_some_and(|t| !constraint.accepts(t))
+                                });
+                        if let (Some(default), Some((constraint, required))) = (default, rejecting)
+                        {
+                            return Err(CompilerError::UnsatisfiedConstraintError(
+                                constraint, default, required,
+                            )
+                            .with_location(location));
+                        }
+                        self.merge_constraints(&existing.0, &constraint, location)?
+                    }
+                }
+            }
+            (concrete, Some((constraint, required))) => {
+                if !constraint.accepts(concrete) {
+                    return Err(CompilerError::UnsatisfiedConstraintError(
+                        constraint,
+                        self.resolve_deep(concrete),
+                        required,
+                    )
+                    .with_location(location));
+                }
+                if let (
+                    Constraint::Iterable(element),
+                    Type::Array(found) | Type::Generator(found),
+                ) = (&constraint, concrete)
+                {
+                    self.unify(element, found, location)?;
+                }
+            }
+        }
+
+        self.type_variables[id].binding = Some(bound_type);
+        self.type_variables[id].origin = Some(location.clone());
+        Ok(())
+    }
+
+    /// Require `required` of a variable already constrained by `existing`. Two iterables are
+    /// the same constraint once their elements are made the same type
+    fn merge_constraints(
+        &mut self,
+        existing: &Constraint,
+        required: &Constraint,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        match (existing, required) {
+            (Constraint::Iterable(existing), Constraint::Iterable(required)) => {
+                self.unify(existing, required, location)
+            }
+            (existing, required) if existing == required => Ok(()),
+            (existing, required) => Err(CompilerError::ConflictingConstraintsError(
+                existing.clone(),
+                required.clone(),
+            )
+            .with_location(location)),
+        }
+    }
+
+    /// Make `expected` and `found` the same type. A conflict is reported at `location`, and also
+    /// points at wherever the conflicting type was fixed
+    pub fn unify(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        match (self.resolve(expected), self.resolve(found)) {
+            // a number given where something else is expected is reported as the number it
+            // would otherwise be
+            (expected_type, Type::Variable(id)) if self.rejects_default(id, &expected_type) => {
+                Err(self.conflict(expected, found, self.resolve_shape(found), location))
+            }
+            (Type::Variable(id), other) | (other, Type::Variable(id)) => {
+                self.bind(id, other, location)
+            }
+            (Type::Array(expected_element), Type::Array(found_element))
+            | (Type::Generator(expected_element), Type::Generator(found_element)) => {
+                self.unify(&expected_element, &found_element, location)
+            }
+            (
+                Type::Result(expected_value, expected_error),
+                Type::Result(found_value, found_error),
+            ) => {
+                self.unify(&expected_value, &found_value, location)?;
+                self.unify(&expected_error, &found_error, location)
+            }
+            (Type::Tuple(expected_elements), Type::Tuple(found_elements))
+                if expected_elements.len() == found_elements.len() =>
+            {
+                for (expected_element, found_element) in
+                    expected_elements.iter().zip(&found_elements)
+                {
+                    self.unify(expected_element, found_element, location)?;
+                }
+                Ok(())
+            }
+            (
+                Type::Function(expected_parameters, expected_return),
+                Type::Function(found_parameters, found_return),
+            ) if expected_parameters.len() == found_parameters.len() => {
+                for (expected_parameter, found_parameter) in
+                    expected_parameters.iter().zip(&found_parameters)
+                {
+                    self.unify(expected_parameter, found_parameter, location)?;
+                }
+                self.unify(&expected_return, &found_return, location)
+            }
+            (expected_type, found_type) if expected_type == found_type => Ok(()),
+            _ => Err(self.conflict(expected, found, self.resolve_deep(found), location)),
+        }
+    }
+
+    /// Whether the variable `id` has a default type but cannot be bound to `t`, which is known
+    fn rejects_default(&self, id: usize, t: &Type) -> bool {
+        let variable: &TypeVariable = &self.type_variables[id];
+        !t.is_variable()
+            && variable.default.is_some()
+            && variable
+                .constraint
+                .as_ref()
+                .is_some_and(|(constraint, _)| !constraint.accepts(t))
+    }
+
+    /// The error for a value of type `found` given where one of type `expected` is, reported as
+    /// being of type `found_type`. It also points at wherever either type was fixed
+    fn conflict(
+        &self,
+        expected: &Type,
+        found: &Type,
+        found_type: Type,
+        location: &SourceLocation,
+    ) -> CompilerError {
+        let origin: Option<&SourceLocation> = [expected, found]
+            .into_iter()
+            .filter_map(|t| self.origin(t))
+            .find(|origin| *origin != location);
+        let expected_type: Type = self.resolve_deep(expected);
+        let error: CompilerError = match origin {
+            Some(origin) => {
+                CompilerError::TypeConflictError(expected_type, found_type, origin.clone())
+            }
+            None => CompilerError::TypeMismatchError(expected_type, found_type),
+        };
+        error.with_location(location)
+    }
+
+    /// Like `unify`, but also accepts the implicit i64 to f64 widening once both types are known
+    pub fn coerce(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        location: &SourceLocation,
+    ) -> Result<(), CompilerError> {
+        let expected_type: Type = self.resolve(expected);
+        let found_type: Type = self.resolve(found);
+        if !expected_type.is_variable()
+            && !found_type.is_variable()
+            && found_type.can_convert_to(&expected_type)
+        {
+            return Ok(());
+        }
+        self.unify(expected, found, location)
+    }
+
+    /// Whether `t` satisfies `constraint`. A type that is not known yet is constrained from here
+    /// on, with `location` as the reason
+    pub fn require(
+        &mut self,
+        constraint: Constraint,
+        t: &Type,
+        location: &SourceLocation,
+    ) -> Result<bool, CompilerError> {
+        match self.resolve(t) {
+            Type::Variable(id) => match self.type_variables[id].constraint.clone() {
+                None => {
+                    self.type_variables[id].constraint = Some((constraint, location.clone()));
+                    Ok(true)
+                }
+                Some((existing, _)) => {
+                    self.merge_constraints(&existing, &constraint, location)?;
+                    Ok(true)
+                }
+            },
+            concrete => Ok(constraint.accepts(&concrete)),
+        }
+    }
+
+    /// Declare the signature of a function whose body is about to be inferred. Until
+    /// `end_definition` the function is monomorphic, so recursive calls all share its types
+    pub fn begin_definition(&mut self, signature: FunctionSignature) {
+        self.definitions.push(Definition {
+            name: signature.name().to_string(),
+            group: Vec::new(),
+            side_effect: None,
+            calls_itself: false,
+            yield_type: None,
+        });
+        self.declare_function(signature);
+    }
+
+    /// Generalize the signature of the function whose body was just inferred. When it shares
+    /// type variables with a function that is still being inferred the two are mutually
+    /// recursive, and it is generalized along with that function instead
+    pub fn end_definition(&mut self) {
+        let mut group: Vec<String> = match self.definitions.pop() {
+            Some(definition) => {
+                self.check_attributes(&definition);
+                let mut group: Vec<String> = definition.group;
+                group.push(definition.name);
+                group
+            }
+            None => return,
+        };
+
+        let group_variables: HashSet<usize> = group
+            .iter()
+            .filter_map(|name| self.signature(name))
+            .flat_map(|signature| self.free_variables(signature))
+            .collect();
+        let owner: Option<usize> = self.definitions.iter().position(|definition| {
+            self.signature(&definition.name).is_some_and(|signature| {
+                self.free_variables(signature)
+                    .iter()
+                    .any(|id| group_variables.contains(id))
+            })
+        });
+        if let Some(owner) = owner {
+            self.definitions[owner].group.append(&mut group);
+            return;
+        }
+
+        let environment: HashSet<usize> = self.environment_variables(&group);
+        self.apply_defaults(&group, &environment);
+        for name in &group {
+            if let Some(signature) = self.signature(name) {
+                let generalized: FunctionSignature = self.generalize(signature, &environment);
+                self.replace_function(generalized);
+            }
+        }
+    }
+
+    /// Give the variables the return types of `group` still have their default type, unless
+    /// a parameter has the same type. Those are generalized instead, so `def half(x) x / 2`
+    /// works on numbers of either type, while `def one() 1` returns an i64
+    fn apply_defaults(&mut self, group: &[String], environment: &HashSet<usize>) {
+        let mut parameter_variables: HashSet<usize> = environment.clone();
+        let mut return_variables: Vec<usize> = Vec::new();
+        for signature in group.iter().filter_map(|name| self.signature(name)) {
+            for parameter in signature.parameters() {
+                parameter_variables.extend(self.constrained_variables(parameter.parameter_type()));
+            }
+            return_variables.extend(self.resolve_deep(signature.return_type()).variables());
+        }
+
+        for id in return_variables {
+            let variable: &mut TypeVariable = &mut self.type_variables[id];
+            if variable.binding.is_none() && !parameter_variables.contains(&id) {
+                variable.binding = variable.default.clone();
+            }
+        }
+    }
+
+    /// Every unbound variable in `t`, along with those in the elements of its iterables
+    fn constrained_variables(&self, t: &Type) -> Vec<usize> {
+        let mut variables: Vec<usize> = self.resolve_deep(t).variables();
+        let mut index: usize = 0;
+        while index < variables.len() {
+            if let Some((Constraint::Iterable(element), _)) =
+                &self.type_variables[variables[index]].constraint
+            {
+                for id in self.resolve_deep(element).variables() {
+                    if !variables.contains(&id) {
+                        variables.push(id);
+                    }
+                }
+            }
+            index += 1;
+        }
+        variables
+    }
+
+    /// Record what the body just inferred does on the function's signature, and check it does
+    /// what the function's attributes ask of it
+    fn check_attributes(&mut self, definition: &Definition) {
+        let signature: FunctionSignature = match self.signature(&definition.name) {
+            Some(signature) => signature.clone(),
+            None => return,
+        };
+
+        if let Some(callee) = &definition.side_effect {
+            let purity = signature
+                .attributes()
+                .iter()
+                .find(|attribute| attribute.kind().requires_purity());
+            if let Some(attribute) = purity {
+                self.errors.push(
+                    CompilerError::SideEffectError(
+                        definition.name.clone(),
+                        attribute.kind().name(),
+                        callee.clone(),
+                    )
+                    .with_location(attribute.location()),
+                );
+            }
+        }
+        if let Some(inline) = signature.attribute(AttributeKind::Inline) {
+            if definition.calls_itself {
+                self.warnings.push(CompilerWarning::new(
+                    WarningKind::RecursiveInline(definition.name.clone()),
+                    inline.location(),
+                ));
+            }
+        }
+        self.replace_function(signature.with_side_effect(definition.side_effect.clone()));
+    }
+
+    /// Turn every type variable of `signature` that does not appear in `environment` into a type
+    /// parameter, numbered after the type parameters the function declares
+    fn generalize(
+        &self,
+        signature: &FunctionSignature,
+        environment: &HashSet<usize>,
+    ) -> FunctionSignature {
+        let resolved: FunctionSignature = signature.map_types(|t, _| self.resolve_deep(t));
+        let mut quantified: Vec<usize> = Vec::new();
+        for id in resolved.types().iter().flat_map(|t| t.variables()) {
+            if !environment.contains(&id) && !quantified.contains(&id) {
+                quantified.push(id);
+            }
+        }
+        // the elements of an iterable are quantified along with it, even when nothing else in
+        // the signature refers to them
+        let mut index: usize = 0;
+        while index < quantified.len() {
+            if let Some((Constraint::Iterable(element), _)) =
+                &self.type_variables[quantified[index]].constraint
+            {
+                for id in self.resolve_deep(element).variables() {
+                    if !environment.contains(&id) && !quantified.contains(&id) {
+                        quantified.push(id);
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        let declared: usize = signature.type_parameters().len();
+        let parameterize = |t: &Type| {
+            t.map_variables(&mut |t| match t {
+                Type::Variable(id) => quantified
+                    .iter()
+                    .position(|quantified_id| quantified_id == id)
+                    .map(|index| Type::Parameter(declared + index)),
+                Type::Generic(name) => signature
+                    .type_parameters()
+                    .iter()
+                    .position(|parameter| parameter.name() == Some(name.as_str()))
+                    .map(Type::Parameter),
+                _ => None,
+            })
+        };
+
+        let mut type_parameters: Vec<TypeParameter> = signature.type_parameters().to_vec();
+        type_parameters.extend(quantified.iter().map(|id| {
+            let constraint: Option<Constraint> =
+                self.type_variables[*id]
+                    .constraint
+                    .as_ref()
+                    .map(|(constraint, _)| {
+                        constraint.map_types(&mut |t| parameterize(&self.resolve_deep(t)))
+                    });
+            TypeParameter::new(constraint)
+        }));
+        resolved
+            .map_types(|t, _| parameterize(t))
+            .with_type_parameters(type_parameters)
+    }
+
+    /// Unbound variables `signature` refers to
+    fn free_variables(&self, signature: &FunctionSignature) -> Vec<usize> {
+        signature
+            .types()
+            .iter()
+            .flat_map(|t| self.resolve_deep(t).variables())
+            .collect()
+    }
+
+    /// Every unbound variable some visible variable or function outside of `excluded` refers to
+    fn environment_variables(&self, excluded: &[String]) -> HashSet<usize> {
+        let mut environment: HashSet<usize> = HashSet::new();
+        for frame in &self.variables {
+            for variable_type in frame.values() {
+                environment.extend(self.resolve_deep(variable_type).variables());
+            }
+        }
+        for frame in &self.functions {
+            for signature in frame.values() {
+                if !excluded.iter().any(|name| name == signature.name()) {
+                    environment.extend(self.free_variables(signature));
+                }
+            }
+        }
+        environment
+    }
+
+    /// Overwrite the innermost declaration of a function with the same name as `signature`
+    fn replace_function(&mut self, signature: FunctionSignature) {
+        if let Some(frame) = self
+            .functions
+            .iter_mut()
+            .rev()
+            .find(|frame| frame.contains_key(signature.name()))
+        {
+            frame.insert(signature.name().to_string(), signature);
+        }
+    }
+
+    /// Replace every type parameter of `signature` with a fresh variable. Parameter types that are
+    /// not variables are wrapped in one, so a conflicting argument also points at the declaration
+    /// the type came from
+    fn instantiate(&mut self, signature: &FunctionSignature) -> FunctionSignature {
+        let fresh: Vec<Type> = signature
+            .type_parameters()
+            .iter()
+            .map(|_| self.fresh_variable())
+            .collect();
+        let instantiate = |t: &Type| {
+            t.map_variables(&mut |t| match t {
+                Type::Parameter(index) => fresh.get(*index).cloned(),
+                _ => None,
+            })
+        };
+        for (type_parameter, variable) in signature.type_parameters().iter().zip(&fresh) {
+            if let (Some(constraint), Type::Variable(id)) = (type_parameter.constraint(), variable)
+            {
+                self.type_variables[*id].constraint = Some((
+                    constraint.map_types(&mut |t| instantiate(t)),
+                    signature.location().clone(),
+                ));
+            }
+        }
+
+        signature
+            .map_types(|t, location| {
+                let instance: Type = instantiate(t);
+                match instance {
+                    Type::Variable(_) => instance,
+                    t => self.fixed_variable(t, location),
+                }
+            })
+            .with_type_parameters(Vec::new())
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use common::{error::CompilerError, token::SimpleBinaryOperater, units::Unit};
+
+use crate::ast_nodes::functions::function_definition::Function;
+
+/// What the units pass knows about the unit of measure of a value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measure {
+    /// Nothing is known, as for a parameter or the value a call through a function value
+    /// returns, so any unit is accepted wherever the value is used
+    Unknown,
+    /// A number measured in the given unit. Numbers written without a unit are dimensionless
+    Unit(Unit),
+    Tuple(Vec<Measure>),
+}
+
+impl Measure {
+    pub fn dimensionless() -> Self {
+        Measure::Unit(Unit::dimensionless())
+    }
+
+    /// The measure of a value that has to be in the same unit as both `self` and `other`, or
+    /// the two units that disagree
+    pub fn agree(&self, other: &Measure) -> Result<Measure, (Unit, Unit)> {
+        match (self, other) {
+            (Measure::Unknown, known) | (known, Measure::Unknown) => Ok(known.clone()),
+            (Measure::Unit(left), Measure::Unit(right)) if left == right => Ok(self.clone()),
+            (Measure::Unit(left), Measure::Unit(right)) => Err((left.clone(), right.clone())),
+            (Measure::Tuple(left), Measure::Tuple(right)) if left.len() == right.len() => left
+                .iter()
+                .zip(right)
+                .map(|(left, right)| left.agree(right))
+                .collect::<Result<Vec<Measure>, (Unit, Unit)>>()
+                .map(Measure::Tuple),
+            // the type checker reports values of different shapes
+            _ => Ok(Measure::Unknown),
+        }
+    }
+
+    /// The measure of the result of a binary operator. Adding, subtracting and comparing
+    /// values needs them to be in the same unit, while multiplying and dividing them derives a
+    /// new unit
+    pub fn binary(
+        operator: &SimpleBinaryOperater,
+        left: &Measure,
+        right: &Measure,
+    ) -> Result<Measure, CompilerError> {
+        let mismatch = |(left, right): (Unit, Unit)| {
+            CompilerError::UnitMismatchError(operator.symbol(), left, right)
+        };
+
+        match (operator, left, right) {
+            (SimpleBinaryOperater::Addition | SimpleBinaryOperater::Subtraction, left, right) => {
+                left.agree(right).map_err(mismatch)
+            }
+            (SimpleBinaryOperater::Multiplication, Measure::Unit(l), Measure::Unit(r)) => {
+                Ok(Measure::Unit(l.multiply(r)?))
+            }
+            (SimpleBinaryOperater::Division, Measure::Unit(l), Measure::Unit(r)) => {
+                Ok(Measure::Unit(l.divide(r)?))
+            }
+            (operator, left, right) if operator.is_comparison() => {
+                left.agree(right).map_err(mismatch)?;
+                Ok(Measure::Unknown)
+            }
+            _ => Ok(Measure::Unknown),
+        }
+    }
+}
+
+/// A stack of lexical frames mapping variables to what is known of their unit of measure, for
+/// the pass checking units are used consistently. Module constants live in the outermost frame
+pub struct UnitContext<'a> {
+    frames: Vec<HashMap<String, Measure>>,
+    /// The functions of the module, whose bodies are checked again for every call with the
+    /// units of its arguments
+    functions: &'a [Function],
+    /// The functions whose bodies are being checked for a call, innermost last
+    calling: Vec<String>,
+}
+
+impl<'a> UnitContext<'a> {
+    pub fn new() -> Self {
+        Self {
+            frames: vec![HashMap::new()],
+            functions: &[],
+            calling: Vec::new(),
+        }
+    }
+
+    /// The same context, working out what calls to `functions` give
+    pub fn with_functions(mut self, functions: &'a [Function]) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// The context the body of a function defined here is checked in. Definitions cannot
+    /// capture variables, so only the constants carry over
+    pub fn definition_context(&self) -> UnitContext<'a> {
+        Self {
+            frames: vec![self.frames[0].clone()],
+            functions: self.functions,
+            calling: self.calling.clone(),
+        }
+    }
+
+    /// The module function a call to `name` goes to, unless a variable of that name hides it
+    pub fn function(&self, name: &str) -> Option<&'a Function> {
+        if self.frames.iter().any(|frame| frame.contains_key(name)) {
+            return None;
+        }
+        self.top_level_function(name)
+    }
+
+    /// The module function named `name`, whatever variable hides it
+    pub fn top_level_function(&self, name: &str) -> Option<&'a Function> {
+        self.functions
+            .iter()
+            .find(|function| function.prototype().name() == name)
+    }
+
+    /// What is known of the unit of what `function` returns when its parameters are in the
+    /// units of `parameters`. Its body is checked again with them, which finds the arguments
+    /// it cannot be used with. A function called from its own body returns a value in any unit
+    pub fn call(
+        &mut self,
+        function: &'a Function,
+        parameters: &[Measure],
+    ) -> Result<Measure, CompilerError> {
+        let name: &str = function.prototype().name();
+        if self.calling.iter().any(|calling| calling == name) {
+            return Ok(Measure::Unknown);
+        }
+
+        let mut context: UnitContext = self.definition_context();
+        context.calling.push(name.to_string());
+        context.push_frame();
+        for (parameter, measure) in function.prototype().args().iter().zip(parameters) {
+            context.declare(parameter.name(), measure.clone());
+        }
+        match function.body().check_units(&mut context) {
+            Ok(measure) => Ok(measure),
+            // a body that is inconsistent whatever it is called with was already reported
+            Err(_)
+                if parameters
+                    .iter()
+                    .all(|measure| *measure == Measure::Unknown) =>
+            {
+                Ok(Measure::Unknown)
+            }
+            Err(e) => match self.call(function, &vec![Measure::Unknown; parameters.len()]) {
+                Ok(_) => Err(e),
+                Err(_) => Ok(Measure::Unknown),
+            },
+        }
+    }
+
+    pub fn push_frame(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub fn declare(&mut self, name: &str, measure: Measure) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.insert(name.to_string(), measure);
+        }
+    }
+
+    /// What is known of the unit of `name`, nothing for functions and anything else the
+    /// context does not track
+    pub fn lookup(&self, name: &str) -> Measure {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(name).cloned())
+            .unwrap_or(Measure::Unknown)
+    }
+
+    /// What is known of the unit of the module constant `name`, whatever variable hides it
+    pub fn lookup_top_level(&self, name: &str) -> Measure {
+        self.frames[0]
+            .get(name)
+            .cloned()
+            .unwrap_or(Measure::Unknown)
+    }
+}
+
+impl Default for UnitContext<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}